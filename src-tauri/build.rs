@@ -1,3 +1,14 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
 fn main() {
+    // Reported by the version_info command. This is when the build script last ran;
+    // tauri_build limits reruns to its own inputs, so source changes are added here too.
+    println!("cargo:rerun-if-changed=src");
+    let build_time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    println!("cargo:rustc-env=BUILD_TIMESTAMP={}", build_time);
+
     tauri_build::build()
 }
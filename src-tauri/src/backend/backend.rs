@@ -1,25 +1,123 @@
 use crate::backend::batch_processing::BatchProcessor;
-use crate::backend::file_processor::{ FileProcessor, ProcessedFileData };
-use crate::backend::interim_reports::InterimReportGenerator;
+use crate::backend::combined_report::{ save_combined_report, SiteData };
+use crate::backend::site_config::SiteConfig;
+use crate::backend::file_processor::{
+    FileProcessor,
+    GapFillStrategy,
+    IntervalChange,
+    INFILLED_COLUMN,
+    NAIVE_TIME_BASIS,
+    ProcessedFileData,
+    ProcessingOptions,
+    ProgressCallback,
+    SUPPORTED_FILE_FORMATS,
+};
+use crate::backend::interim_reports::{
+    DEFAULT_REPORT_DECIMALS,
+    InterimReportGenerator,
+    InterimReportOptions,
+    MonitorType,
+    ReportPeriod,
+};
+use crate::calculations::calculator_factory::{ create_calculator, SUPPORTED_PIPE_SHAPES };
 use crate::calculations::r3_calculator::r3_calculator;
-use crate::fdv::fdv_creator::FDVFlowCreator;
-use crate::fdv::rainfall_creator::FDVRainfallCreator;
+use crate::fdv::antecedent::{ antecedent_rainfall, ANTECEDENT_DAYS };
+use crate::fdv::column_unit::{ is_depth_in_mm, split_unit };
+use crate::fdv::data_status::DataStatus;
+use crate::fdv::fdv_creator::{ FDVFlowCreator, FlowUnit, MissingVelocity, OutputStyle };
+use crate::fdv::fdv_reader::FdvReader;
+use crate::fdv::overwrite::OverwritePolicy;
+use crate::fdv::rainfall_creator::{
+    FDVRainfallCreator,
+    RainfallInputMode,
+    RainfallUnit,
+    DEFAULT_TIP_RESOLUTION,
+};
 use crate::utils::logger::clear_logs;
-use chrono::Duration;
+use chrono::{ DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike };
 use polars::prelude::*;
-use rust_xlsxwriter::{ Workbook, Worksheet };
+use rayon::prelude::*;
+use rust_xlsxwriter::{ ExcelDateTime, Format, Workbook, Worksheet };
 use serde_json::{ json, Value };
-use std::collections::HashMap;
+use sha2::{ Digest, Sha256 };
+use std::collections::{ BTreeMap, HashMap };
 use std::error::Error;
+use std::fs::{ self, File };
+use std::io;
 use std::option::Option;
 use std::path::{ Path, PathBuf };
 use std::time::Instant;
+use zip::write::{ FileOptions, ZipWriter };
+use zip::CompressionMethod;
+
+const DEFAULT_MAX_PLOT_POINTS: usize = 5000;
+const DEFAULT_FLATLINE_RUN: usize = 12;
+const MAX_PLAUSIBLE_VELOCITY: f64 = 10.0;
+const DEFAULT_DWF_WINDOW: (&str, &str) = ("03:00", "05:00");
+/// Days whose mean flow exceeds the median daily mean by more than this ratio are treated
+/// as wet when estimating DWF without an explicit list of dry days.
+const WET_DAY_RATIO: f64 = 1.25;
+
+/// Per day: the sum and count of all flows, and the minimum within the night window.
+type DailyNightFlow = BTreeMap<NaiveDate, (f64, usize, Option<f64>)>;
+
+/// Drift below this fraction of the interval is within clock resolution: at most a fixed
+/// offset, which `shift_timestamps` corrects.
+const DRIFT_TOLERANCE_FRACTION: f64 = 0.1;
+/// R² above which cumulative drift is treated as linear in elapsed time.
+const LINEAR_DRIFT_R2: f64 = 0.9;
+/// Annual maxima needed before Gumbel return-period flows are estimated.
+const MIN_EXTREME_YEARS: usize = 5;
+/// Coverage (%) a year needs for its maximum to count towards the return-period flows.
+const MIN_EXTREME_COVERAGE_PERCENT: f64 = 80.0;
+/// Return periods (years) estimated from the annual maximum series.
+const RETURN_PERIODS: [f64; 3] = [2.0, 5.0, 10.0];
+/// Share of readings at or above the crown beyond which the entered pipe size is more
+/// likely wrong than the pipe surcharging.
+const WRONG_SIZE_FULL_FRACTION: f64 = 0.25;
+/// Peak depth below this fraction of the pipe size suggests the size (or its units) is wrong.
+const MIN_PEAK_DEPTH_FRACTION: f64 = 0.05;
+
+/// Counts and choices reported back from one FDV flow conversion.
+struct FlowRunSummary {
+    /// File written, which differs from the requested path after an overwrite rename.
+    output_path: String,
+    /// SHA-256 of the file written, when checksum files are enabled.
+    sha256: Option<String>,
+    depth_null: usize,
+    velocity_null: usize,
+    quality_suppressed: usize,
+    velocity_source: &'static str,
+    data_status: DataStatus,
+    forward_volume: f64,
+    reverse_volume: f64,
+    padding_readings: usize,
+    no_data_readings: usize,
+}
+
+impl FlowRunSummary {
+    /// Net and gross volume in m³; they only differ when signed flow records reverse flow.
+    fn volume_json(&self) -> Value {
+        json!({
+            "net": self.forward_volume - self.reverse_volume,
+            "gross": self.forward_volume + self.reverse_volume,
+            "forward": self.forward_volume,
+            "reverse": self.reverse_volume,
+        })
+    }
+}
 
 pub struct CommandHandler {
     filepath: PathBuf,
     site_id: String,
     site_name: String,
     pub(crate) data_frame: Option<DataFrame>,
+    /// The DataFrame as loaded, before any trim, so readings ahead of a trimmed record
+    /// remain available for antecedent rainfall.
+    source_data_frame: Option<DataFrame>,
+    /// The readings as parsed, with inserted gap rows empty and flagged in the infilled
+    /// column. Only timestamps and column names follow later edits.
+    raw_data_frame: Option<DataFrame>,
     start_timestamp: String,
     end_timestamp: String,
     pub(crate) column_mapping: HashMap<
@@ -29,7 +127,32 @@ pub struct CommandHandler {
     pub(crate) monitor_type: String,
     pub(crate) interval: Duration,
     gaps: usize,
+    invalid_timestamps: usize,
     pub(crate) time_col: Option<String>,
+    time_basis: String,
+    progress_callback: Option<ProgressCallback>,
+    pub(crate) rainfall_unit: RainfallUnit,
+    /// Gauge tip resolution in mm, used whenever rainfall is read as tip counts.
+    tip_resolution_mm: f64,
+    interval_changes: Vec<IntervalChange>,
+    data_status: DataStatus,
+    quality_col: Option<String>,
+    suppress_bad_quality: bool,
+    datum_offset_m: f64,
+    clock_shift_seconds: i64,
+    trimmed_rows: usize,
+    flow_output_style: OutputStyle,
+    flow_unit: FlowUnit,
+    flow_no_data_value: Option<f64>,
+    overwrite_policy: OverwritePolicy,
+    write_checksums: bool,
+    fdv_comments: Vec<String>,
+    missing_velocity: MissingVelocity,
+    timestamp_format: String,
+    conversions: Vec<Value>,
+    extra_timestamp_formats: Vec<String>,
+    pub(crate) signed_flow: bool,
+    pad_to_full_days: bool,
 }
 
 impl CommandHandler {
@@ -39,21 +162,170 @@ impl CommandHandler {
             site_id: String::new(),
             site_name: String::new(),
             data_frame: None,
+            source_data_frame: None,
+            raw_data_frame: None,
             start_timestamp: String::new(),
             end_timestamp: String::new(),
             column_mapping: HashMap::new(),
             monitor_type: String::new(),
             interval: Duration::seconds(0),
             gaps: 0,
+            invalid_timestamps: 0,
             time_col: None,
+            time_basis: String::new(),
+            progress_callback: None,
+            rainfall_unit: RainfallUnit::default(),
+            tip_resolution_mm: DEFAULT_TIP_RESOLUTION,
+            interval_changes: Vec::new(),
+            data_status: DataStatus::Raw,
+            quality_col: None,
+            suppress_bad_quality: false,
+            datum_offset_m: 0.0,
+            clock_shift_seconds: 0,
+            trimmed_rows: 0,
+            flow_output_style: OutputStyle::default(),
+            flow_unit: FlowUnit::default(),
+            flow_no_data_value: None,
+            overwrite_policy: OverwritePolicy::default(),
+            write_checksums: false,
+            fdv_comments: Vec::new(),
+            missing_velocity: MissingVelocity::default(),
+            timestamp_format: String::new(),
+            conversions: Vec::new(),
+            extra_timestamp_formats: Vec::new(),
+            signed_flow: false,
+            pad_to_full_days: false,
         }
     }
 
+    pub fn set_progress_callback(&mut self, progress_callback: Option<ProgressCallback>) {
+        self.progress_callback = progress_callback;
+    }
+
     pub fn process_file(&mut self, file_path: &str) -> Result<String, String> {
-        self.filepath = PathBuf::from(file_path);
-        let mut file_processor: FileProcessor = FileProcessor::new(None);
+        let file_processor = Self::new_file_processor(&self.extra_timestamp_formats);
+        self.run_file_processor(file_path, file_processor, None)
+    }
+
+    /// A `FileProcessor` that also recognises the formats added with `add_timestamp_format`.
+    fn new_file_processor(extra_formats: &[String]) -> FileProcessor {
+        let mut file_processor = FileProcessor::new(None);
+        for format in extra_formats {
+            // Validated when added
+            let _ = file_processor.add_timestamp_format(format);
+        }
+        file_processor
+    }
+
+    /// Lists the timestamp formats tried during detection, including any added at runtime.
+    pub fn supported_timestamp_formats(&self) -> Result<String, String> {
+        let file_processor = Self::new_file_processor(&self.extra_timestamp_formats);
+        let result =
+            json!({
+            "success": true,
+            "formats": file_processor.supported_timestamp_formats(),
+        });
+        Ok(result.to_string())
+    }
+
+    /// Version and capabilities of the converter, for support tickets and the about dialog.
+    pub fn version_info(&self) -> Result<String, String> {
+        let build_timestamp = env!("BUILD_TIMESTAMP")
+            .parse::<i64>()
+            .ok()
+            .and_then(|secs| DateTime::from_timestamp(secs, 0))
+            .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string());
+        let file_processor = Self::new_file_processor(&self.extra_timestamp_formats);
+
+        let result =
+            json!({
+            "success": true,
+            "name": env!("CARGO_PKG_NAME"),
+            "version": env!("CARGO_PKG_VERSION"),
+            "buildTimestamp": build_timestamp,
+            "fileFormats": SUPPORTED_FILE_FORMATS,
+            "timestampFormats": file_processor.supported_timestamp_formats(),
+            "pipeShapes": SUPPORTED_PIPE_SHAPES,
+            "outputStyles": [OutputStyle::FixedWidth, OutputStyle::Comma, OutputStyle::Tab]
+                .iter()
+                .map(OutputStyle::as_str)
+                .collect::<Vec<_>>(),
+            "flowUnits": [FlowUnit::LitresPerSecond, FlowUnit::CubicMetresPerSecond]
+                .iter()
+                .map(FlowUnit::as_str)
+                .collect::<Vec<_>>(),
+        });
+        Ok(result.to_string())
+    }
+
+    /// Adds a chrono format string to the detection list used by later processing runs.
+    pub fn add_timestamp_format(&mut self, format: &str) -> Result<String, String> {
+        let mut file_processor = Self::new_file_processor(&self.extra_timestamp_formats);
+        let added = file_processor
+            .add_timestamp_format(format)
+            .map_err(|e| format!("Error adding timestamp format: {}", e))?;
+        if added {
+            self.extra_timestamp_formats.push(format.trim().to_string());
+            log::info!("Added timestamp format: {}", format.trim());
+        }
+
+        let result =
+            json!({
+            "success": true,
+            "added": added,
+            "formats": file_processor.supported_timestamp_formats(),
+        });
+        Ok(result.to_string())
+    }
+
+    /// Re-runs the full pipeline on the last processed file with user overrides for the
+    /// timestamp format, interval, sheet, delimiter, timezone and rainfall unit.
+    pub fn reprocess_with_options(&mut self, options: Value) -> Result<String, String> {
+        if self.filepath.as_os_str().is_empty() {
+            return Err("No file has been processed yet".to_string());
+        }
+
+        let options: ProcessingOptions = serde_json
+            ::from_value(options)
+            .map_err(|e| format!("Invalid processing options: {}", e))?;
+        let mut file_processor = Self::new_file_processor(&self.extra_timestamp_formats);
+        file_processor
+            .apply_options(&options)
+            .map_err(|e| format!("Invalid processing options: {}", e))?;
+        let rainfall_unit = options.rainfall_unit
+            .as_deref()
+            .map(|unit| {
+                RainfallUnit::from_str(
+                    unit,
+                    options.tip_resolution.or(Some(self.tip_resolution_mm))
+                )
+            })
+            .transpose()
+            .map_err(|e| format!("Invalid processing options: {}", e))?;
+
+        let file_path = self.filepath.to_string_lossy().to_string();
+        log::info!("Reprocessing {} with options: {:?}", file_path, options);
+        self.run_file_processor(&file_path, file_processor, rainfall_unit)
+    }
+
+    /// Runs the pipeline and only touches handler state once it succeeds, so a failed run
+    /// leaves the previous file fully loaded rather than a mix of old and new fields.
+    fn run_file_processor(
+        &mut self,
+        file_path: &str,
+        mut file_processor: FileProcessor,
+        rainfall_unit: Option<RainfallUnit>
+    ) -> Result<String, String> {
+        file_processor.set_progress_callback(self.progress_callback.clone());
         match file_processor.process_file(&file_path) {
             Ok(processed_data) => {
+                self.filepath = PathBuf::from(file_path);
+                if let Some(rainfall_unit) = rainfall_unit {
+                    self.rainfall_unit = rainfall_unit;
+                    if let RainfallUnit::Tips(resolution) = rainfall_unit {
+                        self.tip_resolution_mm = resolution;
+                    }
+                }
                 self.update_from_processed_data(processed_data);
 
                 let result =
@@ -62,12 +334,17 @@ impl CommandHandler {
                     "message": "File processed successfully",
                     "columnMapping": self.column_mapping,
                     "monitorType": self.monitor_type,
+                    "availableConversions": self.available_conversions(),
                     "startTimestamp": self.start_timestamp,
                     "endTimestamp": self.end_timestamp,
                     "interval": self.interval.num_seconds(),
                     "siteId": self.site_id,
                     "siteName": self.site_name,
                     "gaps": self.gaps,
+                    "invalidTimestamps": self.invalid_timestamps,
+                    "trimmedRows": self.trimmed_rows,
+                    "timeBasis": self.time_basis,
+                    "rainfallUnit": self.rainfall_unit.as_str(),
                 });
 
                 log::info!("File processed successfully.");
@@ -85,6 +362,281 @@ impl CommandHandler {
         }
     }
 
+    /// Processes several files for one site in parallel and loads them as a single record.
+    /// The files must share monitor type, interval, columns and interval alignment. Rows
+    /// are sorted by time, overlapping timestamps keep the reading from the earliest listed
+    /// file, and gaps between files are filled on the shared interval grid. A column read as
+    /// integers in one file and decimals in another is merged as decimals.
+    pub fn process_files(&mut self, paths: Vec<String>) -> Result<String, String> {
+        if paths.is_empty() {
+            return Err("No files to process".to_string());
+        }
+
+        let extra_formats = self.extra_timestamp_formats.clone();
+        let outcomes: Vec<(String, Result<ProcessedFileData, String>)> = paths
+            .into_par_iter()
+            .map(|path| {
+                let processed = Self::new_file_processor(&extra_formats)
+                    .process_file(&path)
+                    .map_err(|e| e.to_string());
+                (path, processed)
+            })
+            .collect();
+
+        let mut files = Vec::new();
+        let mut processed = Vec::new();
+        for (path, outcome) in outcomes {
+            match outcome {
+                Ok(data) => {
+                    files.push(
+                        json!({
+                        "path": path,
+                        "success": true,
+                        "rowCount": data.df.height(),
+                        "startTimestamp": data.start_timestamp,
+                        "endTimestamp": data.end_timestamp,
+                        "gaps": data.gaps_filled,
+                    })
+                    );
+                    processed.push((path, data));
+                }
+                Err(e) => {
+                    log::warn!("Error processing {}: {}", path, e);
+                    files.push(json!({ "path": path, "success": false, "error": e }));
+                }
+            }
+        }
+
+        let merged = Self::merge_processed(&processed).map_err(|e| {
+            format!("Error merging files: {}. Per-file results: {}", e, Value::from(files.clone()))
+        })?;
+        let (df, raw_df, gaps_added, duplicates_removed) = merged;
+
+        let interval_changes = processed
+            .iter()
+            .flat_map(|(_, data)| data.interval_changes.clone())
+            .collect();
+        let gaps_filled = processed.iter().map(|(_, data)| data.gaps_filled).sum::<usize>();
+        let invalid_timestamps = processed
+            .iter()
+            .map(|(_, data)| data.invalid_timestamps)
+            .sum();
+        let trimmed_rows = processed.iter().map(|(_, data)| data.trimmed_rows).sum();
+        let (first_path, first) = processed.swap_remove(0);
+        let time_col = first.column_mapping
+            .get("timestamp")
+            .and_then(|v| v.first())
+            .map(|(name, _, _, _)| name.clone())
+            .unwrap_or_default();
+        let timestamps: Vec<NaiveDateTime> = df
+            .column(&time_col)
+            .and_then(|s| s.datetime().map(|dt| dt.as_datetime_iter().flatten().collect()))
+            .map_err(|e| format!("Error reading timestamp column: {}", e))?;
+        let format = |ts: Option<&NaiveDateTime>| {
+            ts.map(|ts| ts.format("%Y-%m-%d %H:%M:%S").to_string()).unwrap_or_default()
+        };
+
+        self.update_from_processed_data(ProcessedFileData {
+            start_timestamp: format(timestamps.first()),
+            end_timestamp: format(timestamps.last()),
+            df,
+            raw_df,
+            gaps_filled: gaps_filled + gaps_added,
+            invalid_timestamps,
+            interval_changes,
+            trimmed_rows,
+            ..first
+        });
+        self.filepath = PathBuf::from(first_path);
+
+        let result =
+            json!({
+            "success": true,
+            "message": "Files processed successfully",
+            "files": files,
+            "columnMapping": self.column_mapping,
+            "monitorType": self.monitor_type,
+            "availableConversions": self.available_conversions(),
+            "startTimestamp": self.start_timestamp,
+            "endTimestamp": self.end_timestamp,
+            "interval": self.interval.num_seconds(),
+            "siteId": self.site_id,
+            "siteName": self.site_name,
+            "gaps": self.gaps,
+            "duplicatesRemoved": duplicates_removed,
+            "rowCount": timestamps.len(),
+        });
+
+        log::info!(
+            "Processed {} files. Range: {} to {}",
+            files.len(),
+            self.start_timestamp,
+            self.end_timestamp
+        );
+        Ok(result.to_string())
+    }
+
+    /// Stacks the processed files, drops repeated timestamps and re-grids the result on the
+    /// shared interval. Returns the merged DataFrame, the rows inserted between files and
+    /// the duplicate rows removed.
+    fn merge_processed(
+        processed: &[(String, ProcessedFileData)]
+    ) -> Result<(DataFrame, DataFrame, usize, usize), String> {
+        let (first_path, first) = processed.first().ok_or("None of the files could be processed")?;
+        let time_col = first.column_mapping
+            .get("timestamp")
+            .and_then(|v| v.first())
+            .map(|(name, _, _, _)| name.clone())
+            .ok_or("No timestamp column identified")?;
+        let columns: Vec<String> = first.df
+            .get_column_names()
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        let parse = |ts: &str| {
+            NaiveDateTime::parse_from_str(ts, "%Y-%m-%d %H:%M:%S").map_err(|e| e.to_string())
+        };
+        let first_start = parse(&first.start_timestamp)?;
+        let raw_columns: Vec<String> = first.raw_df
+            .get_column_names()
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+
+        let mut dtypes: HashMap<String, DataType> = first.df
+            .get_columns()
+            .iter()
+            .map(|s| (s.name().to_string(), s.dtype().clone()))
+            .collect();
+        let mut frames = vec![(first.df.clone(), first.raw_df.clone())];
+        for (path, data) in &processed[1..] {
+            if data.monitor_type != first.monitor_type {
+                return Err(
+                    format!(
+                        "{} is a {} file but {} is {}",
+                        path,
+                        data.monitor_type,
+                        first_path,
+                        first.monitor_type
+                    )
+                );
+            }
+            if data.interval != first.interval {
+                return Err(
+                    format!(
+                        "{} has a {}s interval but {} has {}s",
+                        path,
+                        data.interval.num_seconds(),
+                        first_path,
+                        first.interval.num_seconds()
+                    )
+                );
+            }
+            let offset = parse(&data.start_timestamp)? - first_start;
+            if offset.num_seconds() % first.interval.num_seconds() != 0 {
+                return Err(format!("{} readings are not aligned with {}", path, first_path));
+            }
+            let df = data.df
+                .select(&columns)
+                .ok()
+                .filter(|df| df.width() == data.df.width())
+                .ok_or_else(|| {
+                    format!("{} does not have the same columns as {}", path, first_path)
+                })?;
+            for s in df.get_columns() {
+                let Some(dtype) = dtypes.get_mut(s.name().as_str()) else {
+                    continue;
+                };
+                if dtype == s.dtype() {
+                    continue;
+                }
+                if !(dtype.is_numeric() && s.dtype().is_numeric()) {
+                    return Err(
+                        format!(
+                            "Column '{}' is {} in {} but {} in {}",
+                            s.name(),
+                            s.dtype(),
+                            path,
+                            dtype,
+                            first_path
+                        )
+                    );
+                }
+                *dtype = DataType::Float64;
+            }
+            let raw = data.raw_df.select(&raw_columns).map_err(|e| e.to_string())?;
+            frames.push((df, raw));
+        }
+
+        let cast = |df: &DataFrame| {
+            df.get_columns()
+                .iter()
+                .map(|s| {
+                    match dtypes.get(s.name().as_str()) {
+                        Some(dtype) if dtype != s.dtype() => s.cast(dtype),
+                        _ => Ok(s.clone()),
+                    }
+                })
+                .collect::<PolarsResult<Vec<Series>>>()
+                .and_then(DataFrame::new)
+                .map_err(|e| e.to_string())
+        };
+        let (mut merged, mut merged_raw) = (cast(&frames[0].0)?, cast(&frames[0].1)?);
+        for (df, raw) in &frames[1..] {
+            merged.vstack_mut(&cast(df)?).map_err(|e| e.to_string())?;
+            merged_raw.vstack_mut(&cast(raw)?).map_err(|e| e.to_string())?;
+        }
+
+        let sort = |df: DataFrame| {
+            df
+                .sort([time_col.as_str()], SortMultipleOptions::new().with_maintain_order(true))
+                .map_err(|e| e.to_string())
+        };
+        let sorted = sort(merged)?;
+        let timestamps: Vec<Option<NaiveDateTime>> = sorted
+            .column(&time_col)
+            .and_then(|s| s.datetime().map(|dt| dt.as_datetime_iter().collect()))
+            .map_err(|e| e.to_string())?;
+        let keep: BooleanChunked = (0..timestamps.len())
+            .map(|i| i == 0 || timestamps[i] != timestamps[i - 1])
+            .collect();
+        let deduped = sorted.filter(&keep).map_err(|e| e.to_string())?;
+        let duplicates_removed = sorted.height() - deduped.height();
+        // Same rows in the same order, so the same mask keeps the matching raw readings
+        let deduped_raw = sort(merged_raw)?.filter(&keep).map_err(|e| e.to_string())?;
+
+        let (Some(&Some(start)), Some(&Some(end))) = (timestamps.first(), timestamps.last()) else {
+            return Err("No valid timestamps found".to_string());
+        };
+        let grid: Vec<NaiveDateTime> = std::iter
+            ::successors(Some(start), |ts| Some(*ts + first.interval))
+            .take_while(|ts| *ts <= end)
+            .collect();
+        let grid = Series::new(time_col.as_str().into(), grid)
+            .cast(deduped.column(&time_col).map_err(|e| e.to_string())?.dtype())
+            .map_err(|e| e.to_string())?;
+        let gaps_added = grid.len() - deduped.height();
+
+        let regrid = |deduped: DataFrame, columns: &[String]| {
+            let mut regridded = DataFrame::new(vec![grid.clone()])?
+                .lazy()
+                .join(
+                    deduped.lazy(),
+                    [col(&time_col)],
+                    [col(&time_col)],
+                    JoinArgs::new(JoinType::Left)
+                );
+            if columns.iter().any(|c| c == INFILLED_COLUMN) {
+                regridded = regridded.with_column(col(INFILLED_COLUMN).fill_null(lit(true)));
+            }
+            regridded.collect().and_then(|df| df.select(columns))
+        };
+        let df = regrid(deduped, &columns).map_err(|e| e.to_string())?;
+        let raw_df = regrid(deduped_raw, &raw_columns).map_err(|e| e.to_string())?;
+
+        Ok((df, raw_df, gaps_added, duplicates_removed))
+    }
+
     fn format_timestamp(&self, timestamp: &str) -> Result<String, String> {
         // Parse the input timestamp
         let dt = chrono::NaiveDateTime
@@ -141,16 +693,286 @@ impl CommandHandler {
         }
     }
 
+    /// Reports what `update_timestamps` would keep for the proposed range without applying
+    /// it: the row count, the gaps left in the range and the interval recomputed from it.
+    /// Gaps are taken from the infilled column when present, otherwise from rows in which
+    /// every mapped measurement column is empty.
+    pub fn preview_trim(&self, start_time: &str, end_time: &str) -> Result<String, String> {
+        let formatted_start = self.format_timestamp(start_time)?;
+        let formatted_end = self.format_timestamp(end_time)?;
+
+        let df = self.data_frame.as_ref().ok_or("No data frame available")?;
+        let time_col = self.time_col.as_ref().ok_or("No timestamp column identified")?;
+        let filtered = FileProcessor::filter_range(df, time_col, &formatted_start, &formatted_end)
+            .map_err(|e| format!("Error previewing trim: {}", e))?;
+        let interval = FileProcessor::new(None)
+            .calculate_interval_from_df(&filtered, time_col)
+            .map_err(|e| format!("Error previewing trim: {}", e))?;
+
+        let gaps = self
+            .infilled_mask(&filtered)?
+            .into_iter()
+            .filter(|&infilled| infilled)
+            .count();
+
+        let result =
+            json!({
+            "success": true,
+            "startTimestamp": formatted_start,
+            "endTimestamp": formatted_end,
+            "rowCount": filtered.height(),
+            "removedRows": df.height() - filtered.height(),
+            "gaps": gaps,
+            "interval": interval.num_seconds(),
+        });
+        Ok(result.to_string())
+    }
+
+    /// Which rows of `df` are gap-filled: the infilled column when present, otherwise rows in
+    /// which every mapped measurement column is empty.
+    fn infilled_mask(&self, df: &DataFrame) -> Result<Vec<bool>, String> {
+        if let Ok(infilled) = df.column(INFILLED_COLUMN).and_then(|c| c.bool()) {
+            return Ok(
+                infilled
+                    .into_iter()
+                    .map(|v| v == Some(true))
+                    .collect()
+            );
+        }
+        let mut value_columns = Vec::new();
+        for (key, columns) in &self.column_mapping {
+            if key != "timestamp" {
+                for (name, _, _, _) in columns {
+                    let values = df
+                        .column(name)
+                        .and_then(|s| s.cast(&DataType::Float64))
+                        .map_err(|e| format!("Error reading column '{}': {}", name, e))?;
+                    value_columns.push(values);
+                }
+            }
+        }
+        Ok(
+            (0..df.height())
+                .map(|row| {
+                    !value_columns.is_empty() &&
+                        value_columns.iter().all(|values| {
+                            values
+                                .f64()
+                                .ok()
+                                .and_then(|ca| ca.get(row))
+                                .is_none_or(|v| v.is_nan())
+                        })
+                })
+                .collect()
+        )
+    }
+
+    /// Audit table of the gap filling: per timestamp whether the row was infilled, then for
+    /// each mapped column the reading as parsed (blank where infilled or missing) and the
+    /// final value the conversions write, with missing readings as 0. Gap-fill estimates and
+    /// edits such as datum offsets show as differences between the two.
+    pub(crate) fn raw_vs_filled(&self) -> Result<DataFrame, String> {
+        let df = self.data_frame.as_ref().ok_or("No data frame available")?;
+        let raw_df = self.raw_data_frame.as_ref().ok_or("No data frame available")?;
+        let time_col = self.time_col.as_ref().ok_or("No timestamp column identified")?;
+        let columns: Vec<String> = self
+            .mapped_value_columns()?
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+
+        let mut raw = vec![col(time_col), col(INFILLED_COLUMN)];
+        let mut filled = vec![col(time_col)];
+        let mut order = vec![col(time_col), col(INFILLED_COLUMN).fill_null(lit(true))];
+        for column in &columns {
+            let raw_name = format!("{} Raw", column);
+            let filled_name = format!("{} Filled", column);
+            raw.push(col(column).cast(DataType::Float64).alias(raw_name.as_str()));
+            filled.push(
+                col(column)
+                    .cast(DataType::Float64)
+                    .fill_nan(lit(0.0))
+                    .fill_null(lit(0.0))
+                    .alias(filled_name.as_str())
+            );
+            order.push(col(&raw_name));
+            order.push(col(&filled_name));
+        }
+
+        df.clone()
+            .lazy()
+            .select(filled)
+            .join(
+                raw_df.clone().lazy().select(raw),
+                [col(time_col)],
+                [col(time_col)],
+                JoinArgs::new(JoinType::Left)
+            )
+            .select(order)
+            .collect()
+            .map_err(|e| format!("Error building raw vs filled report: {}", e))
+    }
+
+    /// Writes the `raw_vs_filled` audit table to its own workbook.
+    pub fn save_raw_vs_filled(&self, output_path: &str) -> Result<String, String> {
+        let report = self.raw_vs_filled()?;
+        let mut workbook = Workbook::new();
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name("Raw vs Filled").map_err(|e| e.to_string())?;
+        Self::write_df_to_worksheet(&report, worksheet).map_err(|e| e.to_string())?;
+        workbook
+            .save(output_path)
+            .map_err(|e| format!("Error saving raw vs filled report: {}", e))?;
+
+        log::info!("Raw vs filled report saved to {}", output_path);
+
+        let result =
+            json!({
+            "success": true,
+            "message": format!("Raw vs filled report saved successfully to {}", output_path),
+            "outputPath": output_path,
+            "rowCount": report.height(),
+        });
+        Ok(result.to_string())
+    }
+
+    /// Rainfall depth (mm) in the 0 to 30 days before the record start, as written to the
+    /// rainfall FDV constants. Uses the data as loaded, so a record trimmed to start later
+    /// draws on the readings removed from its front; totals the data doesn't reach back to
+    /// are null.
+    pub fn antecedent_rainfall(&self, rainfall_col: Option<&str>) -> Result<String, String> {
+        let column = match rainfall_col {
+            Some(column) => column.to_string(),
+            None => self.mapped_column("rainfall").ok_or("No rainfall column identified")?,
+        };
+        let df = self.source_data_frame
+            .as_ref()
+            .or(self.data_frame.as_ref())
+            .ok_or("No data frame available")?;
+        let time_col = self.time_col.as_ref().ok_or("No timestamp column identified")?;
+        let start = NaiveDateTime::parse_from_str(&self.start_timestamp, "%Y-%m-%d %H:%M:%S")
+            .map_err(|e| format!("Invalid start timestamp: {}", e))?;
+
+        let timestamps: Vec<Option<NaiveDateTime>> = df
+            .column(time_col)
+            .and_then(|s| s.datetime().map(|dt| dt.as_datetime_iter().collect()))
+            .map_err(|e| e.to_string())?;
+        let values: Vec<Option<f64>> = df
+            .column(&column)
+            .and_then(|s| s.cast(&DataType::Float64))
+            .and_then(|s| Ok(s.f64()?.into_iter().collect()))
+            .map_err(|e| format!("Error reading column '{}': {}", column, e))?;
+        let factor = self.rainfall_unit.depth_factor((self.interval.num_seconds() as f64) / 60.0);
+        let readings: Vec<(NaiveDateTime, Option<f64>)> = timestamps
+            .into_iter()
+            .zip(values)
+            .filter_map(|(timestamp, value)| timestamp.map(|t| (t, value.map(|v| v * factor))))
+            .collect();
+        let totals = antecedent_rainfall(&readings, start);
+
+        let result =
+            json!({
+            "success": true,
+            "column": column,
+            "startTimestamp": self.start_timestamp,
+            "unit": "mm",
+            "days": (0..=ANTECEDENT_DAYS).collect::<Vec<_>>(),
+            "totals": totals,
+            "known": totals.iter().filter(|total| total.is_some()).count(),
+        });
+        Ok(result.to_string())
+    }
+
+    /// Totals flow volume (m3) or rainfall depth (mm) between two timestamps without
+    /// changing the loaded range.
+    pub fn total_over_range(&self, start_time: &str, end_time: &str) -> Result<String, String> {
+        let formatted_start = self.format_timestamp(start_time)?;
+        let formatted_end = self.format_timestamp(end_time)?;
+
+        let (column_key, unit) = match self.monitor_type.as_str() {
+            "Flow" => ("flow", "m3"),
+            "Rainfall" => ("rainfall", "mm"),
+            other => {
+                return Err(
+                    format!(
+                        "Totals are only available for Flow and Rainfall monitors, not {}",
+                        other
+                    )
+                );
+            }
+        };
+        let column = self
+            .mapped_column(column_key)
+            .ok_or_else(|| format!("No {} column identified", column_key))?;
+
+        let df = self.data_frame.as_ref().ok_or("No data frame available")?;
+        let time_col = self.time_col.as_ref().ok_or("No timestamp column identified")?;
+        let filtered = FileProcessor::filter_range(df, time_col, &formatted_start, &formatted_end)
+            .map_err(|e| format!("Error filtering range: {}", e))?;
+
+        let values: Vec<f64> = filtered
+            .column(&column)
+            .and_then(|s| s.cast(&DataType::Float64))
+            .map_err(|e| format!("Error reading column '{}': {}", column, e))?
+            .f64()
+            .map_err(|e| format!("Error reading column '{}': {}", column, e))?
+            .into_iter()
+            .flatten()
+            .filter(|v| !v.is_nan())
+            .collect();
+        let sum: f64 = values.iter().sum();
+        let interval_seconds = self.interval.num_seconds() as f64;
+        let total = if column_key == "flow" {
+            (sum * interval_seconds) / 1000.0
+        } else {
+            sum * self.rainfall_unit.depth_factor(interval_seconds / 60.0)
+        };
+
+        let result =
+            json!({
+            "success": true,
+            "monitorType": self.monitor_type,
+            "column": column,
+            "startTimestamp": formatted_start,
+            "endTimestamp": formatted_end,
+            "total": total,
+            "unit": unit,
+            "rowCount": filtered.height(),
+            "missingReadings": filtered.height() - values.len(),
+        });
+        Ok(result.to_string())
+    }
+
     fn update_from_processed_data(&mut self, processed_data: ProcessedFileData) {
         self.site_id = processed_data.site_id;
         self.site_name = processed_data.site_name;
+        self.source_data_frame = Some(processed_data.df.clone());
         self.data_frame = Some(processed_data.df);
+        self.raw_data_frame = Some(processed_data.raw_df);
         self.start_timestamp = processed_data.start_timestamp;
         self.end_timestamp = processed_data.end_timestamp;
         self.column_mapping = processed_data.column_mapping;
         self.monitor_type = processed_data.monitor_type;
         self.interval = processed_data.interval;
         self.gaps = processed_data.gaps_filled;
+        self.invalid_timestamps = processed_data.invalid_timestamps;
+        self.time_basis = processed_data.time_basis;
+        self.interval_changes = processed_data.interval_changes;
+        // Gap rows given values rather than left empty are estimates
+        self.data_status = if
+            processed_data.gap_fill != GapFillStrategy::Empty &&
+            processed_data.gaps_filled > 0
+        {
+            DataStatus::Estimated
+        } else {
+            DataStatus::Raw
+        };
+        self.quality_col = processed_data.quality_column;
+        self.datum_offset_m = 0.0;
+        self.clock_shift_seconds = 0;
+        self.trimmed_rows = processed_data.trimmed_rows;
+        self.timestamp_format = processed_data.timestamp_format;
+        self.conversions.clear();
         self.time_col = self.column_mapping
             .get("timestamp")
             .and_then(|v| v.first())
@@ -181,140 +1003,2905 @@ impl CommandHandler {
         Ok(result.to_string())
     }
 
-    pub fn reset(&mut self) {
-        *self = CommandHandler::new();
-        clear_logs();
+    /// Reports stretches of the raw record sampled at a different interval from the one the
+    /// data was regularised to. A single-interval FDV misaligns those stretches.
+    pub fn detect_interval_changes(&self) -> Result<String, String> {
+        if self.data_frame.is_none() {
+            return Err("No data frame available".to_string());
+        }
+
+        let suggestion = if self.interval_changes.is_empty() {
+            None
+        } else {
+            Some(
+                "Split the record at these ranges, or resample to a single interval, \
+                 before creating FDV files."
+            )
+        };
+        let result =
+            json!({
+            "success": true,
+            "interval": self.interval.num_seconds(),
+            "hasChanges": !self.interval_changes.is_empty(),
+            "changes": self.interval_changes,
+            "suggestion": suggestion,
+        });
+        Ok(result.to_string())
     }
 
-    pub fn create_fdv_flow(
-        &mut self,
-        output_path: &str,
-        depth_col: &str,
-        velocity_col: &Option<&str>,
-        pipe_shape: &str,
-        pipe_size: &str
-    ) -> Result<String, String> {
-        let df = self.data_frame.as_ref().ok_or("No data frame available")?;
-        // Create a new FDVFlowCreator
-        let mut fdv_creator = FDVFlowCreator::new();
+    /// Records that the loaded data was edited or estimated. Called by operations that change
+    /// readings so FDV headers declare the data as no longer raw; never downgrades.
+    pub(crate) fn mark_data_status(&mut self, data_status: DataStatus) {
+        self.data_status = self.data_status.max(data_status);
+    }
 
-        // Set up column names
-        let mut col_names = HashMap::new();
-        col_names.insert("timestamp".to_string(), self.time_col.clone().unwrap_or_default());
-        col_names.insert("depth".to_string(), depth_col.to_string());
-        if let Some(vel_col) = velocity_col {
-            col_names.insert("velocity".to_string(), vel_col.to_string());
-        }
+    /// Manually sets the status written to FDV headers, overriding the tracked one.
+    pub fn set_data_status(&mut self, data_status: &str) -> Result<String, String> {
+        self.data_status = DataStatus::from_str(data_status)?;
+        log::info!("Data status set to {}", self.data_status.as_str());
 
-        fdv_creator
-            .set_parameters(
-                df.clone(),
-                &self.site_name,
-                &self.start_timestamp,
-                &self.end_timestamp,
-                self.interval.num_minutes(),
-                output_path,
-                &col_names,
-                pipe_shape,
-                pipe_size
-            )
-            .map_err(|e| format!("Error setting FDV flow parameters: {}", e))?;
-        fdv_creator.create_fdv_flow().map_err(|e| format!("Error creating FDV flow: {}", e))?;
+        let result =
+            json!({
+            "success": true,
+            "message": "Data status updated successfully",
+            "dataStatus": self.data_status.as_str(),
+        });
+        Ok(result.to_string())
+    }
 
-        let (depth_null, velocity_null) = fdv_creator.get_null_readings();
+    /// Chooses whether readings flagged bad in the source quality column are nulled in FDV
+    /// output (and the output marked EDITED) or kept and only counted.
+    pub fn set_quality_suppression(&mut self, suppress: bool) -> Result<String, String> {
+        self.suppress_bad_quality = suppress;
+        log::info!("Bad quality suppression {}", if suppress { "enabled" } else { "disabled" });
 
         let result =
             json!({
-        "success": true,
-        "message": "FDV flow creation initiated",
-        "outputPath": output_path,
-        "depthColumn": depth_col,
-        "velocityColumn": velocity_col,
-        "pipeShape": pipe_shape,
-        "pipeSize": pipe_size,
-        "nullReadings": {
-            "depth": depth_null,
-            "velocity": velocity_null
-        }
-    });
+            "success": true,
+            "message": "Quality handling updated successfully",
+            "suppressBadQuality": self.suppress_bad_quality,
+            "qualityColumn": self.quality_col,
+        });
+        Ok(result.to_string())
+    }
 
-        log::info!("FDV flow created successfully. Output: {}", output_path);
-        log::info!("Null readings: Depth: {}, Velocity: {}", depth_null, velocity_null);
+    /// Sets the mm of rain per gauge tip (typically 0.2 or 0.5). When the rainfall column
+    /// holds tip counts, totals, interim reports and the rainfall FDV use it immediately;
+    /// otherwise it applies once a tip count unit or input mode is chosen.
+    pub fn set_tip_resolution(&mut self, tip_resolution_mm: f64) -> Result<String, String> {
+        if !tip_resolution_mm.is_finite() || tip_resolution_mm <= 0.0 {
+            return Err(format!("Invalid tip resolution: {}", tip_resolution_mm));
+        }
+        self.tip_resolution_mm = tip_resolution_mm;
+        if let RainfallUnit::Tips(_) = self.rainfall_unit {
+            self.rainfall_unit = RainfallUnit::Tips(tip_resolution_mm);
+        }
+        log::info!("Tip resolution set to {} mm", tip_resolution_mm);
 
+        let result =
+            json!({
+            "success": true,
+            "message": "Tip resolution updated successfully",
+            "tipResolution": self.tip_resolution_mm,
+            "rainfallUnit": self.rainfall_unit.as_str(),
+        });
         Ok(result.to_string())
     }
 
-    pub fn create_rainfall(
-        &mut self,
-        output_path: &str,
-        rainfall_col: &str
-    ) -> Result<String, String> {
-        let df = self.data_frame.as_ref().ok_or("No data frame available")?;
-        let mut rainfall_creator = FDVRainfallCreator::new();
-        let mut col_names = HashMap::new();
-        col_names.insert("timestamp".to_string(), self.time_col.clone().unwrap_or_default());
-        col_names.insert("rainfall".to_string(), rainfall_col.to_string());
+    /// Chooses between standard fixed-width FDV and a comma or tab delimited export for the
+    /// flow conversion commands.
+    pub fn set_flow_output_style(&mut self, output_style: &str) -> Result<String, String> {
+        self.flow_output_style = OutputStyle::from_str(output_style)?;
+        log::info!("Flow output style set to {}", self.flow_output_style.as_str());
 
-        rainfall_creator
-            .set_parameters(
-                df.clone(),
+        let result =
+            json!({
+            "success": true,
+            "message": "Flow output style updated successfully",
+            "outputStyle": self.flow_output_style.as_str(),
+        });
+        Ok(result.to_string())
+    }
+
+    /// Writes flow conversions in l/s (default) or m3/s.
+    pub fn set_flow_unit(&mut self, flow_unit: &str) -> Result<String, String> {
+        self.flow_unit = FlowUnit::from_str(flow_unit)?;
+        log::info!("Flow unit set to {}", self.flow_unit.as_str());
+
+        let result =
+            json!({
+            "success": true,
+            "message": "Flow unit updated successfully",
+            "flowUnit": self.flow_unit.as_str(),
+        });
+        Ok(result.to_string())
+    }
+
+    /// Writes `value` (e.g. -9999) for flow readings with a missing depth or velocity instead
+    /// of zero; `None` restores zero.
+    pub fn set_flow_no_data_value(&mut self, value: Option<f64>) -> Result<String, String> {
+        FDVFlowCreator::new()
+            .set_no_data_value(value)
+            .map_err(|e| e.to_string())?;
+        self.flow_no_data_value = value;
+        log::info!("Flow no-data value set to {:?}", value);
+
+        let result =
+            json!({
+            "success": true,
+            "message": "Flow no-data value updated successfully",
+            "noDataValue": self.flow_no_data_value,
+        });
+        Ok(result.to_string())
+    }
+
+    /// Chooses what FDV conversions do when the output file already exists: overwrite it
+    /// (the default, with a warning), fail, or write alongside it under a numbered name.
+    pub fn set_overwrite_policy(&mut self, policy: &str) -> Result<String, String> {
+        self.overwrite_policy = OverwritePolicy::from_str(policy)?;
+        log::info!("Overwrite policy set to {}", self.overwrite_policy.as_str());
+
+        let result =
+            json!({
+            "success": true,
+            "message": "Overwrite policy updated successfully",
+            "overwritePolicy": self.overwrite_policy.as_str(),
+        });
+        Ok(result.to_string())
+    }
+
+    /// Keeps reverse (negative velocity) flow as negative l/s in flow conversions, and adds
+    /// gross volume alongside the net total in flow interim summaries.
+    pub fn set_signed_flow(&mut self, enabled: bool) -> Result<String, String> {
+        self.signed_flow = enabled;
+        log::info!("Signed flow {}", if enabled { "enabled" } else { "disabled" });
+        let result =
+            json!({
+            "success": true,
+            "signedFlow": self.signed_flow,
+        });
+        Ok(result.to_string())
+    }
+
+    /// Pads flow and rainfall outputs to whole days, from midnight on the first day to the
+    /// last interval of the final day. Added readings are written as missing readings.
+    pub fn set_pad_to_full_days(&mut self, enabled: bool) -> Result<String, String> {
+        self.pad_to_full_days = enabled;
+        log::info!("Pad to full days {}", if enabled { "enabled" } else { "disabled" });
+        let result =
+            json!({
+            "success": true,
+            "padToFullDays": self.pad_to_full_days,
+        });
+        Ok(result.to_string())
+    }
+
+    /// Writes a `<output>.sha256` file next to each FDV and report created, for
+    /// chain-of-custody checks on delivered files.
+    pub fn set_write_checksums(&mut self, enabled: bool) -> Result<String, String> {
+        self.write_checksums = enabled;
+        log::info!("Checksum files {}", if enabled { "enabled" } else { "disabled" });
+        let result =
+            json!({
+            "success": true,
+            "writeChecksums": self.write_checksums,
+        });
+        Ok(result.to_string())
+    }
+
+    /// Chooses what flow conversions do when the velocity column is missing: fail, use
+    /// `velocity` m/s throughout, or write a depth-only file.
+    pub fn set_missing_velocity_mode(
+        &mut self,
+        mode: &str,
+        velocity: Option<f64>
+    ) -> Result<String, String> {
+        self.missing_velocity = MissingVelocity::from_str(mode, velocity)?;
+        log::info!("Missing velocity mode set to {}", self.missing_velocity.as_str());
+
+        let result =
+            json!({
+            "success": true,
+            "message": "Missing velocity mode updated successfully",
+            "mode": self.missing_velocity.as_str(),
+            "velocity": match self.missing_velocity {
+                MissingVelocity::ConstantVelocity(v) => Some(v),
+                _ => None,
+            },
+        });
+        Ok(result.to_string())
+    }
+
+    /// Sets the free-text comment lines embedded in the constants block of FDV output.
+    pub fn set_fdv_comment(&mut self, lines: Vec<String>) -> Result<String, String> {
+        self.fdv_comments = lines;
+
+        let result =
+            json!({
+            "success": true,
+            "message": "FDV comment updated successfully",
+            "comment": self.fdv_comments,
+        });
+        Ok(result.to_string())
+    }
+
+    /// Writes a JSON audit record for the loaded file: the source file (with its SHA-256
+    /// when `include_hash` is set), detected format, gap and timestamp handling, edits
+    /// applied and every conversion written from it.
+    pub fn write_provenance(&self, path: &str, include_hash: bool) -> Result<String, String> {
+        if self.filepath.as_os_str().is_empty() {
+            return Err("No file has been processed yet".to_string());
+        }
+        let source_hash = if include_hash {
+            Some(
+                Self::sha256_file(&self.filepath).map_err(|e|
+                    format!("Error hashing source file: {}", e)
+                )?
+            )
+        } else {
+            None
+        };
+
+        let provenance =
+            json!({
+            "software": format!("{} v{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+            "createdAt": chrono::Utc::now().to_rfc3339(),
+            "sourceFile": self.filepath,
+            "sourceSha256": source_hash,
+            "siteId": self.site_id,
+            "siteName": self.site_name,
+            "monitorType": self.monitor_type,
+            "timestampFormat": self.timestamp_format,
+            "timeBasis": self.time_basis,
+            "startTimestamp": self.start_timestamp,
+            "endTimestamp": self.end_timestamp,
+            "interval": self.interval.num_seconds(),
+            "intervalChanges": self.interval_changes.len(),
+            "gapsFilled": self.gaps,
+            "invalidTimestamps": self.invalid_timestamps,
+            "trimmedRows": self.trimmed_rows,
+            "dataStatus": self.data_status.as_str(),
+            "datumOffsetM": self.datum_offset_m,
+            "clockShiftSeconds": self.clock_shift_seconds,
+            "qualityColumn": self.quality_col,
+            "suppressBadQuality": self.suppress_bad_quality,
+            "rainfallUnit": self.rainfall_unit.as_str(),
+            "missingVelocity": self.missing_velocity.as_str(),
+            "signedFlow": self.signed_flow,
+            "padToFullDays": self.pad_to_full_days,
+            "conversions": self.conversions,
+        });
+        let contents = serde_json
+            ::to_string_pretty(&provenance)
+            .map_err(|e| format!("Error serialising provenance: {}", e))?;
+        fs::write(path, contents).map_err(|e| format!("Error writing provenance file: {}", e))?;
+
+        log::info!("Provenance written to {}", path);
+        let result =
+            json!({
+            "success": true,
+            "message": "Provenance written successfully",
+            "outputPath": path,
+            "sourceSha256": source_hash,
+        });
+        Ok(result.to_string())
+    }
+
+    fn sha256_file(path: &Path) -> io::Result<String> {
+        let mut hasher = Sha256::new();
+        io::copy(&mut File::open(path)?, &mut hasher)?;
+        Ok(format!("{:x}", hasher.finalize()))
+    }
+
+    /// Hashes `path` and writes `<path>.sha256` in `sha256sum` format, returning the hash.
+    pub(crate) fn write_checksum_file(path: &Path) -> io::Result<String> {
+        let hash = Self::sha256_file(path)?;
+        let file_name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+        let mut checksum_path = path.as_os_str().to_os_string();
+        checksum_path.push(".sha256");
+        fs::write(PathBuf::from(checksum_path), format!("{}  {}\n", hash, file_name))?;
+        Ok(hash)
+    }
+
+    /// Deletes an output written earlier in a failed call, along with its checksum file.
+    fn remove_output(path: &Path) {
+        let mut checksum_path = path.as_os_str().to_os_string();
+        checksum_path.push(".sha256");
+        for file in [path.to_path_buf(), PathBuf::from(checksum_path)] {
+            match fs::remove_file(&file) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => {
+                    log::warn!("Failed to remove {}: {}", file.display(), e);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Writes the checksum file for `path` when enabled, returning the hash.
+    fn checksum_output(&self, path: &str) -> Result<Option<String>, String> {
+        if !self.write_checksums {
+            return Ok(None);
+        }
+        Self::write_checksum_file(Path::new(path))
+            .map(Some)
+            .map_err(|e| format!("Error writing checksum for {}: {}", path, e))
+    }
+
+    /// Returns the current handler state in one call for UI sync and support diagnostics.
+    pub fn get_state(&self) -> Result<String, String> {
+        let (row_count, columns) = match &self.data_frame {
+            Some(df) => {
+                let columns: Vec<String> = df
+                    .get_column_names()
+                    .iter()
+                    .map(|name| name.to_string())
+                    .collect();
+                (df.height(), columns)
+            }
+            None => (0, Vec::new()),
+        };
+
+        let result =
+            json!({
+            "success": true,
+            "filePath": self.filepath,
+            "siteId": self.site_id,
+            "siteName": self.site_name,
+            "startTimestamp": self.start_timestamp,
+            "endTimestamp": self.end_timestamp,
+            "interval": self.interval.num_seconds(),
+            "monitorType": self.monitor_type,
+            "availableConversions": self.available_conversions(),
+            "columnMapping": self.column_mapping,
+            "timeColumn": self.time_col,
+            "timeBasis": self.time_basis,
+            "gaps": self.gaps,
+            "invalidTimestamps": self.invalid_timestamps,
+            "trimmedRows": self.trimmed_rows,
+            "rainfallUnit": self.rainfall_unit.as_str(),
+            "tipResolution": self.tip_resolution_mm,
+            "dataStatus": self.data_status.as_str(),
+            "qualityColumn": self.quality_col,
+            "suppressBadQuality": self.suppress_bad_quality,
+            "datumOffsetM": self.datum_offset_m,
+            "clockShiftSeconds": self.clock_shift_seconds,
+            "flowOutputStyle": self.flow_output_style.as_str(),
+            "flowUnit": self.flow_unit.as_str(),
+            "flowNoDataValue": self.flow_no_data_value,
+            "overwritePolicy": self.overwrite_policy.as_str(),
+            "writeChecksums": self.write_checksums,
+            "hasData": self.data_frame.is_some(),
+            "rowCount": row_count,
+            "columns": columns,
+        });
+        Ok(result.to_string())
+    }
+
+    /// Overrides the detected monitor type, e.g. when a flow file was classified as Depth
+    /// because its name and columns gave no hint of velocity.
+    pub fn set_monitor_type(&mut self, monitor_type: &str) -> Result<String, String> {
+        let parsed = MonitorType::from_str(monitor_type).map_err(|e| e.to_string())?;
+        log::info!("Monitor type changed from {} to {}", self.monitor_type, parsed.as_str());
+        self.monitor_type = parsed.as_str().to_string();
+
+        let result =
+            json!({
+            "success": true,
+            "message": "Monitor type updated successfully",
+            "monitorType": self.monitor_type,
+            "availableConversions": parsed.available_conversions(),
+        });
+        Ok(result.to_string())
+    }
+
+    fn available_conversions(&self) -> Vec<&'static str> {
+        MonitorType::from_str(&self.monitor_type)
+            .map(|t| t.available_conversions())
+            .unwrap_or_default()
+    }
+
+    pub fn reset(&mut self) {
+        // Added timestamp formats are a user preference rather than per-file state
+        let extra_timestamp_formats = std::mem::take(&mut self.extra_timestamp_formats);
+        *self = CommandHandler::new();
+        self.extra_timestamp_formats = extra_timestamp_formats;
+        clear_logs();
+    }
+
+    pub fn create_fdv_flow(
+        &mut self,
+        output_path: &str,
+        depth_col: &str,
+        velocity_col: &Option<&str>,
+        pipe_shape: &str,
+        pipe_size: &str
+    ) -> Result<String, String> {
+        self.write_fdv_flow(output_path, depth_col, velocity_col, pipe_shape, pipe_size, None).map(
+            |result| result.to_string()
+        )
+    }
+
+    /// Writes a flow FDV to `flow_path` and a depth-only FDV to `depth_path` from the loaded
+    /// data in one call, for clients who want the depth record alongside the flow.
+    pub fn create_both_outputs(
+        &mut self,
+        flow_path: &str,
+        depth_path: &str,
+        depth_col: &str,
+        velocity_col: &Option<&str>,
+        pipe_shape: &str,
+        pipe_size: &str
+    ) -> Result<String, String> {
+        if flow_path == depth_path {
+            return Err("Flow and depth outputs must be written to different paths".to_string());
+        }
+        let flow = self.write_fdv_flow(
+            flow_path,
+            depth_col,
+            velocity_col,
+            pipe_shape,
+            pipe_size,
+            None
+        )?;
+        let depth = match self.write_fdv_depth(depth_path, depth_col, pipe_shape, pipe_size) {
+            Ok(depth) => depth,
+            Err(e) => {
+                // Don't deliver a flow file without the depth file asked for alongside it
+                if let Some(path) = flow["outputPath"].as_str() {
+                    Self::remove_output(Path::new(path));
+                }
+                self.conversions.pop();
+                return Err(format!("{} (the flow output was removed)", e));
+            }
+        };
+
+        let result =
+            json!({
+            "success": true,
+            "message": "FDV flow and depth created",
+            "flow": flow,
+            "depth": depth,
+        });
+        Ok(result.to_string())
+    }
+
+    /// Writes a depth-only FDV (depth as the sole field) regardless of the velocity column.
+    fn write_fdv_depth(
+        &mut self,
+        output_path: &str,
+        depth_col: &str,
+        pipe_shape: &str,
+        pipe_size: &str
+    ) -> Result<Value, String> {
+        let df = self.data_frame.as_ref().ok_or("No data frame available")?;
+        let summary = self.run_fdv_flow_creator(
+            df.clone(),
+            &self.start_timestamp,
+            &self.end_timestamp,
+            output_path,
+            depth_col,
+            &None,
+            pipe_shape,
+            pipe_size,
+            None,
+            true
+        )?;
+
+        let result =
+            json!({
+            "success": true,
+            "message": "FDV depth creation initiated",
+            "outputPath": summary.output_path,
+            "sha256": summary.sha256,
+            "depthColumn": depth_col,
+            "pipeShape": pipe_shape,
+            "pipeSize": pipe_size,
+            "nullReadings": {
+                "depth": summary.depth_null
+            },
+            "qualitySuppressed": summary.quality_suppressed,
+            "velocitySource": summary.velocity_source,
+            "dataStatus": summary.data_status.as_str(),
+            "paddingReadings": summary.padding_readings,
+            "noDataValue": self.flow_no_data_value,
+            "noDataReadings": summary.no_data_readings
+        });
+
+        self.conversions.push(result.clone());
+        log::info!("FDV depth created successfully. Output: {}", summary.output_path);
+        Ok(result)
+    }
+
+    /// Creates an estimated FDV flow from depth alone by applying the same velocity to
+    /// every reading. Intended for quick-look deliverables when no velocity was measured.
+    pub fn create_fdv_flow_constant_velocity(
+        &mut self,
+        output_path: &str,
+        depth_col: &str,
+        velocity: f64,
+        pipe_shape: &str,
+        pipe_size: &str
+    ) -> Result<String, String> {
+        if !velocity.is_finite() || velocity < 0.0 {
+            return Err(format!("Invalid constant velocity: {}", velocity));
+        }
+        log::warn!(
+            "Creating ESTIMATED flow using an assumed constant velocity of {} m/s",
+            velocity
+        );
+        self.write_fdv_flow(
+            output_path,
+            depth_col,
+            &None,
+            pipe_shape,
+            pipe_size,
+            Some(velocity)
+        ).map(|result| result.to_string())
+    }
+
+    fn write_fdv_flow(
+        &mut self,
+        output_path: &str,
+        depth_col: &str,
+        velocity_col: &Option<&str>,
+        pipe_shape: &str,
+        pipe_size: &str,
+        constant_velocity: Option<f64>
+    ) -> Result<Value, String> {
+        let depth_exceedance = self.check_depth_exceedance(depth_col, pipe_shape, pipe_size)?;
+
+        let df = self.data_frame.as_ref().ok_or("No data frame available")?;
+        let summary = self.run_fdv_flow_creator(
+            df.clone(),
+            &self.start_timestamp,
+            &self.end_timestamp,
+            output_path,
+            depth_col,
+            velocity_col,
+            pipe_shape,
+            pipe_size,
+            constant_velocity,
+            false
+        )?;
+
+        let result =
+            json!({
+        "success": true,
+        "message": "FDV flow creation initiated",
+        "outputPath": summary.output_path,
+        "sha256": summary.sha256,
+        "depthColumn": depth_col,
+        "velocityColumn": velocity_col,
+        "pipeShape": pipe_shape,
+        "pipeSize": pipe_size,
+        "nullReadings": {
+            "depth": summary.depth_null,
+            "velocity": summary.velocity_null
+        },
+        "depthExceedance": depth_exceedance,
+        "qualitySuppressed": summary.quality_suppressed,
+        "velocitySource": summary.velocity_source,
+        "estimated": summary.data_status == DataStatus::Estimated,
+        "dataStatus": summary.data_status.as_str(),
+        "constantVelocity": constant_velocity,
+        "signedFlow": self.signed_flow,
+        "flowUnit": self.flow_unit.as_str(),
+        "volume": summary.volume_json(),
+        "paddingReadings": summary.padding_readings,
+        "noDataValue": self.flow_no_data_value,
+        "noDataReadings": summary.no_data_readings
+    });
+
+        self.conversions.push(result.clone());
+        log::info!("FDV flow created successfully. Output: {}", summary.output_path);
+        log::info!(
+            "Null readings: Depth: {}, Velocity: {}",
+            summary.depth_null,
+            summary.velocity_null
+        );
+
+        Ok(result)
+    }
+
+    /// Writes one FDV flow file for `df`, returning the null and quality counts along with
+    /// how velocity was obtained and the status written to the header. `depth_only` writes
+    /// depth as the sole field whatever the velocity column.
+    #[allow(clippy::too_many_arguments)]
+    fn run_fdv_flow_creator(
+        &self,
+        df: DataFrame,
+        start_timestamp: &str,
+        end_timestamp: &str,
+        output_path: &str,
+        depth_col: &str,
+        velocity_col: &Option<&str>,
+        pipe_shape: &str,
+        pipe_size: &str,
+        constant_velocity: Option<f64>,
+        depth_only: bool
+    ) -> Result<FlowRunSummary, String> {
+        // Create a new FDVFlowCreator
+        let mut fdv_creator = FDVFlowCreator::new();
+        fdv_creator.set_overwrite_policy(self.overwrite_policy);
+
+        // Set up column names
+        let mut col_names = HashMap::new();
+        col_names.insert("timestamp".to_string(), self.time_col.clone().unwrap_or_default());
+        col_names.insert("depth".to_string(), depth_col.to_string());
+        if let Some(vel_col) = velocity_col {
+            col_names.insert("velocity".to_string(), vel_col.to_string());
+        }
+
+        fdv_creator
+            .set_parameters(
+                df,
                 &self.site_name,
-                &self.start_timestamp,
-                &self.end_timestamp,
+                start_timestamp,
+                end_timestamp,
                 self.interval.num_minutes(),
                 output_path,
-                &col_names
+                &col_names,
+                pipe_shape,
+                pipe_size
+            )
+            .map_err(|e| format!("Error setting FDV flow parameters: {}", e))?;
+        if let Some(velocity) = constant_velocity {
+            fdv_creator.set_constant_velocity(velocity);
+            fdv_creator.set_data_status(self.data_status.max(DataStatus::Estimated));
+        } else {
+            fdv_creator.set_data_status(self.data_status);
+        }
+        fdv_creator.set_missing_velocity(self.missing_velocity);
+        if depth_only {
+            fdv_creator.set_depth_only();
+        }
+        fdv_creator.set_output_style(self.flow_output_style);
+        fdv_creator.set_flow_unit(self.flow_unit);
+        fdv_creator
+            .set_no_data_value(self.flow_no_data_value)
+            .map_err(|e| format!("Error creating FDV flow: {}", e))?;
+        fdv_creator.set_signed_flow(self.signed_flow);
+        fdv_creator.set_pad_to_full_days(self.pad_to_full_days);
+        fdv_creator.set_comment(self.fdv_comments.clone());
+        if let Some(quality_col) = &self.quality_col {
+            fdv_creator.set_quality_column(quality_col, self.suppress_bad_quality);
+        }
+        fdv_creator.create_fdv_flow().map_err(|e| format!("Error creating FDV flow: {}", e))?;
+
+        let (depth_null, velocity_null) = fdv_creator.get_null_readings();
+        let (forward_volume, reverse_volume) = fdv_creator.get_volumes();
+        let output_path = fdv_creator
+            .output_file_path()
+            .map(|path| path.to_string_lossy().to_string())
+            .unwrap_or_else(|| output_path.to_string());
+        Ok(FlowRunSummary {
+            sha256: self.checksum_output(&output_path)?,
+            output_path,
+            depth_null,
+            velocity_null,
+            quality_suppressed: fdv_creator.get_quality_suppressed(),
+            velocity_source: fdv_creator.velocity_source(),
+            data_status: fdv_creator.data_status(),
+            forward_volume,
+            reverse_volume,
+            padding_readings: fdv_creator.get_padding_readings(),
+            no_data_readings: fdv_creator.get_no_data_readings(),
+        })
+    }
+
+    /// Writes one FDV flow file per period (e.g. per calendar month) into `output_dir`,
+    /// named `<site>_<period>.fdv`. Periods are stepped the same way as interim reports.
+    pub fn create_fdv_flow_split(
+        &mut self,
+        output_dir: &str,
+        period: ReportPeriod,
+        depth_col: &str,
+        velocity_col: &Option<&str>,
+        pipe_shape: &str,
+        pipe_size: &str
+    ) -> Result<String, String> {
+        self.write_fdv_flow_split(
+            output_dir,
+            period,
+            depth_col,
+            velocity_col,
+            pipe_shape,
+            pipe_size
+        ).map(|result| result.to_string())
+    }
+
+    /// Writes one FDV flow file per calendar year into `output_dir`, named
+    /// `<site>_<year>.fdv`, and bundles them into `<site>_yearly.zip` alongside.
+    pub fn create_fdv_flow_yearly(
+        &mut self,
+        output_dir: &str,
+        depth_col: &str,
+        velocity_col: &Option<&str>,
+        pipe_shape: &str,
+        pipe_size: &str
+    ) -> Result<String, String> {
+        let mut result = self.write_fdv_flow_split(
+            output_dir,
+            ReportPeriod::Yearly,
+            depth_col,
+            velocity_col,
+            pipe_shape,
+            pipe_size
+        )?;
+        let output_paths: Vec<PathBuf> = result["outputs"]
+            .as_array()
+            .map(|outputs| {
+                outputs
+                    .iter()
+                    .filter_map(|output| output["outputPath"].as_str())
+                    .map(PathBuf::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let stem = self.output_stem();
+        let zip_path = Path::new(output_dir).join(format!("{}_yearly.zip", stem));
+        Self::zip_files(&zip_path, &output_paths)?;
+        log::info!("Yearly FDV files zipped to {}", zip_path.display());
+
+        result["zipPath"] = json!(zip_path.to_string_lossy());
+        Ok(result.to_string())
+    }
+
+    /// Site name made safe to use as a file name: path separators and characters reserved
+    /// on Windows become `_`, and leading or trailing dots and spaces are dropped so the
+    /// name can't climb out of the output directory.
+    fn output_stem(&self) -> String {
+        let stem: String = self.site_name
+            .chars()
+            .map(|c| if c.is_control() || r#"/\:*?"<>|"#.contains(c) { '_' } else { c })
+            .collect();
+        let stem = stem.trim_matches(|c| c == '.' || c == ' ');
+        if stem.is_empty() { "output".to_string() } else { stem.to_string() }
+    }
+
+    /// Writes `paths` into a new zip at `zip_path`, each under its file name.
+    fn zip_files(zip_path: &Path, paths: &[PathBuf]) -> Result<(), String> {
+        let file = File::create(zip_path).map_err(|e|
+            format!("Failed to create zip file: {}", e)
+        )?;
+        let mut zip = ZipWriter::new(file);
+        let options: FileOptions<'static, ()> = FileOptions::default().compression_method(
+            CompressionMethod::Deflated
+        );
+        for path in paths {
+            let name = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .ok_or("Invalid or non-UTF8 file name")?;
+            zip
+                .start_file(name, options)
+                .map_err(|e| format!("Failed to start file in zip: {}", e))?;
+            let mut file = File::open(path).map_err(|e|
+                format!("Failed to open {}: {}", path.display(), e)
+            )?;
+            io::copy(&mut file, &mut zip).map_err(|e| format!("Failed to write to zip: {}", e))?;
+        }
+        zip.finish().map_err(|e| format!("Failed to finish zip file: {}", e))?;
+        Ok(())
+    }
+
+    fn write_fdv_flow_split(
+        &mut self,
+        output_dir: &str,
+        period: ReportPeriod,
+        depth_col: &str,
+        velocity_col: &Option<&str>,
+        pipe_shape: &str,
+        pipe_size: &str
+    ) -> Result<Value, String> {
+        let depth_exceedance = self.check_depth_exceedance(depth_col, pipe_shape, pipe_size)?;
+        let df = self.data_frame.as_ref().ok_or("No data frame available")?;
+        let time_col = self.time_col.clone().ok_or("No timestamp column available")?;
+        if self.interval.num_seconds() <= 0 {
+            return Err("Interval must be greater than zero to split the data".to_string());
+        }
+
+        let format = "%Y-%m-%d %H:%M:%S";
+        let data_start = NaiveDateTime::parse_from_str(&self.start_timestamp, format).map_err(|e|
+            format!("Invalid start timestamp: {}", e)
+        )?;
+        let data_end = NaiveDateTime::parse_from_str(&self.end_timestamp, format).map_err(|e|
+            format!("Invalid end timestamp: {}", e)
+        )?;
+        let interval = self.interval.num_seconds();
+        let stem = self.output_stem();
+        fs::create_dir_all(output_dir).map_err(|e|
+            format!("Failed to create {}: {}", output_dir, e)
+        )?;
+
+        let mut outputs = Vec::new();
+        let mut period_start = data_start;
+        while period_start <= data_end {
+            let period_end = period.period_end(period_start).min(data_end);
+
+            // First and last readings on the interval grid within the period
+            let offset = (period_start - data_start).num_seconds();
+            let first =
+                data_start + Duration::seconds(((offset + interval - 1) / interval) * interval);
+            let offset = (period_end - data_start).num_seconds();
+            let last = data_start + Duration::seconds((offset / interval) * interval);
+
+            if first <= last {
+                let slice = df
+                    .clone()
+                    .lazy()
+                    .filter(col(&time_col).gt_eq(lit(first)).and(col(&time_col).lt_eq(lit(last))))
+                    .collect()
+                    .map_err(|e| format!("Failed to slice data: {}", e))?;
+
+                if slice.height() > 0 {
+                    let output_path = Path::new(output_dir)
+                        .join(format!("{}_{}.fdv", stem, period.file_suffix(period_start)))
+                        .to_string_lossy()
+                        .to_string();
+                    let summary = self.run_fdv_flow_creator(
+                        slice,
+                        &first.format(format).to_string(),
+                        &last.format(format).to_string(),
+                        &output_path,
+                        depth_col,
+                        velocity_col,
+                        pipe_shape,
+                        pipe_size,
+                        None,
+                        false
+                    )?;
+                    log::info!(
+                        "FDV flow created for {} to {}: {}",
+                        first,
+                        last,
+                        summary.output_path
+                    );
+                    outputs.push(
+                        json!({
+                        "outputPath": summary.output_path,
+                        "sha256": summary.sha256,
+                        "start": first.format(format).to_string(),
+                        "end": last.format(format).to_string(),
+                        "nullReadings": {
+                            "depth": summary.depth_null,
+                            "velocity": summary.velocity_null
+                        },
+                        "qualitySuppressed": summary.quality_suppressed,
+                        "velocitySource": summary.velocity_source,
+                        "dataStatus": summary.data_status.as_str(),
+                        "volume": summary.volume_json(),
+                        "paddingReadings": summary.padding_readings,
+                    })
+                    );
+                }
+            }
+
+            period_start = period.period_end(period_start) + Duration::seconds(1);
+        }
+
+        let result =
+            json!({
+            "success": true,
+            "message": format!("{} FDV flow files created", outputs.len()),
+            "outputs": outputs,
+            "depthColumn": depth_col,
+            "velocityColumn": velocity_col,
+            "pipeShape": pipe_shape,
+            "pipeSize": pipe_size,
+            "depthExceedance": depth_exceedance,
+        });
+        self.conversions.push(result.clone());
+        Ok(result)
+    }
+
+    pub fn create_rainfall(
+        &mut self,
+        output_path: &str,
+        rainfall_col: &str,
+        input_mode: &Option<&str>,
+        tip_resolution: Option<f64>
+    ) -> Result<String, String> {
+        let df = self.data_frame.as_ref().ok_or("No data frame available")?;
+        let mut rainfall_creator = FDVRainfallCreator::new();
+        rainfall_creator.set_overwrite_policy(self.overwrite_policy);
+
+        // An explicit input mode overrides the rainfall unit chosen at processing time
+        let rainfall_unit = match input_mode {
+            Some(mode) => {
+                let input_mode = RainfallInputMode::from_str(mode).map_err(|e| e.to_string())?;
+                let tip_resolution = tip_resolution.unwrap_or(self.tip_resolution_mm);
+                if !tip_resolution.is_finite() || tip_resolution <= 0.0 {
+                    return Err("Tip resolution must be a number greater than zero".to_string());
+                }
+                rainfall_creator.set_input_mode(input_mode, tip_resolution);
+                match input_mode {
+                    RainfallInputMode::Intensity => RainfallUnit::MmPerHour,
+                    _ => RainfallUnit::Tips(tip_resolution),
+                }
+            }
+            None => {
+                rainfall_creator.set_rainfall_unit(self.rainfall_unit);
+                self.rainfall_unit
+            }
+        };
+        rainfall_creator.set_data_status(self.data_status);
+        rainfall_creator.set_comment(self.fdv_comments.clone());
+        rainfall_creator.set_pad_to_full_days(self.pad_to_full_days);
+        if let Some(source) = &self.source_data_frame {
+            rainfall_creator.set_antecedent_data(source.clone());
+        }
+        if let Some(quality_col) = &self.quality_col {
+            rainfall_creator.set_quality_column(quality_col, self.suppress_bad_quality);
+        }
+        let mut col_names = HashMap::new();
+        col_names.insert("timestamp".to_string(), self.time_col.clone().unwrap_or_default());
+        col_names.insert("rainfall".to_string(), rainfall_col.to_string());
+
+        rainfall_creator
+            .set_parameters(
+                df.clone(),
+                &self.site_name,
+                &self.start_timestamp,
+                &self.end_timestamp,
+                self.interval.num_minutes(),
+                output_path,
+                &col_names
+            )
+            .map_err(|e| format!("Error setting Rainfall parameter: {}", e))?;
+
+        rainfall_creator
+            .create_fdv_rainfall()
+            .map_err(|e| format!("Error creating FDV flow: {}", e))?;
+
+        let null_readings = rainfall_creator.get_null_readings();
+        let quality_suppressed = rainfall_creator.get_quality_suppressed();
+        let data_status = if quality_suppressed > 0 {
+            self.data_status.max(DataStatus::Edited)
+        } else {
+            self.data_status
+        };
+
+        let output_path = rainfall_creator
+            .written_path()
+            .map(|path| path.to_string_lossy().to_string())
+            .unwrap_or_else(|| output_path.to_string());
+        let sha256 = self.checksum_output(&output_path)?;
+
+        let result =
+            json!({
+            "success": true,
+            "message": "Rainfall creation initiated",
+            "outputPath": output_path,
+            "sha256": sha256,
+            "rainfallColumn": rainfall_col,
+            "rainfallUnit": rainfall_unit.as_str(),
+            "dataStatus": data_status.as_str(),
+            "tipResolution": match rainfall_unit {
+                RainfallUnit::Tips(resolution) => Some(resolution),
+                _ => None,
+            },
+            "nullReadings": null_readings,
+            "qualitySuppressed": quality_suppressed,
+            "paddingReadings": rainfall_creator.get_padding_readings(),
+            "antecedentRainfall": rainfall_creator.get_antecedent_rainfall()
+        });
+
+        self.conversions.push(result.clone());
+        log::info!("Rainfall creation successfully. Output: {}", output_path);
+        log::info!("Null readings: {}", null_readings);
+
+        Ok(result.to_string())
+    }
+    pub fn calculate_r3(&self, width: f64, height: f64, egg_form: &str) -> f64 {
+        let egg_form_value = match egg_form {
+            "Egg Type 1" => 1,
+            "Egg Type 2" => 2,
+            _ => {
+                log::error!("Unknown egg form: {}", egg_form);
+                return -1.0;
+            }
+        };
+
+        match r3_calculator(width, height, egg_form_value) {
+            Ok(r3_value) => {
+                log::info!("R3 value calculated successfully: {}", r3_value);
+                r3_value
+            }
+            Err(e) => {
+                log::error!("Error calculating R3 value: {:?}", e);
+                -1.0
+            }
+        }
+    }
+
+    fn depth_values_mm(&self, depth_col: &str) -> Result<Vec<f64>, String> {
+        let df = self.data_frame.as_ref().ok_or("No data frame available")?;
+        let depth_series = df
+            .column(depth_col)
+            .and_then(|s| s.cast(&DataType::Float64))
+            .map_err(|e| format!("Error reading depth column '{}': {}", depth_col, e))?;
+        let scale = if is_depth_in_mm(depth_col) { 1.0 } else { 1000.0 };
+
+        let values = depth_series
+            .f64()
+            .map_err(|e| format!("Error reading depth column '{}': {}", depth_col, e))?
+            .into_iter()
+            .flatten()
+            .filter(|v| !v.is_nan())
+            .map(|v| v * scale)
+            .collect();
+
+        Ok(values)
+    }
+
+    /// Pre-scans the depth column against the pipe diameter. Only circular pipes are
+    /// checked, as the calculator clamps over-diameter depths to full-bore silently.
+    fn check_depth_exceedance(
+        &self,
+        depth_col: &str,
+        pipe_shape: &str,
+        pipe_size: &str
+    ) -> Result<Option<Value>, String> {
+        let has_depth_column = self.data_frame
+            .as_ref()
+            .is_some_and(|df| df.get_column_names().iter().any(|&col| col == depth_col));
+        if pipe_shape != "Circular" || pipe_size.is_empty() || !has_depth_column {
+            return Ok(None);
+        }
+        let size_mm = pipe_size
+            .parse::<f64>()
+            .map_err(|e| format!("Invalid pipe size '{}': {}", pipe_size, e))?;
+        let exceedances: Vec<f64> = self
+            .depth_values_mm(depth_col)?
+            .into_iter()
+            .filter(|&depth| depth > size_mm)
+            .map(|depth| depth - size_mm)
+            .collect();
+
+        if exceedances.is_empty() {
+            return Ok(None);
+        }
+
+        let max_exceedance_mm = exceedances.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let warning = format!(
+            "{} depth readings exceed the pipe size of {:.0} mm (max exceedance {:.0} mm)",
+            exceedances.len(),
+            size_mm,
+            max_exceedance_mm
+        );
+        log::warn!("{}", warning);
+
+        Ok(
+            Some(
+                json!({
+                "count": exceedances.len(),
+                "maxExceedanceMm": max_exceedance_mm,
+                "warning": warning,
+            })
+            )
+        )
+    }
+
+    /// Builds the calculator for the given pipe without converting anything, so the UI can
+    /// reject parameters (e.g. egg dimensions in the wrong order) before a conversion runs.
+    pub fn validate_pipe_params(
+        &self,
+        pipe_shape: &str,
+        pipe_size: &str
+    ) -> Result<String, String> {
+        if pipe_size.trim().is_empty() {
+            return Err(format!("No pipe size given for {} pipe", pipe_shape));
+        }
+
+        let (calculator, _) = create_calculator(pipe_shape, pipe_size).map_err(|e|
+            format!("Invalid {} parameters: {}", pipe_shape, e)
+        )?;
+        let geometry = calculator.geometry();
+
+        let invalid: Vec<String> = geometry
+            .iter()
+            .filter(|(_, value)| !value.is_finite() || *value <= 0.0)
+            .map(|(name, value)| format!("{} = {}", name, value))
+            .collect();
+        if !invalid.is_empty() {
+            return Err(
+                format!(
+                    "Invalid {} parameters: derived geometry is not positive ({}). Check the parameter order.",
+                    pipe_shape,
+                    invalid.join(", ")
+                )
+            );
+        }
+
+        let geometry: serde_json::Map<String, Value> = geometry
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), json!(value)))
+            .collect();
+
+        let result =
+            json!({
+            "success": true,
+            "message": format!("{} parameters are valid", pipe_shape),
+            "pipeShape": pipe_shape,
+            "geometry": geometry,
+        });
+        Ok(result.to_string())
+    }
+
+    /// Geometry of the pipe used by the most recent flow or depth conversion this session,
+    /// as derived by its calculator (radii, offsets and heights for eggs, width and height
+    /// for other shapes), for methodology audits and documentation.
+    pub fn describe_active_calculator(&self) -> Result<String, String> {
+        let (pipe_shape, pipe_size) = self.conversions
+            .iter()
+            .rev()
+            .find_map(|conversion| {
+                Some((conversion["pipeShape"].as_str()?, conversion["pipeSize"].as_str()?))
+            })
+            .ok_or("No flow or depth conversion has been run for the loaded file")?;
+
+        let (calculator, _) = create_calculator(pipe_shape, pipe_size).map_err(|e|
+            format!("Invalid {} parameters: {}", pipe_shape, e)
+        )?;
+        let geometry: serde_json::Map<String, Value> = calculator
+            .geometry()
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), json!(value)))
+            .collect();
+
+        let result =
+            json!({
+            "success": true,
+            "pipeShape": pipe_shape,
+            "pipeSize": pipe_size,
+            "geometry": geometry,
+        });
+        Ok(result.to_string())
+    }
+
+    pub fn suggest_pipe_size(
+        &self,
+        depth_col: &str,
+        pipe_size: &Option<&str>
+    ) -> Result<String, String> {
+        let depth_values = self.depth_values_mm(depth_col)?;
+        if depth_values.is_empty() {
+            return Err(format!("No valid depth readings in column '{}'", depth_col));
+        }
+        let max_depth_mm = depth_values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+
+        let mut warning = None;
+        if let Some(size) = pipe_size.filter(|s| !s.is_empty()) {
+            let size_mm = size
+                .parse::<f64>()
+                .map_err(|e| format!("Invalid pipe size '{}': {}", size, e))?;
+            if max_depth_mm > size_mm {
+                let message = format!(
+                    "Observed depth {:.0} mm exceeds the entered pipe size {:.0} mm",
+                    max_depth_mm,
+                    size_mm
+                );
+                log::warn!("{}", message);
+                warning = Some(message);
+            }
+        }
+
+        let result =
+            json!({
+            "success": true,
+            "depthColumn": depth_col,
+            "maxDepthMm": max_depth_mm,
+            "suggestedMinPipeSizeMm": max_depth_mm.ceil(),
+            "warning": warning,
+        });
+
+        log::info!("Suggested minimum pipe size: {:.0} mm", max_depth_mm.ceil());
+        Ok(result.to_string())
+    }
+
+    /// Crown height (mm) of the pipe the calculator builds from `pipe_shape` and `pipe_size`:
+    /// the derived height for shapes that have one and the diameter for circular pipes.
+    /// Open rectangular channels have no crown.
+    fn crown_height_mm(pipe_shape: &str, pipe_size: &str) -> Result<f64, String> {
+        if pipe_size.trim().is_empty() {
+            return Err(format!("No pipe size given for {} pipe", pipe_shape));
+        }
+        let (calculator, _) = create_calculator(pipe_shape, pipe_size).map_err(|e|
+            format!("Invalid {} parameters: {}", pipe_shape, e)
+        )?;
+        let geometry = calculator.geometry();
+        let dimension = |name: &str| {
+            geometry
+                .iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| *value)
+        };
+        let height_m = dimension("height")
+            .or_else(|| dimension("radius").map(|radius| radius * 2.0))
+            .ok_or_else(|| format!("{} channels have no crown to check against", pipe_shape))?;
+        if !height_m.is_finite() || height_m <= 0.0 {
+            return Err(format!("Invalid pipe size '{}' for {} pipe", pipe_size, pipe_shape));
+        }
+        Ok(height_m * 1000.0)
+    }
+
+    /// Compares depth readings with a proposed pipe before conversion: the share of readings
+    /// at or above the crown and the peak depth. Flags the size as likely wrong when depth
+    /// sits at the crown for much of the record or never gets near it, and the site as
+    /// surcharge-prone when it only reaches the crown occasionally.
+    pub fn pipe_size_sanity_check(
+        &self,
+        pipe_shape: &str,
+        pipe_size: &str
+    ) -> Result<String, String> {
+        let size_mm = Self::crown_height_mm(pipe_shape, pipe_size)?;
+        let depth_col = self.mapped_column("depth").ok_or("No depth column identified")?;
+        let depth_values = self.depth_values_mm(&depth_col)?;
+        if depth_values.is_empty() {
+            return Err(format!("No valid depth readings in column '{}'", depth_col));
+        }
+
+        let at_crown = depth_values
+            .iter()
+            .filter(|&&depth| depth >= size_mm)
+            .count();
+        let full_fraction = (at_crown as f64) / (depth_values.len() as f64);
+        let max_depth_mm = depth_values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+
+        let (status, warning) = if full_fraction >= WRONG_SIZE_FULL_FRACTION {
+            (
+                "likely_wrong_size",
+                Some(
+                    format!(
+                        "{:.1}% of depth readings reach the {:.0} mm crown; check the pipe size",
+                        full_fraction * 100.0,
+                        size_mm
+                    )
+                ),
+            )
+        } else if max_depth_mm < size_mm * MIN_PEAK_DEPTH_FRACTION {
+            (
+                "likely_wrong_size",
+                Some(
+                    format!(
+                        "Peak depth {:.0} mm is under {:.0}% of the {:.0} mm pipe; check the size",
+                        max_depth_mm,
+                        MIN_PEAK_DEPTH_FRACTION * 100.0,
+                        size_mm
+                    )
+                ),
+            )
+        } else if at_crown > 0 {
+            (
+                "surcharge_prone",
+                Some(
+                    format!(
+                        "{} depth readings reach the {:.0} mm crown (max {:.0} mm)",
+                        at_crown,
+                        size_mm,
+                        max_depth_mm
+                    )
+                ),
+            )
+        } else {
+            ("ok", None)
+        };
+        if let Some(warning) = &warning {
+            log::warn!("{}", warning);
+        }
+
+        let result =
+            json!({
+            "success": true,
+            "depthColumn": depth_col,
+            "pipeShape": pipe_shape,
+            "pipeSize": pipe_size,
+            "crownHeightMm": size_mm,
+            "readings": depth_values.len(),
+            "readingsAtCrown": at_crown,
+            "fractionAtCrown": full_fraction,
+            "maxDepthMm": max_depth_mm,
+            "status": status,
+            "warning": warning,
+        });
+        Ok(result.to_string())
+    }
+
+    pub fn column_histogram(&self, column: &str, bins: usize) -> Result<String, String> {
+        if bins == 0 {
+            return Err("Number of bins must be greater than zero".to_string());
+        }
+
+        let df = self.data_frame.as_ref().ok_or("No data frame available")?;
+        let series = df
+            .column(column)
+            .and_then(|s| s.cast(&DataType::Float64))
+            .map_err(|e| format!("Error reading column '{}': {}", column, e))?;
+        let all_values: Vec<Option<f64>> = series
+            .f64()
+            .map_err(|e| format!("Error reading column '{}': {}", column, e))?
+            .into_iter()
+            .collect();
+
+        let values: Vec<f64> = all_values
+            .iter()
+            .filter_map(|v| v.filter(|v| !v.is_nan()))
+            .collect();
+        let excluded = all_values.len() - values.len();
+
+        let mut edges = Vec::new();
+        let mut counts = vec![0usize; bins];
+
+        if !values.is_empty() {
+            let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+            let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+            let width = if max > min { (max - min) / (bins as f64) } else { 1.0 };
+
+            edges = (0..=bins).map(|i| min + width * (i as f64)).collect();
+            for value in &values {
+                let bin = (((value - min) / width) as usize).min(bins - 1);
+                counts[bin] += 1;
+            }
+        }
+
+        let result =
+            json!({
+            "success": true,
+            "column": column,
+            "binEdges": edges,
+            "counts": counts,
+            "excluded": excluded,
+        });
+
+        log::info!(
+            "Histogram computed for column '{}' with {} bins ({} values excluded)",
+            column,
+            bins,
+            excluded
+        );
+        Ok(result.to_string())
+    }
+
+    pub fn read_fdv_header(&self, path: &str) -> Result<String, String> {
+        let reader = FdvReader::open(path).map_err(|e| format!("Error reading FDV file: {}", e))?;
+        let header = reader.header();
+        let file_type = if header.fields.iter().any(|f| f == "INTENSITY") {
+            "Rainfall"
+        } else {
+            "Flow"
+        };
+
+        let result =
+            json!({
+            "success": true,
+            "path": path,
+            "fileType": file_type,
+            "identifier": header.identifier,
+            "fields": header.fields,
+            "units": header.units,
+            "fieldWidths": header.field_widths,
+            "recordsPerLine": header.records_per_line,
+            "startTimestamp": header.start.format("%Y-%m-%d %H:%M:%S").to_string(),
+            "endTimestamp": header.end.format("%Y-%m-%d %H:%M:%S").to_string(),
+            "interval": header.interval_minutes * 60,
+            "expectedRecords": header.expected_records(),
+            "headerLines": header.lines,
+        });
+
+        log::info!("Read {} FDV header from {}", file_type, path);
+        Ok(result.to_string())
+    }
+
+    /// Loads a delivered FDV file as the working data so interim reports and rainfall
+    /// totals can be regenerated when the original CSV is lost. Depth is converted from mm
+    /// to m and flow from m3/s to l/s to match processed files, and rainfall is loaded as
+    /// mm/hr intensity.
+    pub fn load_fdv(&mut self, path: &str) -> Result<String, String> {
+        let reader = FdvReader::open(path).map_err(|e| format!("Error reading FDV file: {}", e))?;
+        let header = reader.header().clone();
+        if header.interval_minutes <= 0 {
+            return Err("FDV interval must be greater than zero".to_string());
+        }
+
+        let mut timestamps = Vec::new();
+        let mut values: Vec<Vec<f64>> = vec![Vec::new(); header.fields.len()];
+        for record in reader {
+            let (timestamp, record) = record.map_err(|e| format!("Error reading FDV file: {}", e))?;
+            timestamps.push(timestamp);
+            for (column, value) in values.iter_mut().zip(record) {
+                column.push(value);
+            }
+        }
+        let (Some(&first), Some(&last)) = (timestamps.first(), timestamps.last()) else {
+            return Err("FDV file contains no records".to_string());
+        };
+
+        let time_col = "Timestamp".to_string();
+        let mut column_mapping = HashMap::new();
+        column_mapping.insert("timestamp".to_string(), vec![(time_col.clone(), 0, None, None)]);
+        let mut series = vec![Series::new(time_col.as_str().into(), timestamps)];
+        for (i, (field, column)) in header.fields.iter().zip(values).enumerate() {
+            let unit = header.units.get(i).map(|u| u.to_uppercase()).unwrap_or_default();
+            let (key, name, scale) = match field.as_str() {
+                "FLOW" => {
+                    let flow_unit = FlowUnit::from_str(&unit).map_err(|e|
+                        format!("Unsupported FDV flow unit: {}", e)
+                    )?;
+                    ("flow", "Flow|l/s", 1.0 / flow_unit.factor())
+                }
+                "DEPTH" if unit == "MM" => ("depth", "Depth|m", 0.001),
+                "DEPTH" => ("depth", "Depth|m", 1.0),
+                "VELOCITY" => ("velocity", "Velocity|m/s", 1.0),
+                "INTENSITY" => ("rainfall", "Rainfall|mm/hr", 1.0),
+                other => {
+                    log::warn!("Skipping unrecognised FDV field '{}'", other);
+                    continue;
+                }
+            };
+            column_mapping.insert(
+                key.to_string(),
+                vec![(name.to_string(), series.len(), None, None)]
+            );
+            let column: Vec<f64> = column
+                .into_iter()
+                .map(|v| v * scale)
+                .collect();
+            series.push(Series::new(name.into(), column));
+        }
+
+        let monitor_type = ["Rainfall", "Flow", "Depth"]
+            .into_iter()
+            .find(|t| column_mapping.contains_key(&t.to_lowercase()))
+            .ok_or("FDV file has no flow, depth or rainfall field")?;
+        let df = DataFrame::new(series).map_err(|e| format!("Error building data frame: {}", e))?;
+        let mut raw_df = df.clone();
+        raw_df
+            .with_column(Series::new(INFILLED_COLUMN.into(), vec![false; df.height()]))
+            .map_err(|e| format!("Error building data frame: {}", e))?;
+        let data_status = header.lines
+            .iter()
+            .find_map(|line| line.strip_prefix("**DATA_STATUS:"))
+            .and_then(|value| value.rsplit(',').next())
+            .and_then(|value| DataStatus::from_str(value.trim()).ok())
+            .unwrap_or_default();
+
+        self.update_from_processed_data(ProcessedFileData {
+            df,
+            start_timestamp: first.format("%Y-%m-%d %H:%M:%S").to_string(),
+            end_timestamp: last.format("%Y-%m-%d %H:%M:%S").to_string(),
+            gaps_filled: 0,
+            invalid_timestamps: 0,
+            interval: Duration::minutes(header.interval_minutes),
+            column_mapping,
+            monitor_type: monitor_type.to_string(),
+            site_id: String::new(),
+            site_name: header.identifier.clone(),
+            time_basis: NAIVE_TIME_BASIS.to_string(),
+            interval_changes: Vec::new(),
+            quality_column: None,
+            trimmed_rows: 0,
+            timestamp_format: "%Y%m%d%H%M".to_string(),
+            gap_fill: GapFillStrategy::Empty,
+            raw_df,
+        });
+        self.filepath = PathBuf::from(path);
+        self.data_status = data_status;
+        if monitor_type == "Rainfall" {
+            self.rainfall_unit = RainfallUnit::MmPerHour;
+        }
+
+        let result =
+            json!({
+            "success": true,
+            "message": "FDV file loaded successfully",
+            "columnMapping": self.column_mapping,
+            "monitorType": self.monitor_type,
+            "availableConversions": self.available_conversions(),
+            "startTimestamp": self.start_timestamp,
+            "endTimestamp": self.end_timestamp,
+            "interval": self.interval.num_seconds(),
+            "siteName": self.site_name,
+            "dataStatus": self.data_status.as_str(),
+            "rainfallUnit": self.rainfall_unit.as_str(),
+        });
+
+        log::info!("Loaded {} FDV file {}", self.monitor_type, path);
+        Ok(result.to_string())
+    }
+
+    pub fn validate_fdv_file(&self, path: &str) -> Result<String, String> {
+        let reader = FdvReader::open(path).map_err(|e| format!("Error reading FDV file: {}", e))?;
+        let summary = reader.summarize().map_err(|e| format!("Error reading FDV file: {}", e))?;
+        let header = &summary.header;
+        let expected_records = header.expected_records();
+
+        let field_stats: Vec<Value> = header.fields
+            .iter()
+            .zip(summary.field_stats.iter())
+            .map(|(field, stats)| {
+                json!({
+                    "field": field,
+                    "count": stats.count,
+                    "min": stats.min,
+                    "max": stats.max,
+                    "mean": stats.mean(),
+                })
+            })
+            .collect();
+
+        let result =
+            json!({
+            "success": true,
+            "path": path,
+            "identifier": header.identifier,
+            "startTimestamp": header.start.format("%Y-%m-%d %H:%M:%S").to_string(),
+            "endTimestamp": header.end.format("%Y-%m-%d %H:%M:%S").to_string(),
+            "interval": header.interval_minutes * 60,
+            "recordCount": summary.record_count,
+            "expectedRecords": expected_records,
+            "valid": summary.record_count == expected_records,
+            "fieldStats": field_stats,
+        });
+
+        log::info!(
+            "FDV file validated: {} records read, {} expected",
+            summary.record_count,
+            expected_records
+        );
+        Ok(result.to_string())
+    }
+
+    fn mapped_column(&self, key: &str) -> Option<String> {
+        self.column_mapping
+            .get(key)
+            .and_then(|v| v.first())
+            .map(|(name, _, _, _)| name.clone())
+    }
+
+    fn column_values(&self, column: &str) -> Result<Vec<Option<f64>>, String> {
+        let df = self.data_frame.as_ref().ok_or("No data frame available")?;
+        let series = df
+            .column(column)
+            .and_then(|s| s.cast(&DataType::Float64))
+            .map_err(|e| format!("Error reading column '{}': {}", column, e))?;
+        let values = series
+            .f64()
+            .map_err(|e| format!("Error reading column '{}': {}", column, e))?
+            .into_iter()
+            .map(|v| v.filter(|v| !v.is_nan()))
+            .collect();
+        Ok(values)
+    }
+
+    /// Values of every mapped measurement column, keyed by column name.
+    fn mapped_value_columns(&self) -> Result<Vec<(String, Vec<Option<f64>>)>, String> {
+        let mut value_columns = Vec::new();
+        for (key, columns) in &self.column_mapping {
+            if key != "timestamp" {
+                for (name, _, _, _) in columns {
+                    value_columns.push((name.clone(), self.column_values(name)?));
+                }
+            }
+        }
+        Ok(value_columns)
+    }
+
+    fn timestamps(&self) -> Result<Vec<Option<NaiveDateTime>>, String> {
+        let df = self.data_frame.as_ref().ok_or("No data frame available")?;
+        let time_col = self.time_col.as_ref().ok_or("No timestamp column identified")?;
+        let timestamps = df
+            .column(time_col)
+            .and_then(|s| s.datetime().map(|dt| dt.as_datetime_iter().collect()))
+            .map_err(|e| format!("Error reading timestamp column: {}", e))?;
+        Ok(timestamps)
+    }
+
+    /// Shifts the depth/level column by `offset_m` metres, e.g. from a logger datum to the pipe
+    /// invert. Readings that end up below zero are clamped to zero unless `clamp_negative` is
+    /// false, in which case they are kept and only counted. Offsets accumulate until the file
+    /// is reprocessed.
+    pub fn apply_datum_offset(
+        &mut self,
+        offset_m: f64,
+        clamp_negative: bool
+    ) -> Result<String, String> {
+        if !offset_m.is_finite() {
+            return Err(format!("Invalid datum offset: {}", offset_m));
+        }
+        let depth_col = self.mapped_column("depth").ok_or("No depth column identified")?;
+        let offset = if is_depth_in_mm(depth_col) { offset_m * 1000.0 } else { offset_m };
+
+        let mut negative_readings = 0;
+        let shifted: Vec<f64> = self
+            .column_values(&depth_col)?
+            .into_iter()
+            .map(|value| {
+                let Some(value) = value else {
+                    return f64::NAN;
+                };
+                let shifted = value + offset;
+                if shifted < 0.0 {
+                    negative_readings += 1;
+                    if clamp_negative {
+                        return 0.0;
+                    }
+                }
+                shifted
+            })
+            .collect();
+
+        let df = self.data_frame.as_mut().ok_or("No data frame available")?;
+        df.with_column(Series::new(depth_col.as_str().into(), shifted)).map_err(|e|
+            format!("Error updating depth column '{}': {}", depth_col, e)
+        )?;
+        self.datum_offset_m += offset_m;
+        self.mark_data_status(DataStatus::Edited);
+
+        log::info!(
+            "Applied datum offset of {} m to '{}' ({} negative readings, total offset {} m)",
+            offset_m,
+            depth_col,
+            negative_readings,
+            self.datum_offset_m
+        );
+
+        let result =
+            json!({
+            "success": true,
+            "message": "Datum offset applied successfully",
+            "depthColumn": depth_col,
+            "offsetM": offset_m,
+            "totalOffsetM": self.datum_offset_m,
+            "negativeReadings": negative_readings,
+            "negativesClamped": clamp_negative,
+            "dataStatus": self.data_status.as_str(),
+        });
+        Ok(result.to_string())
+    }
+
+    /// Measures how far reading times wander from a perfectly regular series at the
+    /// detected interval. Each reading is assigned its nominal slot (gaps advance the slot
+    /// by the number of whole intervals skipped) and its offset from that slot is the drift.
+    /// Reports the cumulative drift over the record, the least-squares drift rate and
+    /// whether drift is linear, with a suggestion of a shift or a resample.
+    pub fn detect_clock_drift(&self) -> Result<String, String> {
+        let interval = self.interval.num_seconds();
+        if interval <= 0 {
+            return Err("Interval has not been determined".to_string());
+        }
+        let value_columns: Vec<Vec<Option<f64>>> = self
+            .mapped_value_columns()?
+            .into_iter()
+            .map(|(_, values)| values)
+            .collect();
+        let timestamps: Vec<NaiveDateTime> = self
+            .timestamps()?
+            .into_iter()
+            .enumerate()
+            .filter(|(row, _)| value_columns.iter().any(|values| values[*row].is_some()))
+            .filter_map(|(_, timestamp)| timestamp)
+            .collect();
+        let Some(&first) = timestamps.first() else {
+            return Err("No readings available".to_string());
+        };
+
+        // (elapsed seconds, drift seconds) per reading
+        let mut slot = 0i64;
+        let mut points = vec![(0.0, 0.0)];
+        for pair in timestamps.windows(2) {
+            let step = ((pair[1] - pair[0]).num_milliseconds() as f64) / 1000.0;
+            slot += ((step / (interval as f64)).round() as i64).max(1);
+            let elapsed = ((pair[1] - first).num_milliseconds() as f64) / 1000.0;
+            points.push((elapsed, elapsed - ((slot * interval) as f64)));
+        }
+
+        let (slope, _, r_squared) = Self::linear_fit(&points);
+
+        let cumulative = points.last().map_or(0.0, |(_, y)| *y);
+        let max_drift = points
+            .iter()
+            .map(|(_, y)| y.abs())
+            .fold(0.0, f64::max);
+        let tolerance = (interval as f64) * DRIFT_TOLERANCE_FRACTION;
+        let linear = max_drift > tolerance && r_squared >= LINEAR_DRIFT_R2;
+        let recommendation = if max_drift <= tolerance { "shift" } else { "resample" };
+
+        let result =
+            json!({
+            "success": true,
+            "interval": interval,
+            "readings": timestamps.len(),
+            "cumulativeDriftSeconds": cumulative,
+            "maxDriftSeconds": max_drift,
+            "driftSecondsPerDay": slope * 86_400.0,
+            "rSquared": r_squared,
+            "linear": linear,
+            "withinTolerance": max_drift <= tolerance,
+            "recommendation": recommendation,
+        });
+        Ok(result.to_string())
+    }
+
+    /// Corrects a logger clock error by moving every timestamp by `offset_seconds` (negative
+    /// moves them earlier), including the untrimmed copy used for antecedent rainfall.
+    /// Shifts accumulate until the file is reprocessed and are recorded in provenance.
+    pub fn shift_timestamps(&mut self, offset_seconds: i64) -> Result<String, String> {
+        if offset_seconds == 0 {
+            return Err("Clock shift must be non-zero".to_string());
+        }
+        let time_col = self.time_col.clone().ok_or("No timestamp column identified")?;
+        let offset = Duration::seconds(offset_seconds);
+        let format = "%Y-%m-%d %H:%M:%S";
+        let start = NaiveDateTime::parse_from_str(&self.start_timestamp, format).map_err(|e|
+            format!("Invalid start timestamp: {}", e)
+        )?;
+        let end = NaiveDateTime::parse_from_str(&self.end_timestamp, format).map_err(|e|
+            format!("Invalid end timestamp: {}", e)
+        )?;
+
+        let df = self.data_frame.as_mut().ok_or("No data frame available")?;
+        Self::shift_time_column(df, &time_col, offset)?;
+        let frames = [self.source_data_frame.as_mut(), self.raw_data_frame.as_mut()];
+        for frame in frames.into_iter().flatten() {
+            Self::shift_time_column(frame, &time_col, offset)?;
+        }
+        self.start_timestamp = (start + offset).format(format).to_string();
+        self.end_timestamp = (end + offset).format(format).to_string();
+        self.clock_shift_seconds += offset_seconds;
+        self.mark_data_status(DataStatus::Edited);
+
+        log::info!(
+            "Shifted timestamps by {} s (total clock shift {} s). New range: {} to {}",
+            offset_seconds,
+            self.clock_shift_seconds,
+            self.start_timestamp,
+            self.end_timestamp
+        );
+
+        let result =
+            json!({
+            "success": true,
+            "message": "Timestamps shifted successfully",
+            "offsetSeconds": offset_seconds,
+            "totalShiftSeconds": self.clock_shift_seconds,
+            "startTimestamp": self.start_timestamp,
+            "endTimestamp": self.end_timestamp,
+            "dataStatus": self.data_status.as_str(),
+        });
+        Ok(result.to_string())
+    }
+
+    /// Adds `offset` to every timestamp in `time_col`, keeping the column's datetime type.
+    fn shift_time_column(
+        df: &mut DataFrame,
+        time_col: &str,
+        offset: Duration
+    ) -> Result<(), String> {
+        let series = df.column(time_col).map_err(|e| e.to_string())?;
+        let dtype = series.dtype().clone();
+        let shifted: Vec<Option<NaiveDateTime>> = series
+            .datetime()
+            .map_err(|e| format!("Error reading timestamp column: {}", e))?
+            .as_datetime_iter()
+            .map(|timestamp| timestamp.map(|t| t + offset))
+            .collect();
+        let shifted = Series::new(time_col.into(), shifted)
+            .cast(&dtype)
+            .map_err(|e| e.to_string())?;
+        df.with_column(shifted).map_err(|e| format!("Error updating timestamp column: {}", e))?;
+        Ok(())
+    }
+
+    /// Corrects the declared unit of a mapped column without re-reading the file, e.g. a
+    /// depth logged in cm but labelled mm. Units the conversions read directly (m or mm
+    /// depth, l/s flow, m/s velocity) only relabel the column, since conversions take the
+    /// unit from its `|unit` suffix; others are converted to one of these. Returns the
+    /// column's new name, which later conversions must use.
+    pub fn set_column_unit(&mut self, column: &str, unit: &str) -> Result<String, String> {
+        let key = self.column_mapping
+            .iter()
+            .find(|(key, columns)| {
+                key.as_str() != "timestamp" && columns.iter().any(|(name, ..)| name == column)
+            })
+            .map(|(key, _)| key.clone())
+            .ok_or_else(|| format!("'{}' is not a mapped measurement column", column))?;
+
+        let unit = unit.trim().to_lowercase();
+        let (factor, label) = match (key.as_str(), unit.as_str()) {
+            ("depth", "m") => (1.0, "m"),
+            ("depth", "mm") => (1.0, "mm"),
+            ("depth", "cm") => (10.0, "mm"),
+            ("flow", "l/s") => (1.0, "l/s"),
+            ("flow", "m3/s") => (1000.0, "l/s"),
+            ("velocity", "m/s") => (1.0, "m/s"),
+            ("velocity", "cm/s") => (0.01, "m/s"),
+            ("velocity", "mm/s") => (0.001, "m/s"),
+            ("rainfall", _) => {
+                return Err("Rainfall units are set with the rainfall unit option".to_string());
+            }
+            _ => {
+                return Err(format!("'{}' is not a supported unit for {} columns", unit, key));
+            }
+        };
+
+        // Replace an existing unit suffix rather than stacking another on
+        let base = split_unit(column).map_or(column, |(base, _)| base);
+        let new_name = format!("{}|{}", base, label);
+        let exists = self.data_frame.as_ref().is_some_and(|df| df.column(&new_name).is_ok());
+        if new_name != column && exists {
+            return Err(format!("A column named '{}' already exists", new_name));
+        }
+
+        let frames = [self.data_frame.as_mut(), self.source_data_frame.as_mut()];
+        for df in frames.into_iter().flatten() {
+            if factor != 1.0 {
+                let scaled: Vec<f64> = df
+                    .column(column)
+                    .and_then(|s| s.cast(&DataType::Float64))
+                    .and_then(|s| {
+                        Ok(
+                            s
+                                .f64()?
+                                .into_iter()
+                                .map(|v| v.map_or(f64::NAN, |v| v * factor))
+                                .collect()
+                        )
+                    })
+                    .map_err(|e| format!("Error reading column '{}': {}", column, e))?;
+                df.with_column(Series::new(column.into(), scaled)).map_err(|e|
+                    format!("Error updating column '{}': {}", column, e)
+                )?;
+            }
+            df.rename(column, new_name.as_str().into()).map_err(|e|
+                format!("Error renaming column '{}': {}", column, e)
+            )?;
+        }
+        if let Some(raw) = self.raw_data_frame.as_mut() {
+            raw.rename(column, new_name.as_str().into()).map_err(|e|
+                format!("Error renaming column '{}': {}", column, e)
+            )?;
+        }
+        for (name, ..) in self.column_mapping.values_mut().flatten() {
+            if name == column {
+                *name = new_name.clone();
+            }
+        }
+        if factor != 1.0 {
+            self.mark_data_status(DataStatus::Edited);
+        }
+
+        log::info!(
+            "Declared unit of '{}' set to {} (stored as '{}', factor {})",
+            column,
+            unit,
+            new_name,
+            factor
+        );
+
+        let result =
+            json!({
+            "success": true,
+            "message": "Column unit updated successfully",
+            "column": new_name,
+            "previousColumn": column,
+            "unit": unit,
+            "storedUnit": label,
+            "factor": factor,
+            "columnMapping": self.column_mapping,
+        });
+        Ok(result.to_string())
+    }
+
+    /// Least squares line through `points`, as (slope, intercept, r²). The slope and r² are
+    /// zero when the x values don't vary.
+    fn linear_fit(points: &[(f64, f64)]) -> (f64, f64, f64) {
+        let n = points.len() as f64;
+        let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+        let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+        let sxx: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+        let syy: f64 = points.iter().map(|(_, y)| (y - mean_y).powi(2)).sum();
+        let sxy: f64 = points.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+        let slope = if sxx > 0.0 { sxy / sxx } else { 0.0 };
+        let r_squared = if sxx > 0.0 && syy > 0.0 { (sxy * sxy) / (sxx * syy) } else { 0.0 };
+        (slope, mean_y - slope * mean_x, r_squared)
+    }
+
+    /// Returns the inclusive index ranges of runs of at least `min_run` identical
+    /// consecutive values. Missing values break a run.
+    fn find_flatlines(values: &[Option<f64>], min_run: usize) -> Vec<(usize, usize)> {
+        let mut runs = Vec::new();
+        let mut run_start = 0;
+
+        for i in 1..=values.len() {
+            let continues =
+                i < values.len() && values[i].is_some() && values[i] == values[run_start];
+            if !continues {
+                if values[run_start].is_some() && i - run_start >= min_run {
+                    runs.push((run_start, i - 1));
+                }
+                run_start = i;
+            }
+        }
+
+        runs
+    }
+
+    pub fn detect_flatlines(&self, column: &str, min_run: usize) -> Result<String, String> {
+        if min_run < 2 {
+            return Err("Minimum run length must be at least 2".to_string());
+        }
+        let values = self.column_values(column)?;
+        let timestamps = self.timestamps()?;
+        let format_ts = |i: usize| {
+            timestamps
+                .get(i)
+                .copied()
+                .flatten()
+                .map(|ts| ts.format("%Y-%m-%d %H:%M:%S").to_string())
+        };
+
+        let runs: Vec<Value> = Self::find_flatlines(&values, min_run)
+            .into_iter()
+            .map(|(start, end)| {
+                json!({
+                    "start": format_ts(start),
+                    "end": format_ts(end),
+                    "length": end - start + 1,
+                    "value": values[start],
+                })
+            })
+            .collect();
+
+        let result =
+            json!({
+            "success": true,
+            "column": column,
+            "minRun": min_run,
+            "count": runs.len(),
+            "runs": runs,
+        });
+
+        log::info!("Detected {} flatline runs in column '{}'", runs.len(), column);
+        Ok(result.to_string())
+    }
+
+    /// Returns the inclusive index ranges of consecutive missing values.
+    fn find_null_runs(values: &[Option<f64>]) -> Vec<(usize, usize)> {
+        let mut runs = Vec::new();
+        let mut run_start = None;
+
+        for (i, value) in values.iter().enumerate() {
+            match (value, run_start) {
+                (None, None) => {
+                    run_start = Some(i);
+                }
+                (Some(_), Some(start)) => {
+                    runs.push((start, i - 1));
+                    run_start = None;
+                }
+                _ => {}
+            }
+        }
+        if let Some(start) = run_start {
+            runs.push((start, values.len() - 1));
+        }
+
+        runs
+    }
+
+    /// Re-reads the loaded file and lists timestamps that occur on more than one row, with
+    /// each row's values, so the user can decide how duplicates should be combined.
+    pub fn find_duplicate_timestamps(&self) -> Result<String, String> {
+        if self.filepath.as_os_str().is_empty() {
+            return Err("No file has been processed yet".to_string());
+        }
+        let file_path = self.filepath.to_string_lossy().to_string();
+        let (headers, duplicates) = FileProcessor::new(None)
+            .find_duplicate_timestamps(&file_path)
+            .map_err(|e| format!("Error finding duplicate timestamps: {}", e))?;
+
+        let result =
+            json!({
+            "success": true,
+            "headers": headers,
+            "count": duplicates.len(),
+            "duplicates": duplicates,
+        });
+
+        log::info!("Found {} duplicated timestamps in {}", duplicates.len(), file_path);
+        Ok(result.to_string())
+    }
+
+    /// Lists where `column` is missing, grouped into runs of consecutive null readings.
+    /// At most `max` runs are returned; the totals always cover the whole record.
+    pub fn null_timestamps(&self, column: &str, max: usize) -> Result<String, String> {
+        let values = self.column_values(column)?;
+        let timestamps = self.timestamps()?;
+        let format_ts = |i: usize| {
+            timestamps
+                .get(i)
+                .copied()
+                .flatten()
+                .map(|ts| ts.format("%Y-%m-%d %H:%M:%S").to_string())
+        };
+
+        let null_runs = Self::find_null_runs(&values);
+        let null_readings: usize = null_runs
+            .iter()
+            .map(|(start, end)| end - start + 1)
+            .sum();
+        let runs: Vec<Value> = null_runs
+            .iter()
+            .take(max)
+            .map(|&(start, end)| {
+                json!({
+                    "start": format_ts(start),
+                    "end": format_ts(end),
+                    "length": end - start + 1,
+                })
+            })
+            .collect();
+
+        let result =
+            json!({
+            "success": true,
+            "column": column,
+            "nullReadings": null_readings,
+            "totalRuns": null_runs.len(),
+            "truncated": null_runs.len() > max,
+            "runs": runs,
+        });
+
+        log::info!(
+            "Found {} null readings in {} runs in column '{}'",
+            null_readings,
+            null_runs.len(),
+            column
+        );
+        Ok(result.to_string())
+    }
+
+    /// Lists runs of `column` readings below a sensor's detection `limit`. `mode` "floor"
+    /// raises them to the limit and "null" clears them before conversion; "report" (the
+    /// default) leaves the data untouched.
+    pub fn readings_below_limit(
+        &mut self,
+        column: &str,
+        limit: f64,
+        mode: Option<&str>
+    ) -> Result<String, String> {
+        if !limit.is_finite() {
+            return Err(format!("Invalid detection limit: {}", limit));
+        }
+        let mode = mode.unwrap_or("report").trim().to_lowercase();
+        let replacement = match mode.as_str() {
+            "report" => None,
+            "floor" => Some(limit),
+            "null" => Some(f64::NAN),
+            _ => {
+                return Err(
+                    format!("Invalid mode: {}. Expected 'report', 'floor' or 'null'", mode)
+                );
+            }
+        };
+
+        let values = self.column_values(column)?;
+        let timestamps = self.timestamps()?;
+        let format_ts = |i: usize| {
+            timestamps
+                .get(i)
+                .copied()
+                .flatten()
+                .map(|ts| ts.format("%Y-%m-%d %H:%M:%S").to_string())
+        };
+
+        // Below-limit readings as the "missing" values, so runs group like null runs
+        let marked: Vec<Option<f64>> = values
+            .iter()
+            .map(|value| match value {
+                Some(v) if *v < limit => None,
+                _ => Some(0.0),
+            })
+            .collect();
+        let below_runs = Self::find_null_runs(&marked);
+        let below_readings: usize = below_runs
+            .iter()
+            .map(|(start, end)| end - start + 1)
+            .sum();
+        let runs: Vec<Value> = below_runs
+            .iter()
+            .map(|&(start, end)| {
+                json!({
+                    "start": format_ts(start),
+                    "end": format_ts(end),
+                    "length": end - start + 1,
+                })
+            })
+            .collect();
+
+        if let (Some(replacement), true) = (replacement, below_readings > 0) {
+            let replaced: Vec<f64> = values
+                .iter()
+                .map(|value| match value {
+                    Some(v) if *v < limit => replacement,
+                    Some(v) => *v,
+                    None => f64::NAN,
+                })
+                .collect();
+            let df = self.data_frame.as_mut().ok_or("No data frame available")?;
+            df.with_column(Series::new(column.into(), replaced)).map_err(|e|
+                format!("Error updating column '{}': {}", column, e)
+            )?;
+            self.mark_data_status(DataStatus::Edited);
+        }
+
+        log::info!(
+            "Found {} readings below {} in {} runs in column '{}' (mode: {})",
+            below_readings,
+            limit,
+            below_runs.len(),
+            column,
+            mode
+        );
+
+        let result =
+            json!({
+            "success": true,
+            "column": column,
+            "limit": limit,
+            "mode": mode,
+            "belowReadings": below_readings,
+            "totalRuns": below_runs.len(),
+            "runs": runs,
+            "dataStatus": self.data_status.as_str(),
+        });
+        Ok(result.to_string())
+    }
+
+    /// Headline coverage of the monitoring period for report cover pages: the readings the
+    /// start, end and interval imply against the rows holding at least one reading, with a
+    /// per-column breakdown.
+    pub fn coverage_summary(&self) -> Result<String, String> {
+        let interval_seconds = self.interval.num_seconds();
+        if interval_seconds <= 0 {
+            return Err("Interval has not been determined".to_string());
+        }
+        let format = "%Y-%m-%d %H:%M:%S";
+        let start = NaiveDateTime::parse_from_str(&self.start_timestamp, format).map_err(|e|
+            format!("Invalid start timestamp: {}", e)
+        )?;
+        let end = NaiveDateTime::parse_from_str(&self.end_timestamp, format).map_err(|e|
+            format!("Invalid end timestamp: {}", e)
+        )?;
+        let span_seconds = (end - start).num_seconds();
+        let expected = (span_seconds / interval_seconds + 1).max(0) as usize;
+        let percent = |actual: usize| {
+            if expected > 0 { ((actual as f64) / (expected as f64)) * 100.0 } else { 0.0 }
+        };
+
+        // Only rows within the record count, so they can't exceed the expected readings
+        let in_range: Vec<bool> = self
+            .timestamps()?
+            .iter()
+            .map(|timestamp| timestamp.is_some_and(|t| t >= start && t <= end))
+            .collect();
+        let value_columns = self.mapped_value_columns()?;
+        let actual = (0..in_range.len())
+            .filter(|&row| {
+                in_range[row] && value_columns.iter().any(|(_, values)| values[row].is_some())
+            })
+            .count();
+        let columns: Vec<Value> = value_columns
+            .iter()
+            .map(|(name, values)| {
+                let present = values
+                    .iter()
+                    .zip(&in_range)
+                    .filter(|&(v, &in_range)| in_range && v.is_some())
+                    .count();
+                json!({
+                    "column": name,
+                    "actualReadings": present,
+                    "coveragePercent": percent(present),
+                })
+            })
+            .collect();
+
+        let result =
+            json!({
+            "success": true,
+            "startTimestamp": self.start_timestamp,
+            "endTimestamp": self.end_timestamp,
+            "interval": interval_seconds,
+            "totalDays": (span_seconds + interval_seconds) as f64 / 86_400.0,
+            "expectedReadings": expected,
+            "actualReadings": actual,
+            "missingReadings": expected.saturating_sub(actual),
+            "coveragePercent": percent(actual),
+            "columns": columns,
+        });
+        Ok(result.to_string())
+    }
+
+    /// Per-day expected and actual reading counts for a completeness heatmap. A reading
+    /// counts as present when any mapped measurement column has a value; the expected count
+    /// is a full day at the current interval, so partial first and last days show as
+    /// incomplete.
+    pub fn daily_completeness(&self) -> Result<String, String> {
+        let interval_seconds = self.interval.num_seconds();
+        if interval_seconds <= 0 {
+            return Err("Interval has not been determined".to_string());
+        }
+        // Intervals over a day still expect the one reading a logged day can hold
+        let expected = ((86_400 / interval_seconds) as usize).max(1);
+
+        let timestamps = self.timestamps()?;
+        let value_columns: Vec<Vec<Option<f64>>> = self
+            .mapped_value_columns()?
+            .into_iter()
+            .map(|(_, values)| values)
+            .collect();
+
+        let mut days: BTreeMap<NaiveDate, usize> = BTreeMap::new();
+        for (row, timestamp) in timestamps.iter().enumerate() {
+            let Some(timestamp) = timestamp else {
+                continue;
+            };
+            let present = value_columns.iter().any(|values| values[row].is_some());
+            *days.entry(timestamp.date()).or_insert(0) += present as usize;
+        }
+
+        let days: Vec<Value> = days
+            .into_iter()
+            .map(|(date, actual)| {
+                json!({
+                    "date": date.format("%Y-%m-%d").to_string(),
+                    "expected": expected,
+                    "actual": actual,
+                    "completeness": (actual as f64) / (expected as f64),
+                })
+            })
+            .collect();
+
+        let result =
+            json!({
+            "success": true,
+            "interval": interval_seconds,
+            "days": days,
+        });
+        Ok(result.to_string())
+    }
+
+    /// Compares the detected interval with the one the operator expects. Spacings are taken
+    /// between rows holding a reading, so gap-filled rows don't hide the logger's real
+    /// rate; whole multiples of the expected interval are reported as gaps rather than as
+    /// deviations.
+    pub fn check_expected_interval(&self, expected_minutes: i64) -> Result<String, String> {
+        if expected_minutes <= 0 {
+            return Err("Expected interval must be greater than zero".to_string());
+        }
+        let expected = expected_minutes * 60;
+
+        let value_columns: Vec<Vec<Option<f64>>> = self
+            .mapped_value_columns()?
+            .into_iter()
+            .map(|(_, values)| values)
+            .collect();
+        let timestamps: Vec<NaiveDateTime> = self
+            .timestamps()?
+            .into_iter()
+            .enumerate()
+            .filter(|(row, _)| value_columns.iter().any(|values| values[*row].is_some()))
+            .filter_map(|(_, timestamp)| timestamp)
+            .collect();
+
+        let mut histogram: Vec<(i64, usize)> = FileProcessor::interval_histogram(&timestamps)
+            .into_iter()
+            .map(|(spacing, count)| (spacing.num_seconds(), count))
+            .collect();
+        histogram.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        let total: usize = histogram.iter().map(|(_, count)| count).sum();
+        let count_where = |f: &dyn Fn(i64) -> bool| {
+            histogram
+                .iter()
+                .filter(|(seconds, _)| f(*seconds))
+                .map(|(_, count)| count)
+                .sum::<usize>()
+        };
+        let matching = count_where(&|seconds| seconds == expected);
+        let gaps = count_where(&|seconds| seconds > expected && seconds % expected == 0);
+        let deviating = total - matching - gaps;
+        let detected = self.interval.num_seconds();
+
+        let result =
+            json!({
+            "success": true,
+            "expectedMinutes": expected_minutes,
+            "detectedMinutes": (detected as f64) / 60.0,
+            "matches": detected == expected,
+            "differenceMinutes": ((detected - expected) as f64) / 60.0,
+            "spacings": total,
+            "gapSpacings": gaps,
+            "deviatingSpacings": deviating,
+            "deviatingFraction": if total > 0 { (deviating as f64) / (total as f64) } else { 0.0 },
+            "histogram": histogram
+                .iter()
+                .take(10)
+                .map(|(seconds, count)| json!({ "seconds": seconds, "count": count }))
+                .collect::<Vec<_>>(),
+        });
+        Ok(result.to_string())
+    }
+
+    /// Estimates dry weather flow as the mean of the nighttime minimum flows on dry days,
+    /// returned in l/s and m³/day. The night window (`HH:MM`, default 03:00 to 05:00) may
+    /// cross midnight. Dry days are taken from `dry_days` (`YYYY-MM-DD`) when given,
+    /// otherwise from days whose mean flow is within `WET_DAY_RATIO` of the median.
+    pub fn estimate_dwf(
+        &self,
+        window_start: Option<&str>,
+        window_end: Option<&str>,
+        dry_days: Option<&[String]>
+    ) -> Result<String, String> {
+        if self.monitor_type != "Flow" {
+            return Err(
+                format!("DWF is only available for Flow monitors, not {}", self.monitor_type)
+            );
+        }
+        let (window_start, window_end) = Self::night_window(window_start, window_end)?;
+        let dry_days = dry_days
+            .map(|days| {
+                days.iter()
+                    .map(|day| {
+                        NaiveDate::parse_from_str(day, "%Y-%m-%d").map_err(|e|
+                            format!("Invalid dry day '{}': {}", day, e)
+                        )
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .transpose()?;
+        let (flow_col, days) = self.daily_night_minima(window_start, window_end)?;
+
+        let daily_mean = |(sum, count, _): &(f64, usize, Option<f64>)| sum / (*count as f64);
+        let wet_threshold = {
+            let mut means: Vec<f64> = days.values().map(daily_mean).collect();
+            means.sort_by(|a, b| a.total_cmp(b));
+            means.get(means.len() / 2).map(|median| median * WET_DAY_RATIO)
+        };
+        let night_minima: Vec<(NaiveDate, f64)> = days
+            .iter()
+            .filter(|(date, day)| {
+                match &dry_days {
+                    Some(dry_days) => dry_days.contains(*date),
+                    None => wet_threshold.is_some_and(|threshold| daily_mean(*day) <= threshold),
+                }
+            })
+            .filter_map(|(date, (_, _, min))| min.map(|min| (*date, min)))
+            .collect();
+        if night_minima.is_empty() {
+            return Err("No dry days with readings in the night window".to_string());
+        }
+        let dwf =
+            night_minima
+                .iter()
+                .map(|(_, min)| min)
+                .sum::<f64>() / (night_minima.len() as f64);
+
+        let result =
+            json!({
+            "success": true,
+            "flowColumn": flow_col,
+            "dwf": dwf,
+            "dwfM3PerDay": dwf * 86.4,
+            "windowStart": window_start.format("%H:%M").to_string(),
+            "windowEnd": window_end.format("%H:%M").to_string(),
+            "dryDays": night_minima.len(),
+            "totalDays": days.len(),
+            "nightMinima": night_minima
+                .iter()
+                .map(|(date, min)| {
+                    json!({ "date": date.format("%Y-%m-%d").to_string(), "flow": min })
+                })
+                .collect::<Vec<_>>(),
+        });
+        Ok(result.to_string())
+    }
+
+    /// Estimates groundwater infiltration from the trend in nightly minimum flow: the night
+    /// window minimum of each day is fitted against the day by least squares. A rising or
+    /// falling slope shows infiltration changing over the record; the fitted values at the
+    /// first and last day bound the base flow it contributes.
+    pub fn estimate_infiltration(
+        &self,
+        window_start: Option<&str>,
+        window_end: Option<&str>
+    ) -> Result<String, String> {
+        if self.monitor_type != "Flow" {
+            return Err(
+                format!(
+                    "Infiltration is only available for Flow monitors, not {}",
+                    self.monitor_type
+                )
+            );
+        }
+        let (window_start, window_end) = Self::night_window(window_start, window_end)?;
+        let (flow_col, days) = self.daily_night_minima(window_start, window_end)?;
+
+        let night_minima: Vec<(NaiveDate, f64)> = days
+            .iter()
+            .filter_map(|(date, (_, _, min))| min.map(|min| (*date, min)))
+            .collect();
+        let Some(&(first_day, _)) = night_minima.first() else {
+            return Err("No readings in the night window".to_string());
+        };
+        let points: Vec<(f64, f64)> = night_minima
+            .iter()
+            .map(|(date, min)| ((*date - first_day).num_days() as f64, *min))
+            .collect();
+
+        let (slope, intercept, r_squared) = Self::linear_fit(&points);
+        let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / (points.len() as f64);
+        let last_x = points.last().map_or(0.0, |(x, _)| *x);
+
+        let result =
+            json!({
+            "success": true,
+            "flowColumn": flow_col,
+            "windowStart": window_start.format("%H:%M").to_string(),
+            "windowEnd": window_end.format("%H:%M").to_string(),
+            "days": night_minima.len(),
+            "meanNightMinimum": mean_y,
+            "trendPerDay": slope,
+            "rSquared": r_squared,
+            "fittedStart": intercept,
+            "fittedEnd": intercept + slope * last_x,
+            "fittedEndM3PerDay": (intercept + slope * last_x) * 86.4,
+            "nightMinima": night_minima
+                .iter()
+                .map(|(date, min)| {
+                    json!({ "date": date.format("%Y-%m-%d").to_string(), "flow": min })
+                })
+                .collect::<Vec<_>>(),
+        });
+        Ok(result.to_string())
+    }
+
+    /// Parses an `HH:MM` night window, defaulting to `DEFAULT_DWF_WINDOW`.
+    fn night_window(
+        window_start: Option<&str>,
+        window_end: Option<&str>
+    ) -> Result<(NaiveTime, NaiveTime), String> {
+        let parse_time = |time: &str| {
+            NaiveTime::parse_from_str(time, "%H:%M").map_err(|e|
+                format!("Invalid window time '{}': {}", time, e)
             )
-            .map_err(|e| format!("Error setting Rainfall parameter: {}", e))?;
+        };
+        Ok((
+            parse_time(window_start.unwrap_or(DEFAULT_DWF_WINDOW.0))?,
+            parse_time(window_end.unwrap_or(DEFAULT_DWF_WINDOW.1))?,
+        ))
+    }
 
-        rainfall_creator
-            .create_fdv_rainfall()
-            .map_err(|e| format!("Error creating FDV flow: {}", e))?;
+    /// Groups flow by day: the sum and count of all flows, and the minimum within the night
+    /// window. A window that wraps past midnight is one night, kept under the day it starts;
+    /// the part of a night whose start is before the record is dropped. Returns the flow
+    /// column used alongside.
+    fn daily_night_minima(
+        &self,
+        window_start: NaiveTime,
+        window_end: NaiveTime
+    ) -> Result<(String, DailyNightFlow), String> {
+        let in_window = |time: NaiveTime| {
+            if window_start <= window_end {
+                time >= window_start && time < window_end
+            } else {
+                time >= window_start || time < window_end
+            }
+        };
 
-        let null_readings = rainfall_creator.get_null_readings();
+        let flow_col = self.mapped_column("flow").ok_or("No flow column found")?;
+        let flows = self.column_values(&flow_col)?;
+
+        let wraps = window_start > window_end;
+
+        let mut days = DailyNightFlow::new();
+        let mut night_minima: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+        for (timestamp, flow) in self.timestamps()?.into_iter().zip(flows) {
+            let (Some(timestamp), Some(flow)) = (timestamp, flow) else {
+                continue;
+            };
+            let day = days.entry(timestamp.date()).or_insert((0.0, 0, None));
+            day.0 += flow;
+            day.1 += 1;
+            if in_window(timestamp.time()) {
+                let night = if wraps && timestamp.time() < window_end {
+                    timestamp.date() - Duration::days(1)
+                } else {
+                    timestamp.date()
+                };
+                let min = night_minima.entry(night).or_insert(flow);
+                *min = min.min(flow);
+            }
+        }
+        for (night, min) in night_minima {
+            if let Some(day) = days.get_mut(&night) {
+                day.2 = Some(min);
+            }
+        }
+        Ok((flow_col, days))
+    }
+
+    /// Largest reading of the monitor's primary column and when it occurred: flow and depth
+    /// for flow monitors, depth for depth monitors and intensity for rainfall. Values are in
+    /// the column's own units; a column without readings reports a null peak.
+    pub fn peak_reading(&self) -> Result<String, String> {
+        let keys: &[&str] = match self.monitor_type.as_str() {
+            "Flow" => &["flow", "depth"],
+            "Depth" => &["depth"],
+            "Rainfall" => &["rainfall"],
+            other => {
+                return Err(format!("Peak readings are not available for {} monitors", other));
+            }
+        };
+        let timestamps = self.timestamps()?;
+
+        let mut peaks = Vec::new();
+        for key in keys {
+            let column = self
+                .mapped_column(key)
+                .ok_or_else(|| format!("No {} column identified", key))?;
+            let values = self.column_values(&column)?;
+            let series = values.iter().copied().collect::<Float64Chunked>().into_series();
+            let peak = series.arg_max().map(|row| {
+                let timestamp = timestamps[row].map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string());
+                (values[row], timestamp)
+            });
+            peaks.push(
+                json!({
+                "key": key,
+                "column": column,
+                "value": peak.as_ref().and_then(|(value, _)| *value),
+                "timestamp": peak.and_then(|(_, timestamp)| timestamp),
+            })
+            );
+        }
 
         let result =
             json!({
             "success": true,
-            "message": "Rainfall creation initiated",
-            "outputPath": output_path,
-            "rainfallColumn": rainfall_col,
-            "nullReadings": null_readings
+            "monitorType": self.monitor_type,
+            "peaks": peaks,
         });
+        Ok(result.to_string())
+    }
 
-        log::info!("Rainfall creation successfully. Output: {}", output_path);
-        log::info!("Null readings: {}", null_readings);
+    /// Annual maximum flow per calendar year, with its timestamp and the year's coverage, and
+    /// Gumbel (method of moments) estimates of the 2, 5 and 10 year flows once at least
+    /// `MIN_EXTREME_YEARS` years exist. Partial years understate their maximum, so years
+    /// below `MIN_EXTREME_COVERAGE_PERCENT` are listed but left out of the estimates; a few
+    /// years of record can't support return periods much beyond the record length, so the
+    /// estimates are indicative only.
+    pub fn flow_extremes(&self) -> Result<String, String> {
+        let flow_col = self.mapped_column("flow").ok_or("No flow column identified")?;
+        let values = self.column_values(&flow_col)?;
+        let timestamps = self.timestamps()?;
+        let interval_seconds = self.interval.num_seconds();
+
+        // Per year: the maximum flow and its timestamp, and the number of readings
+        let mut years: BTreeMap<i32, (f64, NaiveDateTime, usize)> = BTreeMap::new();
+        for (timestamp, value) in timestamps.iter().zip(&values) {
+            let (Some(timestamp), Some(value)) = (timestamp, value) else {
+                continue;
+            };
+            let entry = years.entry(timestamp.year()).or_insert((*value, *timestamp, 0));
+            if *value > entry.0 {
+                entry.0 = *value;
+                entry.1 = *timestamp;
+            }
+            entry.2 += 1;
+        }
+
+        let mut annual_maxima = Vec::new();
+        let mut maxima = Vec::new();
+        for (year, (max, timestamp, readings)) in &years {
+            let days = NaiveDate::from_ymd_opt(*year, 12, 31).map_or(365, |d| d.ordinal());
+            let coverage = (interval_seconds > 0).then(|| {
+                let expected = ((days as i64) * 86400 / interval_seconds) as f64;
+                ((*readings as f64 / expected) * 100.0).min(100.0)
+            });
+            // Without an interval the coverage is unknown, so the year is kept
+            let complete = coverage.is_none_or(|c| c >= MIN_EXTREME_COVERAGE_PERCENT);
+            if complete {
+                maxima.push(*max);
+            }
+            annual_maxima.push(
+                json!({
+                "year": year,
+                "maxFlow": max,
+                "timestamp": timestamp.format("%Y-%m-%d %H:%M:%S").to_string(),
+                "readings": readings,
+                "coveragePercent": coverage,
+                "usedForReturnPeriods": complete,
+            })
+            );
+        }
+        let return_periods = if maxima.len() >= MIN_EXTREME_YEARS {
+            let n = maxima.len() as f64;
+            let mean = maxima.iter().sum::<f64>() / n;
+            let variance = maxima
+                .iter()
+                .map(|m| (m - mean).powi(2))
+                .sum::<f64>() / (n - 1.0);
+            let std_dev = variance.sqrt();
+            let scale = (std_dev * 6f64.sqrt()) / std::f64::consts::PI;
+            let location = mean - 0.5772 * scale;
+            RETURN_PERIODS.iter()
+                .map(|&years| {
+                    let flow = location - scale * (-(1.0 - 1.0 / years).ln()).ln();
+                    json!({ "returnPeriodYears": years, "flow": flow })
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let caveat = if maxima.len() < MIN_EXTREME_YEARS {
+            format!(
+                "Only {} years of record with at least {}% coverage; at least {} are needed for \
+                 return periods",
+                maxima.len(),
+                MIN_EXTREME_COVERAGE_PERCENT,
+                MIN_EXTREME_YEARS
+            )
+        } else {
+            "Gumbel estimates from a short annual maximum series are indicative only; years \
+             with low coverage are excluded and return periods beyond the record length are \
+             extrapolated"
+                .to_string()
+        };
+
+        log::info!("Computed annual maximum flow for {} years from '{}'", maxima.len(), flow_col);
+
+        let result =
+            json!({
+            "success": true,
+            "flowColumn": flow_col,
+            "years": maxima.len(),
+            "annualMaxima": annual_maxima,
+            "method": "Gumbel (method of moments)",
+            "returnPeriods": return_periods,
+            "caveat": caveat,
+        });
+        Ok(result.to_string())
+    }
+
+    /// Pearson correlation between depth and velocity over rows where both are present, as
+    /// a quick sensor health check: near-zero or negative correlation in a free-flowing pipe
+    /// often points to a faulty velocity sensor. `correlation` is null when fewer than two
+    /// pairs exist or either series is constant.
+    pub fn depth_velocity_correlation(&self) -> Result<String, String> {
+        let depth_col = self.mapped_column("depth").ok_or("No depth column found")?;
+        let velocity_col = self.mapped_column("velocity").ok_or("No velocity column found")?;
+        let pairs: Vec<(f64, f64)> = self
+            .column_values(&depth_col)?
+            .into_iter()
+            .zip(self.column_values(&velocity_col)?)
+            .filter_map(|(depth, velocity)| depth.zip(velocity))
+            .collect();
+
+        let n = pairs.len() as f64;
+        let correlation = if pairs.len() < 2 {
+            None
+        } else {
+            let mean_depth = pairs.iter().map(|(d, _)| d).sum::<f64>() / n;
+            let mean_velocity = pairs.iter().map(|(_, v)| v).sum::<f64>() / n;
+            let (mut covariance, mut depth_var, mut velocity_var) = (0.0, 0.0, 0.0);
+            for (depth, velocity) in &pairs {
+                let (dd, dv) = (depth - mean_depth, velocity - mean_velocity);
+                covariance += dd * dv;
+                depth_var += dd * dd;
+                velocity_var += dv * dv;
+            }
+            let denominator = (depth_var * velocity_var).sqrt();
+            (denominator > 0.0).then(|| covariance / denominator)
+        };
 
+        let result =
+            json!({
+            "success": true,
+            "depthColumn": depth_col,
+            "velocityColumn": velocity_col,
+            "correlation": correlation,
+            "sampleSize": pairs.len(),
+        });
         Ok(result.to_string())
     }
-    pub fn calculate_r3(&self, width: f64, height: f64, egg_form: &str) -> f64 {
-        let egg_form_value = match egg_form {
-            "Egg Type 1" => 1,
-            "Egg Type 2" => 2,
-            _ => {
-                log::error!("Unknown egg form: {}", egg_form);
-                return -1.0;
+
+    /// Plausible value range for each kind of mapped column. Velocity may be negative
+    /// under surcharged or reverse-flow conditions.
+    fn plausible_range(column_type: &str) -> (f64, f64) {
+        match column_type {
+            "velocity" => (-MAX_PLAUSIBLE_VELOCITY, MAX_PLAUSIBLE_VELOCITY),
+            _ => (0.0, f64::INFINITY),
+        }
+    }
+
+    /// Summarises completeness, missing readings, flatlines and out-of-range values for
+    /// every mapped measurement column. The overall score is the mean proportion of
+    /// usable readings across those columns, as a percentage.
+    pub fn data_quality_summary(&self) -> Result<String, String> {
+        let df = self.data_frame.as_ref().ok_or("No data frame available")?;
+        let row_count = df.height();
+        let completeness = if row_count > 0 {
+            1.0 - (self.gaps as f64) / (row_count as f64)
+        } else {
+            0.0
+        };
+
+        let mut column_reports = Vec::new();
+        let mut usable_fractions = Vec::new();
+        let mut column_types: Vec<&String> = self.column_mapping
+            .keys()
+            .filter(|k| k.as_str() != "timestamp")
+            .collect();
+        column_types.sort();
+
+        for column_type in column_types {
+            let (min, max) = Self::plausible_range(column_type);
+            for (column, _, _, _) in &self.column_mapping[column_type] {
+                let values = self.column_values(column)?;
+                let missing = values
+                    .iter()
+                    .filter(|v| v.is_none())
+                    .count();
+                let out_of_range = values
+                    .iter()
+                    .flatten()
+                    .filter(|&&v| v < min || v > max)
+                    .count();
+                let flatlines = if column_type == "rainfall" {
+                    Vec::new()
+                } else {
+                    Self::find_flatlines(&values, DEFAULT_FLATLINE_RUN)
+                };
+                let flatlined_readings: usize = flatlines
+                    .iter()
+                    .map(|(start, end)| end - start + 1)
+                    .sum();
+
+                let usable = if values.is_empty() {
+                    0.0
+                } else {
+                    let bad = (missing + out_of_range + flatlined_readings).min(values.len());
+                    1.0 - (bad as f64) / (values.len() as f64)
+                };
+                usable_fractions.push(usable);
+
+                column_reports.push(
+                    json!({
+                    "column": column,
+                    "type": column_type,
+                    "missing": missing,
+                    "outOfRange": out_of_range,
+                    "flatlines": flatlines.len(),
+                    "flatlinedReadings": flatlined_readings,
+                    "usable": usable,
+                })
+                );
             }
+        }
+
+        let score = if usable_fractions.is_empty() {
+            0.0
+        } else {
+            (100.0 * usable_fractions.iter().sum::<f64>()) / (usable_fractions.len() as f64)
         };
 
-        match r3_calculator(width, height, egg_form_value) {
-            Ok(r3_value) => {
-                log::info!("R3 value calculated successfully: {}", r3_value);
-                r3_value
+        let result =
+            json!({
+            "success": true,
+            "rowCount": row_count,
+            "gaps": self.gaps,
+            "completeness": completeness,
+            "columns": column_reports,
+            "score": score.round(),
+        });
+
+        log::info!("Data quality score: {:.0}", score);
+        Ok(result.to_string())
+    }
+
+    pub fn depth_velocity_pairs(&self, max_points: Option<usize>) -> Result<String, String> {
+        let depth_col = self.mapped_column("depth").ok_or("No depth column found")?;
+        let velocity_col = self.mapped_column("velocity").ok_or("No velocity column found")?;
+        let max_points = max_points.unwrap_or(DEFAULT_MAX_PLOT_POINTS).max(1);
+
+        let pairs: Vec<(f64, f64)> = self
+            .column_values(&depth_col)?
+            .into_iter()
+            .zip(self.column_values(&velocity_col)?)
+            .filter_map(|(depth, velocity)| Some((depth?, velocity?)))
+            .collect();
+
+        let step = pairs.len().div_ceil(max_points).max(1);
+        let sampled: Vec<[f64; 2]> = pairs
+            .iter()
+            .step_by(step)
+            .map(|&(depth, velocity)| [depth, velocity])
+            .collect();
+
+        let result =
+            json!({
+            "success": true,
+            "depthColumn": depth_col,
+            "velocityColumn": velocity_col,
+            "totalPairs": pairs.len(),
+            "pairs": sampled,
+        });
+
+        log::info!("Returning {} of {} depth/velocity pairs", sampled.len(), pairs.len());
+        Ok(result.to_string())
+    }
+
+    /// Downsamples a column for an overview plot. The record is split into buckets and the
+    /// minimum and maximum of each are kept in time order, so storm peaks and troughs survive
+    /// where plain decimation would skip them.
+    pub fn timeseries_preview(
+        &self,
+        column: &str,
+        max_points: Option<usize>
+    ) -> Result<String, String> {
+        let max_points = max_points.unwrap_or(DEFAULT_MAX_PLOT_POINTS).max(2);
+        let points: Vec<(NaiveDateTime, f64)> = self
+            .timestamps()?
+            .into_iter()
+            .zip(self.column_values(column)?)
+            .filter_map(|(ts, value)| Some((ts?, value?)))
+            .collect();
+
+        let bucket_size = points.len().div_ceil(max_points / 2).max(1);
+        let mut sampled: Vec<(NaiveDateTime, f64)> = Vec::with_capacity(max_points);
+        for bucket in points.chunks(bucket_size) {
+            if bucket.len() <= 2 {
+                sampled.extend_from_slice(bucket);
+                continue;
             }
-            Err(e) => {
-                log::error!("Error calculating R3 value: {:?}", e);
-                -1.0
+            let min = bucket.iter().enumerate().min_by(|a, b| a.1.1.total_cmp(&b.1.1)).unwrap();
+            let max = bucket.iter().enumerate().max_by(|a, b| a.1.1.total_cmp(&b.1.1)).unwrap();
+            let (first, second) = if min.0 <= max.0 { (min.1, max.1) } else { (max.1, min.1) };
+            sampled.push(*first);
+            if min.0 != max.0 {
+                sampled.push(*second);
             }
         }
+
+        let result =
+            json!({
+            "success": true,
+            "column": column,
+            "totalPoints": points.len(),
+            "points": sampled
+                .iter()
+                .map(|(ts, value)| json!([ts.format("%Y-%m-%d %H:%M:%S").to_string(), value]))
+                .collect::<Vec<_>>(),
+        });
+
+        log::info!("Returning {} of {} points for '{}'", sampled.len(), points.len(), column);
+        Ok(result.to_string())
     }
 
     pub fn run_batch_process(
         &self,
         file_infos: Vec<Value>,
-        output_dir: &Path
+        output_dir: &Path,
+        force: bool
     ) -> Result<(), Box<dyn Error>> {
         let mut batch_processor = BatchProcessor::new();
+        batch_processor.set_write_checksums(self.write_checksums);
+        batch_processor.set_force(force);
         let start_time = Instant::now();
 
         log::info!("Starting batch processing {} files...", file_infos.len());
@@ -336,11 +3923,125 @@ impl CommandHandler {
 
         Ok(())
     }
+
+    /// Runs a batch over every CSV/Excel file in `dir`. A `sites.json`/`sites.csv` sidecar in
+    /// `dir` supplies per-site parameters; other flow and depth files use the defaults.
+    /// Files that need pipe parameters when none were given are skipped and listed in the
+    /// result rather than failing the batch. Files whose output is already newer than the
+    /// input are reused unless `force` is set, so a failed batch can be resumed.
+    pub fn run_batch_from_directory(
+        &self,
+        dir: &Path,
+        output_dir: &Path,
+        default_pipe_shape: &Option<&str>,
+        default_pipe_size: &Option<&str>,
+        recursive: bool,
+        force: bool
+    ) -> Result<String, Box<dyn Error>> {
+        let files = BatchProcessor::discover_files(dir, recursive)?;
+        if files.is_empty() {
+            return Err(format!("No CSV or Excel files found in {:?}", dir).into());
+        }
+
+        let file_infos: Vec<Value> = files
+            .iter()
+            .map(|path| {
+                let mut file_info = json!({ "filepath": path.to_string_lossy() });
+                if let Some(shape) = default_pipe_shape.filter(|s| !s.is_empty()) {
+                    file_info["pipeshape"] = json!(shape);
+                }
+                if let Some(size) = default_pipe_size.filter(|s| !s.is_empty()) {
+                    file_info["pipesize"] = json!(size);
+                }
+                file_info
+            })
+            .collect();
+
+        let site_config_path = SiteConfig::find_in(dir);
+        let site_config = match &site_config_path {
+            Some(path) => {
+                let config = SiteConfig::load(path)?;
+                log::info!("Loaded {} site config entries from {:?}", config.len(), path);
+                Some(config)
+            }
+            None => None,
+        };
+
+        let mut batch_processor = BatchProcessor::new();
+        batch_processor.set_skip_missing_params(true);
+        batch_processor.set_site_config(site_config);
+        batch_processor.set_write_checksums(self.write_checksums);
+        batch_processor.set_force(force);
+        let start_time = Instant::now();
+
+        log::info!("Starting batch processing {} files from {:?}...", files.len(), dir);
+
+        let zip_path = batch_processor.process_convert_and_zip(file_infos, output_dir)?;
+        let missing_params: Vec<String> = batch_processor
+            .missing_params()
+            .iter()
+            .map(|path| path.to_string_lossy().to_string())
+            .collect();
+        let checksums: Vec<Value> = batch_processor.processed_files
+            .iter()
+            .filter_map(|file| {
+                Some(
+                    json!({
+                    "outputPath": file.conversion_output_path.as_ref()?.to_string_lossy(),
+                    "sha256": file.sha256.as_ref()?,
+                })
+                )
+            })
+            .collect();
+        let converted = batch_processor.processed_files
+            .iter()
+            .filter(|file| file.conversion_output_path.is_some())
+            .count();
+
+        log::info!(
+            "Directory batch completed in {:?}: {} of {} files converted",
+            start_time.elapsed(),
+            converted,
+            files.len()
+        );
+
+        let result =
+            json!({
+            "success": true,
+            "message": "Batch processing completed successfully",
+            "zipPath": zip_path,
+            "filesFound": files.len(),
+            "filesConverted": converted,
+            "missingParams": missing_params,
+            "siteConfig": site_config_path,
+            "unconfiguredFiles": batch_processor.unconfigured_files(),
+            "skippedFiles": batch_processor.skipped_files(),
+            "checksums": checksums,
+        });
+        Ok(result.to_string())
+    }
+
     pub fn generate_interim_reports(
-        &self
+        &self,
+        options: &InterimReportOptions
     ) -> Result<(DataFrame, DataFrame, DataFrame), Box<dyn Error>> {
         let mut interim_report_generator = InterimReportGenerator::new(self).unwrap();
-        interim_report_generator.generate_report()
+        interim_report_generator.set_daily_completeness(options.daily_completeness);
+        if let (Some(shape), Some(size)) = (&options.pipe_shape, &options.pipe_size) {
+            interim_report_generator.set_hydraulic_check(
+                shape,
+                size,
+                options.manning_n,
+                options.pipe_gradient
+            )?;
+        }
+        interim_report_generator.set_include_empty_periods(options.include_empty_periods);
+        interim_report_generator.set_include_grand_total(options.include_grand_total);
+        if let Some(daily_aggregations) = &options.daily_aggregations {
+            interim_report_generator.set_daily_aggregations(daily_aggregations.clone());
+        }
+        interim_report_generator.set_decimals(options.decimals.unwrap_or(DEFAULT_REPORT_DECIMALS));
+        interim_report_generator.generate_report(options.period)
     }
 
     pub fn generate_rainfall_totals(&self) -> Result<(DataFrame, DataFrame), Box<dyn Error>> {
@@ -348,53 +4049,165 @@ impl CommandHandler {
         interim_report_generator.generate_rainfall_totals()
     }
 
-    fn write_df_to_worksheet(
+    fn to_excel_datetime(dt: NaiveDateTime) -> Result<ExcelDateTime, Box<dyn Error>> {
+        let excel_dt = ExcelDateTime::from_ymd(dt.year() as u16, dt.month() as u8, dt.day() as u8)?
+            .and_hms(dt.hour() as u16, dt.minute() as u8, dt.second())?;
+        Ok(excel_dt)
+    }
+
+    /// Writes a DataFrame with a bold header row. Formatting follows the column dtypes:
+    /// floats to 2 d.p., dates and datetimes as Excel dates, and missing or NaN values
+    /// left blank. Column widths are fitted to the content.
+    pub(crate) fn write_df_to_worksheet(
         df: &DataFrame,
         worksheet: &mut Worksheet
     ) -> Result<(), Box<dyn Error>> {
+        let header_format = Format::new().set_bold();
+        let float_format = Format::new().set_num_format("0.00");
+        let date_format = Format::new().set_num_format("dd/mm/yyyy");
+        let datetime_format = Format::new().set_num_format("dd/mm/yyyy hh:mm");
+
         // Write headers
         for (col, name) in df.get_column_names().iter().enumerate() {
-            worksheet.write_string(0, col as u16, &name.to_string())?;
+            worksheet.write_string_with_format(0, col as u16, &name.to_string(), &header_format)?;
         }
 
         // Write data
-        for (row, series) in df.iter().enumerate() {
-            for (col, value) in series.iter().enumerate() {
+        for (col, series) in df.iter().enumerate() {
+            let col = col as u16;
+            for (row, value) in series.iter().enumerate() {
+                let row = (row as u32) + 1;
                 match value {
-                    AnyValue::Float64(f) => {
-                        worksheet.write_number((row as u32) + 1, col as u16, f)?;
+                    AnyValue::Float64(f) if !f.is_nan() => {
+                        worksheet.write_number_with_format(row, col, f, &float_format)?;
                     }
-                    AnyValue::Float32(f) => {
-                        worksheet.write_number((row as u32) + 1, col as u16, f as f64)?;
+                    AnyValue::Float32(f) if !f.is_nan() => {
+                        worksheet.write_number_with_format(row, col, f as f64, &float_format)?;
                     }
+                    AnyValue::Float64(_) | AnyValue::Float32(_) | AnyValue::Null => {}
                     AnyValue::Int64(i) => {
-                        worksheet.write_number((row as u32) + 1, col as u16, i as i32)?;
+                        worksheet.write_number(row, col, i as f64)?;
                     }
-                    AnyValue::Int32(i) => worksheet.write_number((row as u32) + 1, col as u16, i)?,
+                    AnyValue::Int32(i) => worksheet.write_number(row, col, i)?,
                     AnyValue::UInt64(u) => {
-                        worksheet.write_number((row as u32) + 1, col as u16, u as u32)?;
+                        worksheet.write_number(row, col, u as f64)?;
                     }
-                    AnyValue::UInt32(u) => worksheet.write_number((row as u32) + 1, col as u16, u)?,
-                    AnyValue::Int16(i) => worksheet.write_number((row as u32) + 1, col as u16, i)?,
-                    AnyValue::UInt16(u) => worksheet.write_number((row as u32) + 1, col as u16, u)?,
-                    AnyValue::Int8(i) => worksheet.write_number((row as u32) + 1, col as u16, i)?,
-                    AnyValue::UInt8(u) => worksheet.write_number((row as u32) + 1, col as u16, u)?,
-                    AnyValue::String(s) => worksheet.write_string((row as u32) + 1, col as u16, s)?,
-                    AnyValue::Null => worksheet.write_string((row as u32) + 1, col as u16, "")?,
-                    _ => worksheet.write_string((row as u32) + 1, col as u16, &value.to_string())?,
-                }
+                    AnyValue::UInt32(u) => worksheet.write_number(row, col, u)?,
+                    AnyValue::Int16(i) => worksheet.write_number(row, col, i)?,
+                    AnyValue::UInt16(u) => worksheet.write_number(row, col, u)?,
+                    AnyValue::Int8(i) => worksheet.write_number(row, col, i)?,
+                    AnyValue::UInt8(u) => worksheet.write_number(row, col, u)?,
+                    AnyValue::Boolean(b) => worksheet.write_boolean(row, col, b)?,
+                    AnyValue::Date(days) => {
+                        if let Some(date) = NaiveDate::from_num_days_from_ce_opt(days + 719_163) {
+                            let dt = Self::to_excel_datetime(date.and_hms_opt(0, 0, 0).unwrap())?;
+                            worksheet.write_datetime_with_format(row, col, &dt, &date_format)?;
+                        }
+                    }
+                    AnyValue::Datetime(v, time_unit, _) => {
+                        let dt = match time_unit {
+                            TimeUnit::Nanoseconds => Some(DateTime::from_timestamp_nanos(v)),
+                            TimeUnit::Microseconds => DateTime::from_timestamp_micros(v),
+                            TimeUnit::Milliseconds => DateTime::from_timestamp_millis(v),
+                        };
+                        if let Some(dt) = dt {
+                            let dt = Self::to_excel_datetime(dt.naive_utc())?;
+                            worksheet.write_datetime_with_format(row, col, &dt, &datetime_format)?;
+                        }
+                    }
+                    AnyValue::String(s) => worksheet.write_string(row, col, s)?,
+                    _ => worksheet.write_string(row, col, &value.to_string())?,
+                };
             }
         }
 
+        worksheet.autofit();
+
         Ok(())
     }
 
-    pub fn save_interim_reports_to_excel(&self, file_path: &str) -> Result<(), Box<dyn Error>> {
+    /// Converts a DataFrame to JSON records, one object per row keyed by column name.
+    /// Missing and NaN values become null and dates are formatted as ISO strings.
+    pub(crate) fn df_to_records(df: &DataFrame) -> Vec<Value> {
+        let columns = df.get_columns();
+        (0..df.height())
+            .map(|row| {
+                let record: serde_json::Map<String, Value> = columns
+                    .iter()
+                    .map(|series| {
+                        let value = series.get(row).map_or(Value::Null, Self::any_value_to_json);
+                        (series.name().to_string(), value)
+                    })
+                    .collect();
+                Value::Object(record)
+            })
+            .collect()
+    }
+
+    fn any_value_to_json(value: AnyValue) -> Value {
+        match value {
+            AnyValue::Null => Value::Null,
+            AnyValue::Boolean(b) => json!(b),
+            AnyValue::Float64(f) if f.is_nan() => Value::Null,
+            AnyValue::Float32(f) if f.is_nan() => Value::Null,
+            AnyValue::Float64(f) => json!(f),
+            AnyValue::Float32(f) => json!(f),
+            AnyValue::Int64(i) => json!(i),
+            AnyValue::Int32(i) => json!(i),
+            AnyValue::Int16(i) => json!(i),
+            AnyValue::Int8(i) => json!(i),
+            AnyValue::UInt64(u) => json!(u),
+            AnyValue::UInt32(u) => json!(u),
+            AnyValue::UInt16(u) => json!(u),
+            AnyValue::UInt8(u) => json!(u),
+            AnyValue::Date(days) => {
+                NaiveDate::from_num_days_from_ce_opt(days + 719_163)
+                    .map_or(Value::Null, |date| json!(date.format("%Y-%m-%d").to_string()))
+            }
+            AnyValue::Datetime(v, time_unit, _) => {
+                let dt = match time_unit {
+                    TimeUnit::Nanoseconds => Some(DateTime::from_timestamp_nanos(v)),
+                    TimeUnit::Microseconds => DateTime::from_timestamp_micros(v),
+                    TimeUnit::Milliseconds => DateTime::from_timestamp_millis(v),
+                };
+                dt.map_or(Value::Null, |dt| {
+                    json!(dt.naive_utc().format("%Y-%m-%d %H:%M:%S").to_string())
+                })
+            }
+            AnyValue::String(s) => json!(s),
+            other => json!(other.to_string()),
+        }
+    }
+
+    /// Generates the interim reports and returns the three tables as JSON records for an
+    /// in-app preview, without writing a workbook.
+    pub fn interim_reports_json(&self, options: &InterimReportOptions) -> Result<String, String> {
+        let (summaries, complete_data, daily_summary) = self
+            .generate_interim_reports(options)
+            .map_err(|e| format!("Error generating interim reports: {}", e))?;
+
+        let result =
+            json!({
+            "success": true,
+            "summaries": Self::df_to_records(&summaries),
+            "completeData": Self::df_to_records(&complete_data),
+            "dailySummary": Self::df_to_records(&daily_summary),
+        });
+        Ok(result.to_string())
+    }
+
+    /// Writes the interim reports workbook to `file_path`, returning its SHA-256 when
+    /// checksums are enabled.
+    pub fn save_interim_reports_to_excel(
+        &self,
+        file_path: &str,
+        options: &InterimReportOptions
+    ) -> Result<Option<String>, Box<dyn Error>> {
         // Create a new workbook
         let mut workbook = Workbook::new();
 
         // Generate interim reports
-        let (summaries, complete_data, daily_summary) = self.generate_interim_reports()?;
+        let (summaries, complete_data, daily_summary) = self.generate_interim_reports(options)?;
 
         // Write each DataFrame to a separate worksheet
         let mut worksheet = workbook.add_worksheet();
@@ -409,11 +4222,60 @@ impl CommandHandler {
         worksheet.set_name("Daily Summary")?;
         Self::write_df_to_worksheet(&daily_summary, &mut worksheet)?;
 
+        let raw_vs_filled = self.raw_vs_filled()?;
+        let mut worksheet = workbook.add_worksheet();
+        worksheet.set_name("Raw vs Filled")?;
+        Self::write_df_to_worksheet(&raw_vs_filled, &mut worksheet)?;
+
         // Save the workbook
         workbook.save(file_path)?;
+        let sha256 = self.checksum_output(file_path)?;
 
         log::info!("Interim reports Excel file saved successfully: {}", file_path);
-        Ok(())
+        Ok(sha256)
+    }
+
+    /// The loaded monitor as a combined report entry, or `None` if no file is loaded.
+    pub fn site_data(&self) -> Option<SiteData> {
+        self.data_frame.as_ref().map(|df| SiteData {
+            site_id: self.site_id.clone(),
+            site_name: self.site_name.clone(),
+            monitor_type: self.monitor_type.clone(),
+            start_timestamp: self.start_timestamp.clone(),
+            end_timestamp: self.end_timestamp.clone(),
+            interval_seconds: self.interval.num_seconds(),
+            df: df.clone(),
+        })
+    }
+
+    /// Writes the loaded monitor and each of `file_paths` into one workbook with a sheet
+    /// per site. The extra files are processed in their own handlers, leaving this session
+    /// untouched.
+    pub fn save_combined_report(
+        &self,
+        file_paths: &[String],
+        output_path: &str
+    ) -> Result<String, Box<dyn Error>> {
+        let mut sites: Vec<SiteData> = self.site_data().into_iter().collect();
+
+        for file_path in file_paths {
+            let mut handler = CommandHandler::new();
+            handler
+                .process_file(file_path)
+                .map_err(|e| format!("Failed to process {}: {}", file_path, e))?;
+            sites.extend(handler.site_data());
+        }
+
+        save_combined_report(&sites, output_path)?;
+
+        let result =
+            json!({
+            "success": true,
+            "message": format!("Combined report saved successfully to {}", output_path),
+            "outputPath": output_path,
+            "sites": sites.iter().map(|s| s.site_name.clone()).collect::<Vec<_>>(),
+        });
+        Ok(result.to_string())
     }
 
     pub fn save_rainfall_totals_to_excel(&self, file_path: &str) -> Result<(), Box<dyn Error>> {
@@ -450,3 +4312,362 @@ impl CommandHandler {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> String {
+        format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name)
+    }
+
+    #[test]
+    fn failed_file_leaves_the_previous_file_loaded() {
+        let good = fixture("SITE1.csv");
+        let mut handler = CommandHandler::new();
+        handler.process_file(&good).unwrap();
+        let start = handler.start_timestamp.clone();
+        let end = handler.end_timestamp.clone();
+        let rows = handler.data_frame.as_ref().map(DataFrame::height);
+
+        assert!(handler.process_file(&fixture("missing.csv")).is_err());
+
+        assert_eq!(handler.filepath, PathBuf::from(&good));
+        assert_eq!(handler.start_timestamp, start);
+        assert_eq!(handler.end_timestamp, end);
+        assert_eq!(handler.data_frame.as_ref().map(DataFrame::height), rows);
+        assert_eq!(handler.site_id, "SITE1");
+    }
+
+    #[test]
+    fn coverage_only_counts_readings_within_the_record() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        let timestamps: Vec<NaiveDateTime> = (0..5).map(|h| start + Duration::hours(h)).collect();
+        let depths = [Some(0.1), Some(0.1), None, Some(0.1), Some(0.1)];
+        let mut handler = CommandHandler::new();
+        handler.data_frame = Some(
+            DataFrame::new(
+                vec![
+                    Series::new("Timestamp".into(), timestamps),
+                    Series::new("Depth_m".into(), &depths)
+                ]
+            ).unwrap()
+        );
+        handler.column_mapping = HashMap::from([
+            ("timestamp".to_string(), vec![("Timestamp".to_string(), 0, None, None)]),
+            ("depth".to_string(), vec![("Depth_m".to_string(), 1, None, None)]),
+        ]);
+        handler.time_col = Some("Timestamp".to_string());
+        handler.interval = Duration::hours(1);
+        handler.start_timestamp = "2024-01-01 01:00:00".to_string();
+        handler.end_timestamp = "2024-01-01 03:00:00".to_string();
+
+        let coverage: Value = serde_json::from_str(&handler.coverage_summary().unwrap()).unwrap();
+        assert_eq!(coverage["expectedReadings"], 3);
+        assert_eq!(coverage["actualReadings"], 2);
+        assert_eq!(coverage["missingReadings"], 1);
+        assert_eq!(coverage["columns"][0]["actualReadings"], 2);
+    }
+
+    #[test]
+    fn tip_resolution_applies_to_tip_count_data() {
+        let mut handler = CommandHandler::new();
+        handler.rainfall_unit = RainfallUnit::Tips(DEFAULT_TIP_RESOLUTION);
+        for resolution in [0.2, 0.5] {
+            handler.set_tip_resolution(resolution).unwrap();
+            assert_eq!(handler.rainfall_unit, RainfallUnit::Tips(resolution));
+            assert_eq!(handler.rainfall_unit.depth_factor(15.0), resolution);
+        }
+        assert!(handler.set_tip_resolution(0.0).is_err());
+        assert!(handler.set_tip_resolution(f64::NAN).is_err());
+        assert!(handler.set_tip_resolution(f64::INFINITY).is_err());
+
+        // Depth units aren't tips, so only the stored setting changes
+        handler.rainfall_unit = RainfallUnit::MmPerHour;
+        handler.set_tip_resolution(0.2).unwrap();
+        assert_eq!(handler.rainfall_unit, RainfallUnit::MmPerHour);
+        assert_eq!(handler.tip_resolution_mm, 0.2);
+    }
+
+    #[test]
+    fn relabelled_depth_unit_wins_over_the_column_name() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        let mut handler = CommandHandler::new();
+        handler.data_frame = Some(
+            DataFrame::new(
+                vec![
+                    Series::new("Timestamp".into(), [start, start + Duration::minutes(15)]),
+                    Series::new("Depth_mm".into(), [0.1, 0.2])
+                ]
+            ).unwrap()
+        );
+        handler.column_mapping = HashMap::from([
+            ("depth".to_string(), vec![("Depth_mm".to_string(), 1, None, None)]),
+        ]);
+
+        handler.set_column_unit("Depth_mm", "m").unwrap();
+        assert_eq!(handler.mapped_column("depth").as_deref(), Some("Depth_mm|m"));
+        assert_eq!(handler.depth_values_mm("Depth_mm|m").unwrap(), [100.0, 200.0]);
+    }
+
+    #[test]
+    fn linear_fit_recovers_a_line() {
+        let points: Vec<(f64, f64)> = (0..5).map(|x| (x as f64, 2.0 * (x as f64) + 1.0)).collect();
+        let (slope, intercept, r_squared) = CommandHandler::linear_fit(&points);
+        assert!((slope - 2.0).abs() < 1e-12);
+        assert!((intercept - 1.0).abs() < 1e-12);
+        assert!((r_squared - 1.0).abs() < 1e-12);
+        assert_eq!(CommandHandler::linear_fit(&[(1.0, 3.0), (1.0, 5.0)]), (0.0, 4.0, 0.0));
+    }
+
+    #[test]
+    fn night_window_past_midnight_is_one_night() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        let timestamps: Vec<NaiveDateTime> = (0..72).map(|h| start + Duration::hours(h)).collect();
+        let flows: Vec<f64> = (0..72)
+            .map(|h| {
+                match h {
+                    23 => 4.0, // 1st 23:00
+                    25 => 3.0, // 2nd 01:00, same night
+                    47 => 6.0, // 2nd 23:00
+                    49 => 7.0, // 3rd 01:00, same night
+                    _ => 10.0,
+                }
+            })
+            .collect();
+        let mut handler = CommandHandler::new();
+        handler.data_frame = Some(
+            DataFrame::new(
+                vec![
+                    Series::new("Timestamp".into(), timestamps),
+                    Series::new("Flow_ls".into(), flows)
+                ]
+            ).unwrap()
+        );
+        handler.column_mapping = HashMap::from([
+            ("flow".to_string(), vec![("Flow_ls".to_string(), 1, None, None)]),
+        ]);
+        handler.time_col = Some("Timestamp".to_string());
+        handler.monitor_type = "Flow".to_string();
+
+        let result = handler.estimate_infiltration(Some("23:00"), Some("05:00")).unwrap();
+        let result: Value = serde_json::from_str(&result).unwrap();
+        let minima: Vec<(String, f64)> = result["nightMinima"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|night| {
+                (night["date"].as_str().unwrap().to_string(), night["flow"].as_f64().unwrap())
+            })
+            .collect();
+        assert_eq!(
+            minima,
+            [
+                ("2024-01-01".to_string(), 3.0),
+                ("2024-01-02".to_string(), 6.0),
+                ("2024-01-03".to_string(), 10.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn raw_vs_filled_shows_gaps_as_parsed() {
+        let mut handler = CommandHandler::new();
+        handler.process_file(&fixture("gap.csv")).unwrap();
+        handler.reprocess_with_options(json!({ "gapFill": "forward_fill" })).unwrap();
+
+        let report = handler.raw_vs_filled().unwrap();
+        let infilled: Vec<Option<bool>> = report
+            .column(INFILLED_COLUMN)
+            .unwrap()
+            .bool()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(infilled[1..4], [Some(false), Some(true), Some(false)]);
+        let raw = report.column("1_1|Depth|m Raw").unwrap().f64().unwrap();
+        let filled = report.column("1_1|Depth|m Filled").unwrap().f64().unwrap();
+        assert_eq!(raw.get(1), Some(0.11));
+        assert_eq!(filled.get(1), Some(0.11));
+        // The forward-filled estimate is only in the final values
+        assert_eq!(raw.get(2), None);
+        assert_eq!(filled.get(2), Some(0.11));
+    }
+
+    #[test]
+    fn flow_extremes_leave_out_partial_years() {
+        // Daily readings for 2019 to 2024, then ten days of 2025 with a larger peak
+        let start = NaiveDate::from_ymd_opt(2019, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        let end = NaiveDate::from_ymd_opt(2025, 1, 10).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        let timestamps: Vec<NaiveDateTime> = std::iter
+            ::successors(Some(start), |ts| Some(*ts + Duration::days(1)))
+            .take_while(|ts| *ts <= end)
+            .collect();
+        let flows: Vec<f64> = timestamps
+            .iter()
+            .map(|ts| ((ts.year() - 2018) as f64) * 10.0 + (ts.ordinal() as f64) / 1000.0)
+            .collect();
+        let mut handler = CommandHandler::new();
+        handler.data_frame = Some(
+            DataFrame::new(
+                vec![
+                    Series::new("Timestamp".into(), timestamps),
+                    Series::new("Flow".into(), flows)
+                ]
+            ).unwrap()
+        );
+        handler.column_mapping = HashMap::from([
+            ("timestamp".to_string(), vec![("Timestamp".to_string(), 0, None, None)]),
+            ("flow".to_string(), vec![("Flow".to_string(), 1, None, None)]),
+        ]);
+        handler.time_col = Some("Timestamp".to_string());
+        handler.interval = Duration::days(1);
+
+        let extremes: Value = serde_json::from_str(&handler.flow_extremes().unwrap()).unwrap();
+        let maxima = extremes["annualMaxima"].as_array().unwrap();
+        assert_eq!(maxima.len(), 7);
+        assert_eq!(maxima[6]["year"], 2025);
+        assert_eq!(maxima[6]["usedForReturnPeriods"], false);
+        assert!(maxima[..6].iter().all(|year| year["usedForReturnPeriods"] == true));
+        assert_eq!(extremes["years"], 6);
+        assert_eq!(extremes["returnPeriods"].as_array().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn interim_report_returns_its_checksum() {
+        let mut handler = CommandHandler::new();
+        handler.process_file(&fixture("SITE1.csv")).unwrap();
+        let output = std::env::temp_dir().join(format!("interim_{}.xlsx", std::process::id()));
+        let output_path = output.to_str().unwrap();
+
+        let options = InterimReportOptions::default();
+        let unchecked = handler.save_interim_reports_to_excel(output_path, &options).unwrap();
+        assert_eq!(unchecked, None);
+
+        handler.set_write_checksums(true).unwrap();
+        let sha256 = handler.save_interim_reports_to_excel(output_path, &options).unwrap();
+        assert_eq!(sha256, Some(CommandHandler::sha256_file(&output).unwrap()));
+
+        let mut checksum_path = output.as_os_str().to_os_string();
+        checksum_path.push(".sha256");
+        fs::remove_file(&output).unwrap();
+        fs::remove_file(PathBuf::from(checksum_path)).unwrap();
+    }
+
+    #[test]
+    fn pipe_size_check_uses_the_shape_height() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        let timestamps: Vec<NaiveDateTime> = (0..5).map(|h| start + Duration::hours(h)).collect();
+        let mut handler = CommandHandler::new();
+        handler.data_frame = Some(
+            DataFrame::new(
+                vec![
+                    Series::new("Timestamp".into(), timestamps),
+                    Series::new("Depth_m".into(), [0.7, 0.8, 0.9, 1.0, 1.25])
+                ]
+            ).unwrap()
+        );
+        handler.column_mapping = HashMap::from([
+            ("timestamp".to_string(), vec![("Timestamp".to_string(), 0, None, None)]),
+            ("depth".to_string(), vec![("Depth_m".to_string(), 1, None, None)]),
+        ]);
+        handler.time_col = Some("Timestamp".to_string());
+        let check = |shape: &str, size: &str| -> Value {
+            serde_json::from_str(&handler.pipe_size_sanity_check(shape, size).unwrap()).unwrap()
+        };
+
+        // A 600 mm wide, 1200 mm high U-shape only surcharges at the last reading
+        let u_shape = check("U-Shape", "600,1200");
+        assert_eq!(u_shape["crownHeightMm"], 1200.0);
+        assert_eq!(u_shape["readingsAtCrown"], 1);
+        assert_eq!(u_shape["status"], "surcharge_prone");
+
+        let circular = check("Circular", "900");
+        assert_eq!(circular["crownHeightMm"], 900.0);
+        assert_eq!(circular["readingsAtCrown"], 3);
+        assert_eq!(circular["status"], "likely_wrong_size");
+
+        assert!(handler.pipe_size_sanity_check("Rectangular", "600").is_err());
+        assert!(handler.pipe_size_sanity_check("U-Shape", "").is_err());
+    }
+
+    #[test]
+    fn integer_tip_counts_make_a_rainfall_file() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        let timestamps: Vec<NaiveDateTime> = (0..4)
+            .map(|i| start + Duration::minutes(15 * i))
+            .collect();
+        let mut handler = CommandHandler::new();
+        handler.data_frame = Some(
+            DataFrame::new(
+                vec![
+                    Series::new("Timestamp".into(), timestamps),
+                    Series::new("1_2|Rainfall|mm".into(), [0i64, 1, 3, 0])
+                ]
+            ).unwrap()
+        );
+        handler.monitor_type = "Rainfall".to_string();
+        handler.time_col = Some("Timestamp".to_string());
+        handler.interval = Duration::minutes(15);
+        handler.start_timestamp = "2024-01-01 00:00:00".to_string();
+        handler.end_timestamp = "2024-01-01 00:45:00".to_string();
+        handler.rainfall_unit = RainfallUnit::Tips(DEFAULT_TIP_RESOLUTION);
+        let output = std::env::temp_dir().join(format!("int_rain_{}.r", std::process::id()));
+
+        let result = handler
+            .create_rainfall(output.to_str().unwrap(), "1_2|Rainfall|mm", &None, None)
+            .unwrap();
+        let result: Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(result["success"], true);
+        assert_eq!(result["nullReadings"], 0);
+        fs::remove_file(&output).unwrap();
+    }
+
+    #[test]
+    fn create_rainfall_rejects_a_non_finite_tip_resolution() {
+        let mut handler = CommandHandler::new();
+        handler.data_frame = Some(DataFrame::default());
+        for resolution in [0.0, f64::NAN, f64::INFINITY] {
+            let error = handler
+                .create_rainfall("", "Rainfall", &Some("incremental"), Some(resolution))
+                .unwrap_err();
+            assert!(error.contains("Tip resolution"), "{}", error);
+        }
+    }
+
+    #[test]
+    fn output_stem_stays_inside_the_output_directory() {
+        let mut handler = CommandHandler::new();
+        assert_eq!(handler.output_stem(), "output");
+        handler.site_name = "../../etc/Site 1".to_string();
+        assert_eq!(handler.output_stem(), "_.._etc_Site 1");
+        handler.site_name = "..".to_string();
+        assert_eq!(handler.output_stem(), "output");
+        handler.site_name = r"C:\Site?".to_string();
+        assert_eq!(handler.output_stem(), "C__Site_");
+    }
+
+    #[test]
+    fn failed_depth_output_removes_the_flow_output() {
+        let mut handler = CommandHandler::new();
+        handler.process_file(&fixture("SITE1.csv")).unwrap();
+        let flow = std::env::temp_dir().join(format!("both_flow_{}.fdv", std::process::id()));
+        let depth = std::env::temp_dir()
+            .join(format!("missing_{}", std::process::id()))
+            .join("both_depth.fdv");
+
+        let error = handler
+            .create_both_outputs(
+                flow.to_str().unwrap(),
+                depth.to_str().unwrap(),
+                "1_1|Depth|m",
+                &Some("1_2|Velocity|m/s"),
+                "Circular",
+                "300"
+            )
+            .unwrap_err();
+        assert!(error.contains("flow output was removed"), "{}", error);
+        assert!(!flow.exists());
+        assert!(handler.conversions.is_empty());
+    }
+}
@@ -1,18 +1,31 @@
 use crate::backend::backend::CommandHandler;
+use crate::backend::file_processor::FileProcessor;
+use crate::backend::site_config::{ SiteConfig, SITE_CONFIG_FILES };
 use rayon::prelude::*;
 use serde_json::Value;
+use std::collections::HashSet;
 use std::fs::{ self, File };
-use std::io::{ Read, Write };
+use std::io;
 use std::path::{ Path, PathBuf };
 use std::sync::{ Arc, Mutex };
 use zip::write::{ FileOptions, ZipWriter };
 use zip::CompressionMethod;
 
+/// File extensions picked up when discovering batch input files in a directory.
+const BATCH_EXTENSIONS: [&str; 5] = ["csv", "txt", "tsv", "xlsx", "xls"];
+
 #[derive(Debug, Clone)]
 pub struct ProcessedFileInfo {
     pub conversion_output_path: Option<PathBuf>,
+    pub provenance_path: Option<PathBuf>,
+    /// `<output>.sha256` written when checksums are enabled.
+    pub checksum_path: Option<PathBuf>,
+    pub sha256: Option<String>,
 }
 
+/// Batch manifest listing the SHA-256 of every converted file, in `sha256sum` format.
+const CHECKSUM_MANIFEST: &str = "checksums.sha256";
+
 #[derive(Debug, thiserror::Error)]
 pub enum BatchProcessingError {
     #[error("File processing error: {0}")] FileProcessingError(String),
@@ -24,6 +37,14 @@ pub enum BatchProcessingError {
 pub struct BatchProcessor {
     command_handler: Arc<Mutex<CommandHandler>>,
     pub processed_files: Vec<ProcessedFileInfo>,
+    skip_missing_params: bool,
+    missing_params: Mutex<Vec<PathBuf>>,
+    site_config: Option<SiteConfig>,
+    unconfigured_files: Mutex<Vec<PathBuf>>,
+    write_checksums: bool,
+    manifest_path: Option<PathBuf>,
+    force: bool,
+    skipped_files: Mutex<Vec<PathBuf>>,
 }
 
 impl BatchProcessor {
@@ -31,7 +52,148 @@ impl BatchProcessor {
         BatchProcessor {
             command_handler: Arc::new(Mutex::new(CommandHandler::new())),
             processed_files: Vec::new(),
+            skip_missing_params: false,
+            missing_params: Mutex::new(Vec::new()),
+            site_config: None,
+            unconfigured_files: Mutex::new(Vec::new()),
+            write_checksums: false,
+            manifest_path: None,
+            force: false,
+            skipped_files: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns `stem.extension`, or `stem_2.extension`, `stem_3.extension`, ... if that name
+    /// was already used, so zip entries sharing a name don't overwrite each other.
+    fn unique_name(stem: &str, extension: &str, used: &mut HashSet<String>) -> String {
+        let mut name = format!("{}.{}", stem, extension);
+        let mut suffix = 2;
+        while !used.insert(name.to_lowercase()) {
+            name = format!("{}_{}.{}", stem, suffix, extension);
+            suffix += 1;
+        }
+        name
+    }
+
+    /// Output file stem for each input: its file name without the extension(s), numbered
+    /// `_2`, `_3`, ... in input order when inputs share a name. Fixed before any file is
+    /// converted, so a rerun maps every input to the same output whatever order the
+    /// conversions finish in.
+    fn output_stems(file_infos: &[Value]) -> Vec<String> {
+        let mut used = HashSet::new();
+        file_infos
+            .iter()
+            .map(|file_info| {
+                let mut path = PathBuf::from(file_info["filepath"].as_str().unwrap_or_default());
+                if FileProcessor::is_gzipped_csv(&path.to_string_lossy()) {
+                    path.set_extension("");
+                }
+                let stem = path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                let mut name = stem.clone();
+                let mut suffix = 2;
+                while !used.insert(name.to_lowercase()) {
+                    name = format!("{}_{}", stem, suffix);
+                    suffix += 1;
+                }
+                name
+            })
+            .collect()
+    }
+
+    /// Per-site parameters matched against each file's detected site id. Entries take
+    /// precedence over the parameters passed in the file info.
+    pub fn set_site_config(&mut self, site_config: Option<SiteConfig>) {
+        self.site_config = site_config;
+    }
+
+    /// Files whose site id had no entry in the site config.
+    pub fn unconfigured_files(&self) -> Vec<PathBuf> {
+        self.unconfigured_files
+            .lock()
+            .map(|files| files.clone())
+            .unwrap_or_default()
+    }
+
+    /// Writes a `.sha256` file beside each output and a `checksums.sha256` manifest, all
+    /// added to the zip.
+    pub fn set_write_checksums(&mut self, write_checksums: bool) {
+        self.write_checksums = write_checksums;
+    }
+
+    /// Reconverts every file even when an up-to-date output already exists in the output
+    /// directory. Off by default, so a batch that failed partway resumes where it stopped.
+    pub fn set_force(&mut self, force: bool) {
+        self.force = force;
+    }
+
+    /// Input files whose existing output was reused instead of being converted again.
+    pub fn skipped_files(&self) -> Vec<PathBuf> {
+        self.skipped_files
+            .lock()
+            .map(|skipped| skipped.clone())
+            .unwrap_or_default()
+    }
+
+    /// Whether `output_path` exists and was written after `input_path` last changed.
+    fn is_up_to_date(output_path: &Path, input_path: &Path) -> bool {
+        let modified = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+        match (modified(output_path), modified(input_path)) {
+            (Some(output), Some(input)) => output > input,
+            _ => false,
+        }
+    }
+
+    /// When set, flow/depth files without pipe parameters are skipped and reported through
+    /// `missing_params` instead of failing the whole batch.
+    pub fn set_skip_missing_params(&mut self, skip_missing_params: bool) {
+        self.skip_missing_params = skip_missing_params;
+    }
+
+    pub fn missing_params(&self) -> Vec<PathBuf> {
+        self.missing_params
+            .lock()
+            .map(|files| files.clone())
+            .unwrap_or_default()
+    }
+
+    /// Lists the CSV, text and Excel files in `dir`, descending into subdirectories when
+    /// `recursive` is set. The result is sorted so batches run in a stable order.
+    pub fn discover_files(
+        dir: &Path,
+        recursive: bool
+    ) -> Result<Vec<PathBuf>, BatchProcessingError> {
+        let mut files = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                if recursive {
+                    files.extend(Self::discover_files(&path, recursive)?);
+                }
+            } else if Self::is_batch_input(&path) {
+                files.push(path);
+            }
         }
+        files.sort();
+        Ok(files)
+    }
+
+    /// True for files a batch should convert. Site config files kept next to the inputs
+    /// share the CSV extension but are not logger data.
+    fn is_batch_input(path: &Path) -> bool {
+        let is_site_config = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| SITE_CONFIG_FILES.iter().any(|c| c.eq_ignore_ascii_case(name)));
+        let is_data_file =
+            path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| BATCH_EXTENSIONS.contains(&ext.to_lowercase().as_str())) ||
+            FileProcessor::is_gzipped_csv(&path.to_string_lossy());
+        is_data_file && !is_site_config
     }
 
     pub fn process_convert_and_zip(
@@ -43,9 +205,11 @@ impl BatchProcessor {
 
         fs::create_dir_all(output_dir)?;
 
+        let output_stems = Self::output_stems(&file_infos);
         let results: Result<Vec<_>, _> = file_infos
             .into_par_iter()
-            .map(|file_info| {
+            .zip(output_stems)
+            .map(|(file_info, output_stem)| {
                 let input_path = PathBuf::from(
                     file_info["filepath"]
                         .as_str()
@@ -66,21 +230,38 @@ impl BatchProcessor {
                     );
                 }
 
-                let output_path = self.process_and_convert_file(
-                    &file_info,
-                    &input_path,
-                    output_dir
-                )?;
-
-                let processed_file_info = ProcessedFileInfo {
-                    conversion_output_path: Some(output_path),
-                };
-                Ok(processed_file_info)
+                let existing = ["fdv", "r"]
+                    .iter()
+                    .map(|extension| output_dir.join(format!("{}.{}", output_stem, extension)))
+                    .find(|output_path| Self::is_up_to_date(output_path, &input_path));
+                match existing {
+                    Some(output_path) if !self.force => self.reuse_output(&input_path, output_path),
+                    _ =>
+                        self.process_and_convert_file(
+                            &file_info,
+                            &input_path,
+                            output_dir,
+                            &output_stem
+                        ),
+                }
             })
             .collect();
 
         self.processed_files = results?;
 
+        if self.write_checksums {
+            let manifest: String = self.processed_files
+                .iter()
+                .filter_map(|file| {
+                    let name = file.conversion_output_path.as_ref()?.file_name()?;
+                    Some(format!("{}  {}\n", file.sha256.as_ref()?, name.to_string_lossy()))
+                })
+                .collect();
+            let manifest_path = output_dir.join(CHECKSUM_MANIFEST);
+            fs::write(&manifest_path, manifest)?;
+            self.manifest_path = Some(manifest_path);
+        }
+
         log::info!("File processing and conversion completed. Starting zip creation...");
 
         let zip_path = output_dir.join("processed_files.zip");
@@ -91,12 +272,37 @@ impl BatchProcessor {
         Ok(zip_path)
     }
 
+    /// Reports the up-to-date `output_path` of `input_path` in place of converting it again.
+    fn reuse_output(
+        &self,
+        input_path: &Path,
+        output_path: PathBuf
+    ) -> Result<ProcessedFileInfo, BatchProcessingError> {
+        log::info!("Skipping {:?}: output {:?} is already up to date", input_path, output_path);
+        if let Ok(mut skipped) = self.skipped_files.lock() {
+            skipped.push(input_path.to_path_buf());
+        }
+        let mut provenance_name = output_path.as_os_str().to_os_string();
+        provenance_name.push(".provenance.json");
+        let provenance_path = Some(PathBuf::from(provenance_name)).filter(|p| p.exists());
+        let (checksum_path, sha256) = self.write_checksum(&output_path)?;
+        Ok(ProcessedFileInfo {
+            conversion_output_path: Some(output_path),
+            provenance_path,
+            checksum_path,
+            sha256,
+        })
+    }
+
+    /// Converts `input_path` to `<output_stem>.fdv` or `<output_stem>.r` in `output_dir`,
+    /// depending on its monitor type.
     fn process_and_convert_file(
         &self,
         file_info: &Value,
         input_path: &Path,
-        output_dir: &Path
-    ) -> Result<PathBuf, BatchProcessingError> {
+        output_dir: &Path,
+        output_stem: &str
+    ) -> Result<ProcessedFileInfo, BatchProcessingError> {
         let mut ch = self.command_handler
             .lock()
             .map_err(|e| BatchProcessingError::LockError(e.to_string()))?;
@@ -116,38 +322,76 @@ impl BatchProcessor {
             );
         }
 
-        let monitor_type = process_result["monitorType"]
+        let site_params = self.site_config.as_ref().and_then(|config| {
+            let site_id = process_result["siteId"].as_str().unwrap_or_default();
+            let params = config.get(site_id).cloned();
+            if params.is_none() {
+                log::warn!("No site config entry for site '{}' ({:?})", site_id, input_path);
+                if let Ok(mut unconfigured) = self.unconfigured_files.lock() {
+                    unconfigured.push(input_path.to_path_buf());
+                }
+            }
+            params
+        });
+
+        let mut monitor_type = process_result["monitorType"]
             .as_str()
             .ok_or_else(|| {
                 BatchProcessingError::FileProcessingError("Invalid monitor type".to_string())
-            })?;
+            })?
+            .to_string();
+        if let Some(override_type) = site_params.as_ref().and_then(|p| p.monitor_type.as_deref()) {
+            ch
+                .set_monitor_type(override_type)
+                .map_err(BatchProcessingError::FileProcessingError)?;
+            monitor_type = ch.monitor_type.clone();
+        }
+        let monitor_type = monitor_type.as_str();
+
+        let pipe_shape = site_params
+            .as_ref()
+            .and_then(|p| p.pipe_shape.clone())
+            .or_else(|| file_info["pipeshape"].as_str().map(String::from));
+        let pipe_size = site_params
+            .as_ref()
+            .and_then(|p| p.pipe_size.clone())
+            .or_else(|| file_info["pipesize"].as_str().map(String::from));
         let column_mapping = process_result["columnMapping"]
             .as_object()
             .ok_or_else(|| {
                 BatchProcessingError::FileProcessingError("Invalid column mapping".to_string())
             })?;
-        let site_name = process_result["siteName"]
-            .as_str()
-            .ok_or_else(|| {
-                BatchProcessingError::FileProcessingError("Site name not found".to_string())
-            })?;
 
         let file_extension = if monitor_type == "Rainfall" { "r" } else { "fdv" };
-        let output_filename = format!("{}.{}", site_name, file_extension);
-        let output_path = output_dir.join(output_filename);
+        let output_path = output_dir.join(format!("{}.{}", output_stem, file_extension));
+
+        let has_pipe_params = pipe_shape.is_some() && pipe_size.is_some();
+        let needs_pipe_params = matches!(monitor_type, "Flow" | "Depth");
+        if needs_pipe_params && !has_pipe_params && self.skip_missing_params {
+            log::warn!("Skipping {:?}: no pipe parameters for {} file", input_path, monitor_type);
+            if let Ok(mut missing) = self.missing_params.lock() {
+                missing.push(input_path.to_path_buf());
+            }
+            return Ok(ProcessedFileInfo {
+                conversion_output_path: None,
+                provenance_path: None,
+                checksum_path: None,
+                sha256: None,
+            });
+        }
 
         (
             match monitor_type {
                 "Flow" | "Depth" => {
-                    let pipe_shape = file_info["pipeshape"]
-                        .as_str()
+                    let pipe_shape = pipe_shape
+                        .as_deref()
                         .ok_or_else(|| {
                             BatchProcessingError::FileProcessingError(
                                 "Pipe shape is required for flow/depth conversion".to_string()
                             )
                         })?;
-                    let pipe_size = file_info["pipesize"]
-                        .as_str()
+                    let pipe_size = pipe_size
+                        .as_deref()
                         .ok_or_else(|| {
                             BatchProcessingError::FileProcessingError(
                                 "Pipe size is required for flow/depth conversion".to_string()
@@ -168,7 +412,9 @@ impl BatchProcessor {
                 "Rainfall" =>
                     ch.create_rainfall(
                         output_path.to_str().unwrap(),
-                        &Self::extract_column_name(column_mapping, "rainfall")?
+                        &Self::extract_column_name(column_mapping, "rainfall")?,
+                        &None,
+                        None
                     ),
                 _ => Err(format!("Unsupported monitor type: {}", monitor_type)),
             }
@@ -178,7 +424,41 @@ impl BatchProcessor {
             )
         })?;
 
-        Ok(output_path)
+        // Audit record written alongside each output, named after it
+        let mut provenance_name = output_path.as_os_str().to_os_string();
+        provenance_name.push(".provenance.json");
+        let provenance_path = PathBuf::from(provenance_name);
+        let provenance_str = provenance_path.to_str().ok_or_else(|| {
+            BatchProcessingError::FileProcessingError(
+                format!("Provenance path is not valid UTF-8: {}", provenance_path.display())
+            )
+        })?;
+        ch
+            .write_provenance(provenance_str, true)
+            .map_err(BatchProcessingError::FileProcessingError)?;
+
+        let (checksum_path, sha256) = self.write_checksum(&output_path)?;
+
+        Ok(ProcessedFileInfo {
+            conversion_output_path: Some(output_path),
+            provenance_path: Some(provenance_path),
+            checksum_path,
+            sha256,
+        })
+    }
+
+    /// Writes `<output>.sha256` when checksums are enabled, returning its path and the hash.
+    fn write_checksum(
+        &self,
+        output_path: &Path
+    ) -> Result<(Option<PathBuf>, Option<String>), BatchProcessingError> {
+        if !self.write_checksums {
+            return Ok((None, None));
+        }
+        let sha256 = CommandHandler::write_checksum_file(output_path)?;
+        let mut checksum_name = output_path.as_os_str().to_os_string();
+        checksum_name.push(".sha256");
+        Ok((Some(PathBuf::from(checksum_name)), Some(sha256)))
     }
 
     fn create_zip_file(&self, zip_path: &Path) -> Result<(), BatchProcessingError> {
@@ -186,55 +466,54 @@ impl BatchProcessor {
             BatchProcessingError::FileProcessingError(format!("Failed to create zip file: {}", e))
         })?;
         let mut zip = ZipWriter::new(file);
-        for processed_file in &self.processed_files {
-            if let Some(output_path) = &processed_file.conversion_output_path {
-                log::info!("Adding file to zip: {:?}", output_path);
-                if !output_path.exists() {
-                    return Err(
-                        BatchProcessingError::FileProcessingError(
-                            format!("Processed file does not exist: {:?}", output_path)
-                        )
-                    );
-                }
-                let options: FileOptions<'static, ()> = FileOptions::default()
-                    .compression_method(CompressionMethod::Deflated)
-                    .unix_permissions(0o755);
-                let file_name = output_path
-                    .file_name()
-                    .and_then(|name| name.to_str())
-                    .ok_or_else(|| {
-                        BatchProcessingError::FileProcessingError(
-                            "Invalid or non-UTF8 file name".to_string()
-                        )
-                    })?;
-                zip
-                    .start_file(file_name, options)
-                    .map_err(|e| {
-                        BatchProcessingError::FileProcessingError(
-                            format!("Failed to start file in zip: {}", e)
-                        )
-                    })?;
-                let mut file = File::open(output_path).map_err(|e| {
+        let mut entry_names = HashSet::new();
+        let output_paths = self.processed_files
+            .iter()
+            .flat_map(|file| {
+                [&file.conversion_output_path, &file.provenance_path, &file.checksum_path]
+            })
+            .chain([&self.manifest_path])
+            .flatten();
+        for output_path in output_paths {
+            log::info!("Adding file to zip: {:?}", output_path);
+            if !output_path.exists() {
+                return Err(
                     BatchProcessingError::FileProcessingError(
-                        format!("Failed to open processed file: {}", e)
+                        format!("Processed file does not exist: {:?}", output_path)
                     )
-                })?;
-                let mut buffer = Vec::new();
-                file
-                    .read_to_end(&mut buffer)
-                    .map_err(|e| {
-                        BatchProcessingError::FileProcessingError(
-                            format!("Failed to read processed file: {}", e)
-                        )
-                    })?;
-                zip
-                    .write_all(&buffer)
-                    .map_err(|e| {
-                        BatchProcessingError::FileProcessingError(
-                            format!("Failed to write to zip: {}", e)
-                        )
-                    })?;
+                );
             }
+            let options: FileOptions<'static, ()> = FileOptions::default()
+                .compression_method(CompressionMethod::Deflated)
+                .unix_permissions(0o755);
+            let (stem, extension) = output_path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .zip(output_path.extension().and_then(|ext| ext.to_str()).or(Some("")))
+                .ok_or_else(|| {
+                    BatchProcessingError::FileProcessingError(
+                        "Invalid or non-UTF8 file name".to_string()
+                    )
+                })?;
+            let file_name = Self::unique_name(stem, extension, &mut entry_names);
+            zip
+                .start_file(file_name, options)
+                .map_err(|e| {
+                    BatchProcessingError::FileProcessingError(
+                        format!("Failed to start file in zip: {}", e)
+                    )
+                })?;
+            let mut file = File::open(output_path).map_err(|e| {
+                BatchProcessingError::FileProcessingError(
+                    format!("Failed to open processed file: {}", e)
+                )
+            })?;
+            // Stream rather than buffer so large FDVs aren't held in memory
+            io::copy(&mut file, &mut zip).map_err(|e| {
+                BatchProcessingError::FileProcessingError(
+                    format!("Failed to write to zip: {}", e)
+                )
+            })?;
         }
         zip
             .finish()
@@ -265,3 +544,63 @@ impl BatchProcessor {
             })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh directory under the system temp dir, unique to the calling test.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(
+            format!("fdv_batch_{}_{}", name, std::process::id())
+        );
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn discover_files_skips_site_config() {
+        let dir = temp_dir("discover");
+        let inputs = ["site_a.csv", "site_b.txt", "site_c.tsv"];
+        for name in inputs.iter().chain(&["sites.csv", "sites.json", "notes.md"]) {
+            fs::write(dir.join(name), "Timestamp,Depth\n").unwrap();
+        }
+        let files = BatchProcessor::discover_files(&dir, false).unwrap();
+        assert_eq!(files, inputs.map(|name| dir.join(name)));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn output_stems_follow_the_input_order() {
+        let file_infos: Vec<Value> = ["a/SITE1.csv", "b/site1.xlsx", "c/SITE2.csv.gz"]
+            .iter()
+            .map(|path| serde_json::json!({ "filepath": path }))
+            .collect();
+        assert_eq!(BatchProcessor::output_stems(&file_infos), ["SITE1", "site1_2", "SITE2"]);
+    }
+
+    #[test]
+    fn up_to_date_outputs_are_skipped_unless_forced() {
+        let dir = temp_dir("skip");
+        let input = format!("{}/tests/fixtures/SITE1.csv", env!("CARGO_MANIFEST_DIR"));
+        let file_infos = vec![
+            serde_json::json!({ "filepath": input, "pipeshape": "Circular", "pipesize": "300" })
+        ];
+
+        let mut batch = BatchProcessor::new();
+        batch.process_convert_and_zip(file_infos.clone(), &dir).unwrap();
+        assert!(dir.join("SITE1.fdv").exists());
+        assert!(batch.skipped_files().is_empty());
+
+        let mut batch = BatchProcessor::new();
+        batch.process_convert_and_zip(file_infos.clone(), &dir).unwrap();
+        assert_eq!(batch.skipped_files(), [PathBuf::from(&input)]);
+
+        let mut batch = BatchProcessor::new();
+        batch.set_force(true);
+        batch.process_convert_and_zip(file_infos, &dir).unwrap();
+        assert!(batch.skipped_files().is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,132 @@
+use crate::backend::backend::CommandHandler;
+use polars::prelude::*;
+use rust_xlsxwriter::Workbook;
+use std::collections::HashSet;
+use std::error::Error;
+
+/// Excel limits worksheet names to 31 characters.
+const MAX_SHEET_NAME_LEN: usize = 31;
+
+/// A processed monitor ready to be written into a combined workbook.
+pub struct SiteData {
+    pub site_id: String,
+    pub site_name: String,
+    pub monitor_type: String,
+    pub start_timestamp: String,
+    pub end_timestamp: String,
+    pub interval_seconds: i64,
+    pub df: DataFrame,
+}
+
+/// Builds a sheet name from the site, dropping characters Excel rejects and adding a
+/// numeric suffix when two sites would share a name.
+fn sheet_name(site: &SiteData, used: &mut HashSet<String>) -> String {
+    let label = if site.site_name.is_empty() { &site.site_id } else { &site.site_name };
+    let base: String = label
+        .chars()
+        .filter(|c| !matches!(c, '[' | ']' | ':' | '*' | '?' | '/' | '\\'))
+        .take(MAX_SHEET_NAME_LEN)
+        .collect();
+    let base = if base.trim().is_empty() { "Site".to_string() } else { base };
+
+    let mut name = base.clone();
+    let mut suffix = 2;
+    while !used.insert(name.to_lowercase()) {
+        let suffix_str = format!(" ({})", suffix);
+        let stem: String = base
+            .chars()
+            .take(MAX_SHEET_NAME_LEN - suffix_str.len())
+            .collect();
+        name = format!("{}{}", stem, suffix_str);
+        suffix += 1;
+    }
+    name
+}
+
+fn summary_index(sites: &[SiteData], sheet_names: &[String]) -> PolarsResult<DataFrame> {
+    DataFrame::new(
+        vec![
+            Series::new("Sheet".into(), sheet_names.to_vec()),
+            Series::new(
+                "Site ID".into(),
+                sites
+                    .iter()
+                    .map(|s| s.site_id.clone())
+                    .collect::<Vec<_>>()
+            ),
+            Series::new(
+                "Site Name".into(),
+                sites
+                    .iter()
+                    .map(|s| s.site_name.clone())
+                    .collect::<Vec<_>>()
+            ),
+            Series::new(
+                "Monitor Type".into(),
+                sites
+                    .iter()
+                    .map(|s| s.monitor_type.clone())
+                    .collect::<Vec<_>>()
+            ),
+            Series::new(
+                "Start".into(),
+                sites
+                    .iter()
+                    .map(|s| s.start_timestamp.clone())
+                    .collect::<Vec<_>>()
+            ),
+            Series::new(
+                "End".into(),
+                sites
+                    .iter()
+                    .map(|s| s.end_timestamp.clone())
+                    .collect::<Vec<_>>()
+            ),
+            Series::new(
+                "Interval (s)".into(),
+                sites
+                    .iter()
+                    .map(|s| s.interval_seconds)
+                    .collect::<Vec<_>>()
+            ),
+            Series::new(
+                "Rows".into(),
+                sites
+                    .iter()
+                    .map(|s| s.df.height() as u64)
+                    .collect::<Vec<_>>()
+            )
+        ]
+    )
+}
+
+/// Writes several monitors into one workbook: an "Index" sheet listing every site,
+/// followed by one sheet per site holding its processed data.
+pub fn save_combined_report(sites: &[SiteData], file_path: &str) -> Result<(), Box<dyn Error>> {
+    if sites.is_empty() {
+        return Err("No sites to include in the combined report".into());
+    }
+
+    let mut used_names = HashSet::from(["index".to_string()]);
+    let sheet_names: Vec<String> = sites
+        .iter()
+        .map(|site| sheet_name(site, &mut used_names))
+        .collect();
+
+    let mut workbook = Workbook::new();
+
+    let mut worksheet = workbook.add_worksheet();
+    worksheet.set_name("Index")?;
+    CommandHandler::write_df_to_worksheet(&summary_index(sites, &sheet_names)?, &mut worksheet)?;
+
+    for (site, name) in sites.iter().zip(&sheet_names) {
+        let mut worksheet = workbook.add_worksheet();
+        worksheet.set_name(name)?;
+        CommandHandler::write_df_to_worksheet(&site.df, &mut worksheet)?;
+    }
+
+    workbook.save(file_path)?;
+
+    log::info!("Combined report with {} sites saved successfully: {}", sites.len(), file_path);
+    Ok(())
+}
@@ -1,18 +1,158 @@
 use crate::backend::site_info::SiteInfo;
+use crate::fdv::quality::QualityFlag;
 use calamine::{ open_workbook, Reader, Xlsx };
-use chrono::{ Duration, NaiveDate, NaiveDateTime, NaiveTime };
+use chrono::format::{ Item, StrftimeItems };
+use chrono::{ Duration, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeZone };
+use chrono_tz::Tz;
 use csv::ReaderBuilder;
+use flate2::read::GzDecoder;
 use log::{ error, info };
 use polars::prelude::*;
 use rayon::prelude::*;
 use regex::Regex;
 use serde::{ Deserialize, Serialize };
-use std::collections::HashMap;
+use std::collections::{ BTreeMap, HashMap, HashSet };
 use std::fs::File;
 use std::io::Read;
 use std::path::Path;
+use std::sync::Arc;
 use thiserror::Error;
 
+/// Timestamps are parsed without an offset and written to FDV headers as GMT.
+pub const NAIVE_TIME_BASIS: &str = "naive-local";
+
+/// Timestamps were read in a configured timezone and converted to UTC.
+pub const UTC_TIME_BASIS: &str = "utc";
+
+/// Receives a phase description and a coarse completion percentage while a file is processed.
+pub type ProgressCallback = Arc<dyn Fn(&str, u8) + Send + Sync>;
+
+/// Name of the optional boolean column marking rows synthesized by gap filling.
+pub const INFILLED_COLUMN: &str = "Infilled";
+
+/// Input file types accepted by `read_file`.
+pub const SUPPORTED_FILE_FORMATS: [&str; 5] = ["xlsx", "csv", "csv.gz", "txt", "tsv"];
+
+/// Delimiters the CSV sniffer chooses between, comma first so it wins ties.
+const SNIFF_DELIMITERS: [u8; 3] = [b',', b';', b'\t'];
+
+/// Lines inspected when sniffing the delimiter.
+const SNIFF_LINES: usize = 5;
+
+/// Leading bytes of a gzip stream.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Column types whose gaps a fill strategy estimates. Rainfall gaps stay empty, as a
+/// repeated or interpolated reading would invent rain, and unmapped columns (codes, counts,
+/// text) have no meaningful in-between value.
+const GAP_FILL_COLUMNS: [&str; 3] = ["depth", "velocity", "flow"];
+
+/// Written by `parse_dates` in place of timestamps that do not match the detected format.
+pub const INVALID_DATE: &str = "Invalid Date";
+
+/// Header pattern identifying a per-reading quality/status column when none is configured.
+const DEFAULT_QUALITY_PATTERN: &str = r"(?i)\b(quality|qc|status|flag)\b";
+
+/// Appended to the quality column's header to name the column of parsed quality codes.
+const QUALITY_FLAG_SUFFIX: &str = "_flag";
+
+/// Timestamp formats tried by `identify_timestamp_format`, in order of preference.
+/// `%.f` accepts an optional fractional part, so these also match whole seconds.
+const DEFAULT_TIMESTAMP_FORMATS: [&str; 8] = [
+    "%d/%m/%Y %H:%M",
+    "%m/%d/%Y %H:%M",
+    "%d-%m-%Y %H:%M:%S",
+    "%d-%m-%Y %H:%M",
+    "%Y%m%d%H%M%S",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y/%m/%d %H:%M:%S",
+];
+
+/// Share of populated cells that must parse as numbers for a column to be read as f64.
+/// Columns below it are kept as text rather than silently becoming all-NaN.
+const MIN_NUMERIC_RATIO: f64 = 0.5;
+
+/// Logger intervals in seconds preferred when two spacings are equally frequent.
+const COMMON_INTERVALS: [i64; 8] = [60, 120, 300, 600, 900, 1800, 3600, 86400];
+
+/// Consecutive non-mode spacings needed before a stretch is reported as an interval change,
+/// so isolated jitter or a single late reading is ignored.
+const MIN_INTERVAL_CHANGE_RUN: usize = 3;
+
+/// A timestamp that appears on more than one source row. Gap filling keeps the last row.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateTimestamp {
+    pub timestamp: String,
+    pub source_rows: Vec<usize>,
+    pub values: Vec<Vec<String>>,
+}
+
+/// A stretch of the raw record sampled at a different interval from the dominant one.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntervalChange {
+    pub start: String,
+    pub end: String,
+    pub interval_seconds: i64,
+    pub readings: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InvalidTimestampPolicy {
+    /// Remove rows whose timestamp could not be parsed and report how many were dropped.
+    #[default]
+    Drop,
+    /// Fail processing if any timestamp could not be parsed.
+    Error,
+}
+
+/// How rows inserted for missing timestamps get their values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GapFillStrategy {
+    /// Leave inserted rows empty, read as missing readings.
+    #[default]
+    Empty,
+    /// Repeat the last real reading before the gap.
+    ForwardFill,
+    /// Interpolate numeric values linearly between the readings either side of the gap.
+    LinearInterpolate,
+}
+
+/// Excel stores dates as serial day numbers counted from one of two epochs. Files
+/// created on older Macs use the 1904 system; everything else uses the 1900 system.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExcelDateSystem {
+    Excel1900,
+    Excel1904,
+}
+
+/// User overrides applied when re-running the processing pipeline.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessingOptions {
+    pub timestamp_format: Option<String>,
+    pub interval: Option<i64>,
+    pub sheet: Option<String>,
+    pub delimiter: Option<String>,
+    pub timezone: Option<String>,
+    pub include_infilled: Option<bool>,
+    pub invalid_timestamps: Option<InvalidTimestampPolicy>,
+    pub min_non_null_ratio: Option<f64>,
+    pub rainfall_unit: Option<String>,
+    pub tip_resolution: Option<f64>,
+    pub quality_pattern: Option<String>,
+    pub trim_null_edges: Option<bool>,
+    /// Whether CSV row 2 holds units rather than data; `None` detects it.
+    pub units_row: Option<bool>,
+    pub gap_fill: Option<GapFillStrategy>,
+    /// Forces the Excel date system; `None` reads it from the workbook.
+    pub excel_date_system: Option<ExcelDateSystem>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileData {
     pub headers: Vec<String>,
@@ -28,6 +168,24 @@ pub struct FileProcessor {
     column_patterns: HashMap<String, Regex>,
     pub(crate) monitor_type: String,
     site_info: SiteInfo,
+    excel_date_system: Option<ExcelDateSystem>,
+    include_infilled_column: bool,
+    timestamp_format: Option<String>,
+    interval_override: Option<Duration>,
+    sheet_name: Option<String>,
+    delimiter: Option<u8>,
+    invalid_timestamp_policy: InvalidTimestampPolicy,
+    progress_callback: Option<ProgressCallback>,
+    min_non_null_ratio: Option<f64>,
+    interval_changes: Vec<IntervalChange>,
+    quality_pattern: Regex,
+    pub(crate) quality_col: Option<String>,
+    trim_null_edges: bool,
+    timestamp_formats: Vec<String>,
+    user_timestamp_formats: usize,
+    units_row: Option<bool>,
+    gap_fill: GapFillStrategy,
+    timezone: Option<Tz>,
 }
 
 pub struct ProcessedFileData {
@@ -35,11 +193,22 @@ pub struct ProcessedFileData {
     pub start_timestamp: String,
     pub end_timestamp: String,
     pub gaps_filled: usize,
+    pub invalid_timestamps: usize,
     pub interval: Duration,
     pub column_mapping: HashMap<String, Vec<(String, usize, Option<String>, Option<String>)>>,
     pub monitor_type: String,
     pub site_id: String,
     pub site_name: String,
+    pub time_basis: String,
+    pub interval_changes: Vec<IntervalChange>,
+    pub quality_column: Option<String>,
+    pub trimmed_rows: usize,
+    pub timestamp_format: String,
+    /// How the `gaps_filled` rows got their values.
+    pub gap_fill: GapFillStrategy,
+    /// The readings as parsed: the same rows as `df` with the inserted gap rows left empty
+    /// whatever the gap-fill strategy, and flagged in an infilled column.
+    pub raw_df: DataFrame,
 }
 
 pub struct UpdatedTimestampData {
@@ -94,9 +263,164 @@ impl FileProcessor {
             column_patterns,
             monitor_type: "Unknown".to_string(),
             site_info: SiteInfo::new(),
+            excel_date_system: None,
+            include_infilled_column: false,
+            timestamp_format: None,
+            interval_override: None,
+            sheet_name: None,
+            delimiter: None,
+            invalid_timestamp_policy: InvalidTimestampPolicy::default(),
+            progress_callback: None,
+            min_non_null_ratio: None,
+            interval_changes: Vec::new(),
+            quality_pattern: Regex::new(DEFAULT_QUALITY_PATTERN).unwrap(),
+            quality_col: None,
+            trim_null_edges: false,
+            timestamp_formats: DEFAULT_TIMESTAMP_FORMATS.iter().map(|f| f.to_string()).collect(),
+            user_timestamp_formats: 0,
+            units_row: None,
+            gap_fill: GapFillStrategy::default(),
+            timezone: None,
         }
     }
 
+    /// Drops columns with a lower proportion of populated cells than `ratio` (0.0 to 1.0)
+    /// before the DataFrame is built. `None` keeps every column.
+    pub fn set_min_non_null_ratio(&mut self, ratio: Option<f64>) {
+        self.min_non_null_ratio = ratio;
+    }
+
+    pub fn set_progress_callback(&mut self, progress_callback: Option<ProgressCallback>) {
+        self.progress_callback = progress_callback;
+    }
+
+    fn report_progress(&self, phase: &str, percent: u8) {
+        if let Some(callback) = &self.progress_callback {
+            callback(phase, percent);
+        }
+    }
+
+    pub fn apply_options(&mut self, options: &ProcessingOptions) -> Result<(), FileProcessorError> {
+        self.timestamp_format = options.timestamp_format.clone().filter(|f| !f.is_empty());
+        self.sheet_name = options.sheet.clone().filter(|s| !s.is_empty());
+
+        if let Some(seconds) = options.interval {
+            if seconds <= 0 {
+                return Err(
+                    FileProcessorError::ParseError("Interval must be greater than zero".to_string())
+                );
+            }
+            self.interval_override = Some(Duration::seconds(seconds));
+        }
+
+        if let Some(delimiter) = options.delimiter.as_deref() {
+            self.delimiter = match delimiter {
+                "\\t" | "\t" | "tab" => Some(b'\t'),
+                d if d.len() == 1 => Some(d.as_bytes()[0]),
+                d => {
+                    return Err(
+                        FileProcessorError::ParseError(format!("Invalid delimiter: {}", d))
+                    );
+                }
+            };
+        }
+
+        if let Some(timezone) = options.timezone.as_deref() {
+            self.set_timezone(timezone)?;
+        }
+
+        if options.excel_date_system.is_some() {
+            self.set_excel_date_system(options.excel_date_system);
+        }
+
+        if let Some(include) = options.include_infilled {
+            self.set_include_infilled_column(include);
+        }
+
+        if let Some(policy) = options.invalid_timestamps {
+            self.invalid_timestamp_policy = policy;
+        }
+
+        if let Some(ratio) = options.min_non_null_ratio {
+            if !(0.0..=1.0).contains(&ratio) {
+                return Err(
+                    FileProcessorError::ParseError(
+                        "Non-null ratio must be between 0 and 1".to_string()
+                    )
+                );
+            }
+            self.set_min_non_null_ratio(Some(ratio));
+        }
+
+        if let Some(trim) = options.trim_null_edges {
+            self.trim_null_edges = trim;
+        }
+
+        self.units_row = options.units_row;
+
+        if let Some(gap_fill) = options.gap_fill {
+            self.set_gap_fill_strategy(gap_fill);
+        }
+
+        if let Some(pattern) = options.quality_pattern.as_deref().filter(|p| !p.is_empty()) {
+            self.quality_pattern = Regex::new(pattern).map_err(|e|
+                FileProcessorError::ParseError(format!("Invalid quality column pattern: {}", e))
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Sets the IANA timezone (e.g. `Europe/London`) the logger clock followed. When set, the
+    /// series is built in UTC so the repeated hour when clocks go back keeps both readings.
+    /// An empty string, `UTC` or `GMT` reads timestamps as GMT unchanged.
+    pub fn set_timezone(&mut self, timezone: &str) -> Result<(), FileProcessorError> {
+        let timezone = timezone.trim();
+        if matches!(timezone.to_uppercase().as_str(), "" | "UTC" | "GMT") {
+            self.timezone = None;
+            return Ok(());
+        }
+        let tz = timezone.parse::<Tz>().map_err(|_| {
+            FileProcessorError::ParseError(format!("Unsupported timezone: {}", timezone))
+        })?;
+        self.timezone = Some(tz);
+        Ok(())
+    }
+
+    /// Converts a local wall-clock time to UTC. In the repeated hour when clocks go back the
+    /// first reading is summer time and any later one winter time; times skipped when clocks
+    /// go forward are moved on by an hour, as loggers that missed the change record them.
+    fn local_to_utc(tz: Tz, local: NaiveDateTime, repeated: bool) -> NaiveDateTime {
+        match tz.from_local_datetime(&local) {
+            LocalResult::Single(dt) => dt.naive_utc(),
+            LocalResult::Ambiguous(earliest, latest) => {
+                if repeated { latest.naive_utc() } else { earliest.naive_utc() }
+            }
+            LocalResult::None =>
+                tz
+                    .from_local_datetime(&(local + Duration::hours(1)))
+                    .earliest()
+                    .map(|dt| dt.naive_utc())
+                    .unwrap_or(local),
+        }
+    }
+
+    /// Chooses how rows inserted for missing timestamps are filled; they are still counted
+    /// in `gaps_filled`.
+    pub fn set_gap_fill_strategy(&mut self, gap_fill: GapFillStrategy) {
+        self.gap_fill = gap_fill;
+    }
+
+    /// Adds a boolean column to the DataFrame flagging rows inserted to fill gaps.
+    pub fn set_include_infilled_column(&mut self, include: bool) {
+        self.include_infilled_column = include;
+    }
+
+    /// Forces the Excel date system instead of detecting it from the workbook.
+    pub fn set_excel_date_system(&mut self, date_system: Option<ExcelDateSystem>) {
+        self.excel_date_system = date_system;
+    }
+
     pub fn read_file(&mut self, file_path: &str) -> Result<FileData, FileProcessorError> {
         let path = Path::new(file_path);
         let extension = path
@@ -106,7 +430,8 @@ impl FileProcessor {
 
         match extension.to_lowercase().as_str() {
             "xlsx" => self.read_excel(file_path),
-            "csv" => self.read_csv(file_path),
+            "csv" | "txt" | "tsv" => self.read_csv(file_path),
+            "gz" if Self::is_gzipped_csv(file_path) => self.read_csv(file_path),
             _ => {
                 error!("Unsupported file format: {}", extension);
                 Err(FileProcessorError::UnsupportedFileFormat(extension.to_string()))
@@ -120,15 +445,21 @@ impl FileProcessor {
         let mut workbook: Xlsx<_> = open_workbook(file_path).map_err(|_|
             FileProcessorError::FileNotFound(file_path.to_string())
         )?;
-        let sheet_name = workbook
-            .sheet_names()
-            .get(0)
-            .ok_or(FileProcessorError::SheetNotFound)?
-            .clone();
-        let range = workbook.worksheet_range(&sheet_name);
+        let sheet_name = match &self.sheet_name {
+            Some(name) => name.clone(),
+            None =>
+                workbook
+                    .sheet_names()
+                    .get(0)
+                    .ok_or(FileProcessorError::SheetNotFound)?
+                    .clone(),
+        };
+        let range = workbook
+            .worksheet_range(&sheet_name)
+            .map_err(|_| FileProcessorError::SheetNotFound)?;
         let mut headers = Vec::new();
         let mut data = Vec::new();
-        for (row_index, row) in range.unwrap().rows().enumerate() {
+        for (row_index, row) in range.rows().enumerate() {
             if row_index == 0 {
                 headers = row
                     .iter()
@@ -147,19 +478,65 @@ impl FileProcessor {
             return Err(FileProcessorError::EmptyFileData);
         }
         let mut file_data = FileData { headers, data };
-        self.convert_excel_timestamp(&mut file_data)?;
+        let date_system = self.excel_date_system.unwrap_or_else(||
+            Self::detect_excel_date_system(file_path)
+        );
+        self.convert_excel_timestamp(&mut file_data, date_system)?;
 
         Ok(file_data)
     }
 
+    /// Reads the `date1904` flag from the workbook properties, falling back to the 1900
+    /// system if the workbook part cannot be read.
+    fn detect_excel_date_system(file_path: &str) -> ExcelDateSystem {
+        let workbook_xml = File::open(file_path)
+            .ok()
+            .and_then(|file| zip::ZipArchive::new(file).ok())
+            .and_then(|mut archive| {
+                let mut content = String::new();
+                archive
+                    .by_name("xl/workbook.xml")
+                    .ok()?
+                    .read_to_string(&mut content)
+                    .ok()?;
+                Some(content)
+            })
+            .unwrap_or_default();
+
+        if workbook_xml.contains("date1904=\"1\"") || workbook_xml.contains("date1904=\"true\"") {
+            info!("Workbook uses the 1904 date system");
+            ExcelDateSystem::Excel1904
+        } else {
+            ExcelDateSystem::Excel1900
+        }
+    }
+
+    /// True for `.csv.gz` archives, matched case-insensitively on the full file name.
+    pub fn is_gzipped_csv(file_path: &str) -> bool {
+        file_path.to_lowercase().ends_with(".csv.gz")
+    }
+
+    /// Reads a CSV file, transparently decompressing it when it starts with the gzip magic
+    /// bytes so archived `.csv.gz` exports can be loaded directly.
     fn read_csv(&self, file_path: &str) -> Result<FileData, FileProcessorError> {
         info!("Reading CSV file: {}", file_path);
 
-        let mut file = File::open(file_path)?;
+        let mut bytes = Vec::new();
+        File::open(file_path)?.read_to_end(&mut bytes)?;
         let mut content = String::new();
-        file.read_to_string(&mut content)?;
+        if bytes.starts_with(&GZIP_MAGIC) {
+            GzDecoder::new(bytes.as_slice()).read_to_string(&mut content)?;
+        } else {
+            content = String::from_utf8(bytes).map_err(|e|
+                FileProcessorError::ParseError(format!("CSV file is not valid UTF-8: {}", e))
+            )?;
+        }
 
-        let mut reader = ReaderBuilder::new().has_headers(true).from_reader(content.as_bytes());
+        let delimiter = self.delimiter.unwrap_or_else(|| Self::sniff_delimiter(&content));
+        let mut reader = ReaderBuilder::new()
+            .has_headers(true)
+            .delimiter(delimiter)
+            .from_reader(content.as_bytes());
 
         let headers = reader
             .headers()?
@@ -179,17 +556,149 @@ impl FileProcessor {
             )
             .collect::<Result<_, _>>()?;
 
-        if data.is_empty() {
+        let mut file_data = FileData { headers, data };
+        // Before units detection, so "0,5" reads as a number rather than a unit
+        if delimiter != b',' {
+            Self::normalize_decimal_commas(&mut file_data.data);
+        }
+        let has_units_row = self.units_row.unwrap_or_else(|| self.is_units_row(&file_data));
+        if has_units_row && !file_data.data.is_empty() {
+            let units = file_data.data.remove(0);
+            Self::merge_units(&mut file_data.headers, &units);
+            info!("Read row 2 as units: {:?}", file_data.headers);
+        }
+
+        if file_data.data.is_empty() {
             error!("CSV file is empty: {}", file_path);
             return Err(FileProcessorError::EmptyFileData);
         }
 
-        Ok(FileData { headers, data })
+        Ok(file_data)
     }
 
+    /// Picks the delimiter that splits each of the first few lines into the same number of
+    /// fields, preferring the one giving the most fields. Falls back to a comma when no
+    /// candidate is consistent or two are equally good, so comma files read as before.
+    fn sniff_delimiter(content: &str) -> u8 {
+        let lines: Vec<&str> = content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .take(SNIFF_LINES)
+            .collect();
+        let consistent_fields = |delimiter: u8| -> Option<usize> {
+            let counts: Vec<usize> = lines
+                .iter()
+                .map(|line| line.bytes().filter(|&b| b == delimiter).count())
+                .collect();
+            let first = *counts.first()?;
+            (first > 0 && counts.iter().all(|&count| count == first)).then_some(first)
+        };
+
+        let mut best: Option<(u8, usize)> = None;
+        let mut tied = false;
+        for delimiter in SNIFF_DELIMITERS {
+            let Some(fields) = consistent_fields(delimiter) else {
+                continue;
+            };
+            match best {
+                Some((_, best_fields)) if fields == best_fields => {
+                    tied = true;
+                }
+                Some((_, best_fields)) if fields < best_fields => {}
+                _ => {
+                    best = Some((delimiter, fields));
+                    tied = false;
+                }
+            }
+        }
+
+        match best {
+            Some((delimiter, _)) if !tied => {
+                if delimiter != b',' {
+                    info!("Detected '{}' delimiter", (delimiter as char).escape_default());
+                }
+                delimiter
+            }
+            _ => b',',
+        }
+    }
+
+    /// Rewrites decimal commas (`1,25`) as points in files that don't use comma delimiters,
+    /// as in many European logger exports. Only cells that then parse as numbers change, and
+    /// three digits after the comma (`1,234`) read as a thousands separator unless the whole
+    /// part is zero.
+    fn normalize_decimal_commas(data: &mut [Vec<String>]) {
+        data.par_iter_mut().for_each(|row| {
+            for cell in row.iter_mut() {
+                let Some((whole, fraction)) = cell.trim().split_once(',') else {
+                    continue;
+                };
+                let thousands =
+                    fraction.len() == 3 && !matches!(whole.trim_start_matches('-'), "0" | "");
+                if thousands || fraction.contains(',') || cell.contains('.') {
+                    continue;
+                }
+                let replaced = format!("{}.{}", whole, fraction);
+                if replaced.parse::<f64>().is_ok() {
+                    *cell = replaced;
+                }
+            }
+        });
+    }
+
+    /// A units row has a unit such as `mm` in most of its value cells and no numbers, and
+    /// is followed by a row that does hold numbers, so a short file of text columns isn't
+    /// mistaken for one. The timestamp column is left out, so a first reading with blank
+    /// values isn't taken for units either.
+    fn is_units_row(&self, file_data: &FileData) -> bool {
+        let is_number = |cell: &str| cell.trim().parse::<f64>().is_ok();
+        let [first, second, ..] = file_data.data.as_slice() else {
+            return false;
+        };
+        let timestamp_index = self
+            .identify_timestamp_column(file_data)
+            .ok()
+            .and_then(|column| file_data.headers.iter().position(|header| *header == column));
+        let value_cells = |row: &[String]| -> Vec<String> {
+            row.iter()
+                .enumerate()
+                .filter(|(index, _)| Some(*index) != timestamp_index)
+                .map(|(_, cell)| cell.trim().to_string())
+                .collect()
+        };
+
+        let units = value_cells(first);
+        let unit_cells = units
+            .iter()
+            .filter(|cell| !cell.is_empty() && !is_number(cell.as_str()))
+            .count();
+        !units.iter().any(|cell| is_number(cell.as_str())) &&
+            unit_cells * 2 > units.len() &&
+            value_cells(second)
+                .iter()
+                .any(|cell| is_number(cell.as_str()))
+    }
+
+    /// Appends each non-empty unit to its column name as `name|unit`, the form the column
+    /// patterns and unit checks already expect. Names already ending in the unit are kept.
+    fn merge_units(headers: &mut [String], units: &[String]) {
+        for (header, unit) in headers.iter_mut().zip(units) {
+            let unit = unit.trim();
+            if !unit.is_empty() && !header.ends_with(&format!("|{}", unit)) {
+                *header = format!("{}|{}", header, unit);
+            }
+        }
+    }
+
+    /// Converts Excel serial dates to `%Y-%m-%d %H:%M:%S` strings.
+    ///
+    /// The 1900 system counts from 1899-12-30 so that serials from 1900-03-01 onwards line
+    /// up despite Excel treating 1900 as a leap year. Serials before 61 are shifted by a day
+    /// to account for the fictitious 1900-02-29 (serial 60 maps to 1900-03-01).
     pub fn convert_excel_timestamp(
         &mut self,
-        file_data: &mut FileData
+        file_data: &mut FileData,
+        date_system: ExcelDateSystem
     ) -> Result<(), FileProcessorError> {
         let timestamp_column = self.identify_timestamp_column(file_data)?;
         let column_index = file_data.headers
@@ -197,15 +706,26 @@ impl FileProcessor {
             .position(|h| h == &timestamp_column)
             .ok_or(FileProcessorError::TimestampColumnNotFound)?;
 
-        let excel_epoch = NaiveDateTime::new(
-            NaiveDate::from_ymd_opt(1899, 12, 30).unwrap(),
-            NaiveTime::from_hms_opt(0, 0, 0).unwrap()
-        );
+        let excel_epoch = match date_system {
+            ExcelDateSystem::Excel1900 =>
+                NaiveDateTime::new(
+                    NaiveDate::from_ymd_opt(1899, 12, 30).unwrap(),
+                    NaiveTime::from_hms_opt(0, 0, 0).unwrap()
+                ),
+            ExcelDateSystem::Excel1904 =>
+                NaiveDateTime::new(
+                    NaiveDate::from_ymd_opt(1904, 1, 1).unwrap(),
+                    NaiveTime::from_hms_opt(0, 0, 0).unwrap()
+                ),
+        };
 
         file_data.data.par_iter_mut().for_each(|row| {
             if let Some(timestamp) = row.get_mut(column_index) {
                 if let Ok(excel_date) = timestamp.parse::<f64>() {
-                    let days = excel_date.trunc() as i64;
+                    let mut days = excel_date.trunc() as i64;
+                    if date_system == ExcelDateSystem::Excel1900 && days < 61 {
+                        days += 1;
+                    }
                     let seconds = (excel_date.fract() * 86400.0).round() as i64;
                     let datetime = excel_epoch + Duration::days(days) + Duration::seconds(seconds);
                     *timestamp = datetime.format("%Y-%m-%d %H:%M:%S").to_string();
@@ -228,20 +748,55 @@ impl FileProcessor {
             .ok_or(FileProcessorError::TimestampColumnNotFound)
     }
 
+    /// Finds the first column whose header matches the quality pattern, skipping the
+    /// timestamp and measurement columns.
+    pub fn identify_quality_column(
+        &self,
+        file_data: &FileData,
+        timestamp_column: &str
+    ) -> Option<String> {
+        file_data.headers
+            .iter()
+            .find(|&header| {
+                header != timestamp_column &&
+                    self.quality_pattern.is_match(header) &&
+                    !self.column_patterns.values().any(|pattern| pattern.is_match(header))
+            })
+            .cloned()
+    }
+
+    /// Name of the column holding the `QualityFlag` codes parsed from quality column `header`.
+    fn quality_flag_column(header: &str) -> String {
+        format!("{}{}", header, QUALITY_FLAG_SUFFIX)
+    }
+
+    /// The timestamp formats tried during detection, user-supplied formats first.
+    pub fn supported_timestamp_formats(&self) -> Vec<String> {
+        self.timestamp_formats.clone()
+    }
+
+    /// Adds a chrono format string to the detection list, ahead of the built-in formats but
+    /// after any formats added earlier. Returns `false` if the format was already present.
+    pub fn add_timestamp_format(&mut self, format: &str) -> Result<bool, FileProcessorError> {
+        let format = format.trim();
+        if format.is_empty() || StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+            return Err(
+                FileProcessorError::ParseError(format!("Invalid timestamp format: '{}'", format))
+            );
+        }
+        if self.timestamp_formats.iter().any(|f| f == format) {
+            return Ok(false);
+        }
+        self.timestamp_formats.insert(self.user_timestamp_formats, format.to_string());
+        self.user_timestamp_formats += 1;
+        Ok(true)
+    }
+
     pub fn identify_timestamp_format(
         &self,
         file_data: &FileData,
         timestamp_column: &str
     ) -> Result<String, FileProcessorError> {
-        let timestamp_formats = vec![
-            "%d/%m/%Y %H:%M",
-            "%m/%d/%Y %H:%M",
-            "%d-%m-%Y %H:%M:%S",
-            "%d-%m-%Y %H:%M",
-            "%Y%m%d%H%M%S",
-            "%Y-%m-%d %H:%M:%S",
-            "%Y/%m/%d %H:%M:%S"
-        ];
         let column_index = file_data.headers
             .iter()
             .position(|h| h == timestamp_column)
@@ -250,18 +805,25 @@ impl FileProcessor {
         let max_rows_to_check = (100).min(file_data.data.len());
         for row in file_data.data.iter().take(max_rows_to_check) {
             if let Some(timestamp) = row.get(column_index) {
-                for format in &timestamp_formats {
+                for format in &self.timestamp_formats {
                     if NaiveDateTime::parse_from_str(timestamp, format).is_ok() {
-                        *format_counts.entry(format).or_insert(0) += 1;
+                        *format_counts.entry(format.as_str()).or_insert(0) += 1;
                         break;
                     }
                 }
             }
         }
-        format_counts
-            .into_iter()
-            .max_by_key(|&(_, count)| count)
-            .map(|(format, _)| format.to_string())
+        // Most frequent match wins; ties go to the format tried first
+        self.timestamp_formats
+            .iter()
+            .filter_map(|format| format_counts.get(format.as_str()).map(|&count| (format, count)))
+            .fold(None, |best: Option<(&String, usize)>, (format, count)| {
+                match best {
+                    Some((_, best_count)) if best_count >= count => best,
+                    _ => Some((format, count)),
+                }
+            })
+            .map(|(format, _)| format.clone())
             .ok_or(FileProcessorError::TimestampFormatNotIdentified)
     }
 
@@ -279,12 +841,119 @@ impl FileProcessor {
             if let Some(timestamp) = row.get_mut(column_index) {
                 *timestamp = NaiveDateTime::parse_from_str(timestamp, format)
                     .map(|parsed_date| parsed_date.format("%Y-%m-%d %H:%M:%S").to_string())
-                    .unwrap_or_else(|_| "Invalid Date".to_string());
+                    .unwrap_or_else(|_| INVALID_DATE.to_string());
             }
         });
         Ok(())
     }
 
+    /// Applies the invalid timestamp policy to rows marked by `parse_dates`, returning the
+    /// number of rows dropped.
+    pub fn handle_invalid_timestamps(
+        &self,
+        file_data: &mut FileData,
+        timestamp_column: &str
+    ) -> Result<usize, FileProcessorError> {
+        let column_index = file_data.headers
+            .iter()
+            .position(|h| h == timestamp_column)
+            .ok_or(FileProcessorError::TimestampColumnNotFound)?;
+        let is_invalid = |row: &Vec<String>| {
+            row.get(column_index).is_none_or(|ts| ts == INVALID_DATE)
+        };
+
+        let invalid_count = file_data.data
+            .iter()
+            .filter(|row| is_invalid(row))
+            .count();
+        if invalid_count == 0 {
+            return Ok(0);
+        }
+
+        match self.invalid_timestamp_policy {
+            InvalidTimestampPolicy::Error => {
+                let first_row = file_data.data.iter().position(is_invalid).unwrap_or(0);
+                error!("{} rows have unparseable timestamps", invalid_count);
+                Err(
+                    FileProcessorError::ParseError(
+                        format!(
+                            "{} rows have unparseable timestamps (first at data row {})",
+                            invalid_count,
+                            first_row + 1
+                        )
+                    )
+                )
+            }
+            InvalidTimestampPolicy::Drop => {
+                file_data.data.retain(|row| !is_invalid(row));
+                log::warn!("Dropped {} rows with unparseable timestamps", invalid_count);
+                if file_data.data.is_empty() {
+                    return Err(FileProcessorError::EmptyFileData);
+                }
+                Ok(invalid_count)
+            }
+        }
+    }
+
+    /// Removes sparsely populated columns, keeping the timestamp column and any column
+    /// matching a known measurement pattern. Returns the names of the pruned columns.
+    pub fn prune_sparse_columns(
+        &self,
+        file_data: &mut FileData,
+        timestamp_column: &str
+    ) -> Vec<String> {
+        let min_ratio = match self.min_non_null_ratio {
+            Some(ratio) if !file_data.data.is_empty() => ratio,
+            _ => {
+                return Vec::new();
+            }
+        };
+        let row_count = file_data.data.len() as f64;
+
+        let keep: Vec<bool> = file_data.headers
+            .iter()
+            .enumerate()
+            .map(|(i, header)| {
+                if
+                    header == timestamp_column ||
+                    self.quality_col.as_ref() == Some(header) ||
+                    self.column_patterns.values().any(|pattern| pattern.is_match(header))
+                {
+                    return true;
+                }
+                let populated = file_data.data
+                    .iter()
+                    .filter(|row| row.get(i).is_some_and(|v| !v.trim().is_empty()))
+                    .count();
+                (populated as f64) / row_count >= min_ratio
+            })
+            .collect();
+
+        let pruned: Vec<String> = file_data.headers
+            .iter()
+            .zip(&keep)
+            .filter(|(_, &k)| !k)
+            .map(|(h, _)| h.clone())
+            .collect();
+        if pruned.is_empty() {
+            return pruned;
+        }
+
+        let retain = |values: &mut Vec<String>| {
+            let mut index = 0;
+            values.retain(|_| {
+                let k = keep.get(index).copied().unwrap_or(true);
+                index += 1;
+                k
+            });
+        };
+        retain(&mut file_data.headers);
+        file_data.data.iter_mut().for_each(retain);
+
+        info!("Pruned {} sparse columns: {}", pruned.len(), pruned.join(", "));
+        pruned
+    }
+
     fn get_parsed_timestamps(
         &self,
         file_data: &FileData,
@@ -329,28 +998,142 @@ impl FileProcessor {
         ))
     }
 
+    /// Detects the logging interval as the most common spacing between readings. A file
+    /// with a single reading has no spacing and needs the interval option; one with two
+    /// readings is accepted with a warning, as its interval rests on one sample.
     pub fn calculate_interval(
         &self,
         file_data: &FileData,
         timestamp_column: &str,
         format: &str
     ) -> Result<Duration, FileProcessorError> {
-        let mut timestamps = self.get_parsed_timestamps(file_data, timestamp_column, format)?;
+        let timestamps = self.get_parsed_timestamps(file_data, timestamp_column, format)?;
+        Self::detect_interval(timestamps, "The file")
+    }
+
+    /// Most common spacing of `timestamps`, with the single and two reading cases of
+    /// `calculate_interval`. `source` names what holds the readings in messages.
+    fn detect_interval(
+        mut timestamps: Vec<NaiveDateTime>,
+        source: &str
+    ) -> Result<Duration, FileProcessorError> {
         timestamps.sort_unstable();
-        let mut intervals = HashMap::new();
+        timestamps.dedup();
+        match timestamps.len() {
+            0 => {
+                return Err(FileProcessorError::ParseError("No valid timestamps found".to_string()));
+            }
+            1 => {
+                return Err(
+                    FileProcessorError::ParseError(
+                        format!(
+                            "{} holds a single reading, so its interval can't be detected. \
+                             Set the interval option to convert it.",
+                            source
+                        )
+                    )
+                );
+            }
+            2 => {
+                log::warn!(
+                    "{} holds two readings; the interval is based on a single spacing. \
+                     Set the interval option if it is not the logging interval.",
+                    source
+                );
+            }
+            _ => {}
+        }
+        Self::mode_interval(&timestamps).ok_or_else(|| {
+            FileProcessorError::ParseError("Could not determine a mode interval".to_string())
+        })
+    }
+
+    /// Counts each positive spacing between consecutive sorted timestamps.
+    pub(crate) fn interval_histogram(timestamps: &[NaiveDateTime]) -> HashMap<Duration, usize> {
+        let mut intervals: HashMap<Duration, usize> = HashMap::new();
         for window in timestamps.windows(2) {
             if let [prev, next] = window {
                 let diff = *next - *prev;
-                *intervals.entry(diff).or_insert(0) += 1;
+                if diff > Duration::zero() {
+                    *intervals.entry(diff).or_insert(0) += 1;
+                }
             }
         }
         intervals
+    }
+
+    /// Most frequent spacing between consecutive sorted timestamps. Ties are broken
+    /// deterministically: a common logger interval (see `COMMON_INTERVALS`) wins, then the
+    /// smaller interval, so the same file always yields the same interval and gap count.
+    /// Duplicate timestamps (zero spacing) are ignored.
+    fn mode_interval(timestamps: &[NaiveDateTime]) -> Option<Duration> {
+        Self::interval_histogram(timestamps)
             .into_iter()
-            .max_by_key(|&(_, count)| count)
-            .map(|(interval, _)| interval)
-            .ok_or_else(|| {
-                FileProcessorError::ParseError("Could not determine a mode interval".to_string())
+            .max_by(|(a, a_count), (b, b_count)| {
+                let a_common = COMMON_INTERVALS.contains(&a.num_seconds());
+                let b_common = COMMON_INTERVALS.contains(&b.num_seconds());
+                a_count
+                    .cmp(b_count)
+                    .then(a_common.cmp(&b_common))
+                    .then(b.cmp(a))
             })
+            .map(|(interval, _)| interval)
+    }
+
+    /// Finds stretches where the logger sampled at a spacing other than `interval`, using the
+    /// same consecutive differences as `calculate_interval`. A run of at least
+    /// `MIN_INTERVAL_CHANGE_RUN` equal non-interval spacings is a rate change, even when the
+    /// spacing is a whole multiple of the interval (5 to 15 minutes); anything shorter, such
+    /// as a single long spacing across a gap, is ignored.
+    pub fn find_interval_changes(
+        timestamps: &[NaiveDateTime],
+        interval: Duration
+    ) -> Vec<IntervalChange> {
+        let interval_seconds = interval.num_seconds();
+        let mut changes = Vec::new();
+        if interval_seconds <= 0 {
+            return changes;
+        }
+
+        let format = "%Y-%m-%d %H:%M:%S";
+        let mut run: Option<(usize, i64)> = None;
+        let mut diff_count = 0;
+
+        let mut close_run = |start: usize, end: usize, seconds: i64, count: usize| {
+            if count >= MIN_INTERVAL_CHANGE_RUN {
+                changes.push(IntervalChange {
+                    start: timestamps[start].format(format).to_string(),
+                    end: timestamps[end].format(format).to_string(),
+                    interval_seconds: seconds,
+                    readings: end - start + 1,
+                });
+            }
+        };
+
+        for (i, window) in timestamps.windows(2).enumerate() {
+            let seconds = (window[1] - window[0]).num_seconds();
+            let is_change = seconds > 0 && seconds != interval_seconds;
+
+            match run {
+                Some((_, run_seconds)) if is_change && seconds == run_seconds => {
+                    diff_count += 1;
+                }
+                _ => {
+                    if let Some((start, run_seconds)) = run.take() {
+                        close_run(start, i, run_seconds, diff_count);
+                    }
+                    if is_change {
+                        run = Some((i, seconds));
+                        diff_count = 1;
+                    }
+                }
+            }
+        }
+        if let Some((start, run_seconds)) = run {
+            close_run(start, timestamps.len() - 1, run_seconds, diff_count);
+        }
+
+        changes
     }
 
     pub fn create_timestamp_series(
@@ -358,58 +1141,279 @@ impl FileProcessor {
         file_data: &FileData,
         timestamp_column: &str,
         format: &str
-    ) -> Result<(FileData, usize), FileProcessorError> {
+    ) -> Result<(FileData, usize, Vec<bool>), FileProcessorError> {
         let (start_str, end_str) = self.get_start_end_timestamps(
             file_data,
             timestamp_column,
             format
         )?;
-        let start = NaiveDateTime::parse_from_str(&start_str, "%Y-%m-%d %H:%M:%S").map_err(|_| {
+        let mut start = NaiveDateTime::parse_from_str(&start_str, "%Y-%m-%d %H:%M:%S").map_err(|_| {
             FileProcessorError::ParseError("Failed to parse start timestamp".to_string())
         })?;
-        let end = NaiveDateTime::parse_from_str(&end_str, "%Y-%m-%d %H:%M:%S").map_err(|_| {
+        let mut end = NaiveDateTime::parse_from_str(&end_str, "%Y-%m-%d %H:%M:%S").map_err(|_| {
             FileProcessorError::ParseError("Failed to parse end timestamp".to_string())
         })?;
-        let interval = self.calculate_interval(file_data, timestamp_column, format)?;
+        if let Some(tz) = self.timezone {
+            start = Self::local_to_utc(tz, start, false);
+            end = Self::local_to_utc(tz, end, true);
+        }
+        let interval = match self.interval_override {
+            Some(interval) => interval,
+            None => self.calculate_interval(file_data, timestamp_column, format)?,
+        };
         self.interval = Some(interval.clone());
+
+        let mut raw_timestamps = self.get_parsed_timestamps(file_data, timestamp_column, format)?;
+        raw_timestamps.sort_unstable();
+        self.interval_changes = Self::find_interval_changes(&raw_timestamps, interval);
+        if !self.interval_changes.is_empty() {
+            log::warn!(
+                "Sampling interval changes within the record: {} stretch(es) differ from {}s",
+                self.interval_changes.len(),
+                interval.num_seconds()
+            );
+        }
+
         let timestamp_index = file_data.headers
             .iter()
             .position(|h| h == timestamp_column)
             .ok_or(FileProcessorError::TimestampColumnNotFound)?;
         let mut data_map: HashMap<String, Vec<String>> = HashMap::new();
+        let mut seen_local: HashSet<NaiveDateTime> = HashSet::new();
+        let mut collapsed = 0;
         for row in &file_data.data {
             if let Some(timestamp) = row.get(timestamp_index) {
-                let parsed_timestamp = NaiveDateTime::parse_from_str(timestamp, format).map_err(
+                let mut parsed_timestamp = NaiveDateTime::parse_from_str(timestamp, format).map_err(
                     |_| {
                         FileProcessorError::ParseError(
                             format!("Failed to parse timestamp: {}", timestamp)
                         )
                     }
                 )?;
+                let mut row = row.clone();
+                if let Some(tz) = self.timezone {
+                    let repeated = !seen_local.insert(parsed_timestamp);
+                    parsed_timestamp = Self::local_to_utc(tz, parsed_timestamp, repeated);
+                }
                 let formatted_timestamp = parsed_timestamp.format("%Y-%m-%d %H:%M:%S").to_string();
-                data_map.insert(formatted_timestamp, row.clone());
+                row[timestamp_index] = formatted_timestamp.clone();
+                if data_map.insert(formatted_timestamp, row).is_some() {
+                    collapsed += 1;
+                }
             }
         }
+        if collapsed > 0 {
+            log::warn!(
+                "{} reading(s) share a timestamp with a later row and were dropped",
+                collapsed
+            );
+        }
         let mut new_data: Vec<Vec<String>> = Vec::new();
+        let mut infilled: Vec<bool> = Vec::new();
         let mut gap_count = 0;
         let mut current = start;
         while current <= end {
             let timestamp = current.format("%Y-%m-%d %H:%M:%S").to_string();
             if let Some(existing_row) = data_map.get(&timestamp) {
                 new_data.push(existing_row.clone());
+                infilled.push(false);
             } else {
                 let mut empty_row = vec![timestamp];
                 empty_row.extend(vec!["".to_string(); file_data.headers.len() - 1]);
                 new_data.push(empty_row);
+                infilled.push(true);
                 gap_count += 1;
             }
             current += interval;
         }
+        if gap_count > 0 && self.gap_fill != GapFillStrategy::Empty {
+            let fill_patterns: Vec<&Regex> = GAP_FILL_COLUMNS.iter()
+                .filter_map(|key| self.column_patterns.get(*key))
+                .collect();
+            let value_columns: Vec<usize> = file_data.headers
+                .iter()
+                .enumerate()
+                .filter(|(_, header)| fill_patterns.iter().any(|pattern| pattern.is_match(header)))
+                .map(|(i, _)| i)
+                .collect();
+            Self::fill_gap_rows(&mut new_data, &infilled, &value_columns, self.gap_fill);
+            info!("Filled {} gap rows using {:?}", gap_count, self.gap_fill);
+        }
         let new_file_data = FileData {
             headers: file_data.headers.clone(),
             data: new_data,
         };
-        Ok((new_file_data, gap_count))
+        Ok((new_file_data, gap_count, infilled))
+    }
+
+    /// Fills `value_columns` of each run of inserted rows from the real rows either side.
+    /// Forward fill copies the cell before the run; linear interpolation needs numbers on
+    /// both sides, so gaps without a bracketing reading stay empty.
+    fn fill_gap_rows(
+        data: &mut [Vec<String>],
+        infilled: &[bool],
+        value_columns: &[usize],
+        strategy: GapFillStrategy
+    ) {
+        let mut row = 0;
+        while row < data.len() {
+            if !infilled[row] {
+                row += 1;
+                continue;
+            }
+            let start = row;
+            while row < data.len() && infilled[row] {
+                row += 1;
+            }
+            let (before, after) = (start.checked_sub(1), Some(row).filter(|&r| r < data.len()));
+            let run_length = row - start;
+
+            for &column in value_columns {
+                let cell = |index: Option<usize>| {
+                    index
+                        .and_then(|i| data[i].get(column))
+                        .filter(|c| !c.trim().is_empty())
+                        .cloned()
+                };
+                let (previous, next) = (cell(before), cell(after));
+                for (offset, index) in (start..row).enumerate() {
+                    let value = match strategy {
+                        GapFillStrategy::Empty => None,
+                        GapFillStrategy::ForwardFill => previous.clone(),
+                        GapFillStrategy::LinearInterpolate => {
+                            let number =
+                                |c: &Option<String>| c.as_ref()?.trim().parse::<f64>().ok();
+                            number(&previous).zip(number(&next)).map(|(from, to)| {
+                                let fraction = ((offset + 1) as f64) / ((run_length + 1) as f64);
+                                (from + (to - from) * fraction).to_string()
+                            })
+                        }
+                    };
+                    if let Some(value) = value {
+                        if let Some(target) = data[index].get_mut(column) {
+                            *target = value;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Builds the readings as parsed from the final series: every column other than the
+    /// timestamp is emptied on the rows `infilled` marks as inserted, undoing any gap fill,
+    /// and the mask itself is kept as the infilled column.
+    fn raw_readings(
+        series: &[Series],
+        infilled: &Series,
+        timestamp_column: &str
+    ) -> Result<DataFrame, FileProcessorError> {
+        let recorded = !infilled.bool()?;
+        let mut columns = Vec::with_capacity(series.len() + 1);
+        for s in series {
+            if s.name().as_str() == timestamp_column {
+                columns.push(s.clone());
+            } else {
+                let empty = Series::full_null(s.name().clone(), s.len(), s.dtype());
+                columns.push(s.zip_with(&recorded, &empty)?);
+            }
+        }
+        columns.push(infilled.clone());
+        Ok(DataFrame::new(columns)?)
+    }
+
+    /// Removes leading and trailing rows in which every measurement column is empty, so the
+    /// start and end timestamps span only recorded data. Rows between the first and last
+    /// populated readings are kept. Returns the number of rows removed and how many of those
+    /// were inserted by gap filling.
+    pub fn trim_empty_edges(
+        &self,
+        file_data: &mut FileData,
+        infilled: &mut Vec<bool>
+    ) -> (usize, usize) {
+        let value_indices: Vec<usize> = file_data.headers
+            .iter()
+            .enumerate()
+            .filter(|(_, header)| {
+                self.column_patterns.values().any(|pattern| pattern.is_match(header))
+            })
+            .map(|(i, _)| i)
+            .collect();
+        if value_indices.is_empty() {
+            return (0, 0);
+        }
+
+        let has_value = |row: &Vec<String>| {
+            value_indices
+                .iter()
+                .any(|&i| row.get(i).is_some_and(|v| v.trim().parse::<f64>().is_ok()))
+        };
+        let (Some(first), Some(last)) = (
+            file_data.data.iter().position(has_value),
+            file_data.data.iter().rposition(has_value),
+        ) else {
+            return (0, 0);
+        };
+
+        let trimmed = first + (file_data.data.len() - 1 - last);
+        if trimmed == 0 {
+            return (0, 0);
+        }
+        let trimmed_infilled = infilled[..first]
+            .iter()
+            .chain(&infilled[last + 1..])
+            .filter(|&&f| f)
+            .count();
+        file_data.data.truncate(last + 1);
+        file_data.data.drain(..first);
+        infilled.truncate(last + 1);
+        infilled.drain(..first);
+
+        info!("Trimmed {} empty rows from the start and end of the record", trimmed);
+        (trimmed, trimmed_infilled)
+    }
+
+    /// Reads `file_path` up to date parsing and returns the headers along with every
+    /// timestamp shared by more than one row, in time order. Source rows are numbered from 1
+    /// after the header and the values are each row's full contents.
+    pub fn find_duplicate_timestamps(
+        &mut self,
+        file_path: &str
+    ) -> Result<(Vec<String>, Vec<DuplicateTimestamp>), FileProcessorError> {
+        let mut file_data = self.read_file(file_path)?;
+        let timestamp_column = self.identify_timestamp_column(&file_data)?;
+        let timestamp_format = match &self.timestamp_format {
+            Some(format) => format.clone(),
+            None => self.identify_timestamp_format(&file_data, &timestamp_column)?,
+        };
+        self.parse_dates(&mut file_data, &timestamp_column, &timestamp_format)?;
+        let timestamp_index = file_data.headers
+            .iter()
+            .position(|h| *h == timestamp_column)
+            .ok_or(FileProcessorError::TimestampColumnNotFound)?;
+
+        let mut rows_by_timestamp: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+        for (index, row) in file_data.data.iter().enumerate() {
+            if let Some(timestamp) = row.get(timestamp_index).filter(|t| *t != INVALID_DATE) {
+                rows_by_timestamp.entry(timestamp.as_str()).or_default().push(index);
+            }
+        }
+
+        let duplicates = rows_by_timestamp
+            .into_iter()
+            .filter(|(_, rows)| rows.len() > 1)
+            .map(|(timestamp, rows)| DuplicateTimestamp {
+                timestamp: timestamp.to_string(),
+                source_rows: rows
+                    .iter()
+                    .map(|row| row + 1)
+                    .collect(),
+                values: rows
+                    .iter()
+                    .map(|&row| file_data.data[row].clone())
+                    .collect(),
+            })
+            .collect();
+        Ok((file_data.headers, duplicates))
     }
 
     fn extract_columns(
@@ -465,7 +1469,15 @@ impl FileProcessor {
         }
         // Extract other columns
         for (col_type, pattern) in &self.column_patterns {
-            let cols = self.extract_columns(pattern, &df_columns);
+            let mut cols = self.extract_columns(pattern, &df_columns);
+            // A text column can't feed a calculation, so it is never mapped
+            cols.retain(|(name, _, _, _)| {
+                let numeric = df.column(name).is_ok_and(|s| s.dtype().is_numeric());
+                if !numeric {
+                    log::warn!("Not mapping text column '{}' as {}", name, col_type);
+                }
+                numeric
+            });
             if !cols.is_empty() {
                 column_mapping.insert(col_type.clone(), cols);
             }
@@ -483,39 +1495,149 @@ impl FileProcessor {
         self.monitor_type = self.site_info.get_monitor_type().to_string();
     }
 
-    pub fn process_file(
-        &mut self,
-        file_path: &str
-    ) -> Result<ProcessedFileData, FileProcessorError> {
-        let mut file_data = self.read_file(file_path)?;
-        let timestamp_column = self.identify_timestamp_column(&file_data)?;
-        self.time_col = Some(timestamp_column.clone());
-        let timestamp_format = self.identify_timestamp_format(&file_data, &timestamp_column)?;
-        self.parse_dates(&mut file_data, &timestamp_column, &timestamp_format)?;
-        let (file_data_with_series, gap_count) = self.create_timestamp_series(
-            &file_data,
-            &timestamp_column,
-            "%Y-%m-%d %H:%M:%S"
-        )?;
+    /// True when fewer than `MIN_NUMERIC_RATIO` of the populated cells in column `index`
+    /// parse as numbers. Empty columns are not textual.
+    fn is_textual(file_data: &FileData, index: usize) -> bool {
+        let (populated, numeric) = file_data.data
+            .iter()
+            .filter_map(|row| row.get(index).map(|v| v.trim()))
+            .filter(|v| !v.is_empty())
+            .fold((0, 0), |(populated, numeric), v| {
+                (populated + 1, numeric + (v.parse::<f64>().is_ok() as usize))
+            });
+        populated > 0 && (numeric as f64) < (populated as f64) * MIN_NUMERIC_RATIO
+    }
+
+    /// True when every populated cell in column `index` parses as an integer.
+    /// Empty columns are not integer.
+    fn is_integer(file_data: &FileData, index: usize) -> bool {
+        let mut populated = file_data.data
+            .iter()
+            .filter_map(|row| row.get(index).map(|v| v.trim()))
+            .filter(|v| !v.is_empty())
+            .peekable();
+        populated.peek().is_some() && populated.all(|v| v.parse::<i64>().is_ok())
+    }
 
+    /// Builds one Series per column, with the timestamp column parsed from the normalised
+    /// `%Y-%m-%d %H:%M:%S` strings produced by `create_timestamp_series`.
+    /// Integer columns such as logger status codes stay `i64` unless they match a measurement
+    /// pattern, since depth, flow, velocity and rainfall are always read as `f64`.
+    /// The quality column is followed by a column of its parsed flag codes.
+    pub fn build_series(
+        &self,
+        file_data: &FileData,
+        timestamp_column: &str
+    ) -> Result<Vec<Series>, FileProcessorError> {
         let mut series_vec: Vec<Series> = Vec::new();
-        for (i, header) in file_data_with_series.headers.iter().enumerate() {
-            let series = if header == &timestamp_column {
-                let timestamps: Vec<NaiveDateTime> = file_data_with_series.data
+        for (i, header) in file_data.headers.iter().enumerate() {
+            let series = if header == timestamp_column {
+                let timestamps: Vec<NaiveDateTime> = file_data.data
                     .iter()
-                    .map(|row| NaiveDateTime::parse_from_str(&row[i], "%Y-%m-%d %H:%M:%S").unwrap())
-                    .collect();
+                    .enumerate()
+                    .map(|(row_index, row)| {
+                        let timestamp = row.get(i).map(|s| s.as_str()).unwrap_or("");
+                        NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%S").map_err(|_| {
+                            FileProcessorError::ParseError(
+                                format!(
+                                    "Invalid timestamp '{}' at data row {}",
+                                    timestamp,
+                                    row_index + 1
+                                )
+                            )
+                        })
+                    })
+                    .collect::<Result<_, _>>()?;
                 Series::new(header.into(), timestamps)
+            } else if Self::is_textual(file_data, i) {
+                log::warn!("Column '{}' is mostly non-numeric; keeping it as text", header);
+                let values: Vec<&str> = file_data.data
+                    .iter()
+                    .map(|row| row.get(i).map_or("", |v| v.as_str()))
+                    .collect();
+                Series::new(header.into(), values)
+            } else if
+                Self::is_integer(file_data, i) &&
+                !self.column_patterns.values().any(|pattern| pattern.is_match(header))
+            {
+                let values: Vec<Option<i64>> = file_data.data
+                    .iter()
+                    .map(|row| row.get(i).and_then(|v| v.trim().parse::<i64>().ok()))
+                    .collect();
+                Series::new(header.into(), values)
             } else {
-                let values: Vec<f64> = file_data_with_series.data
+                let values: Vec<f64> = file_data.data
                     .iter()
-                    .map(|row| row[i].parse::<f64>().unwrap_or(f64::NAN))
+                    .map(|row| {
+                        row.get(i)
+                            .and_then(|v| v.parse::<f64>().ok())
+                            .unwrap_or(f64::NAN)
+                    })
                     .collect();
                 Series::new(header.into(), values)
             };
             series_vec.push(series);
+
+            if self.quality_col.as_ref() == Some(header) {
+                // Flags are parsed into their own column of codes, leaving the logger's
+                // status values as read; blank or unknown flags become NaN
+                let codes: Vec<f64> = file_data.data
+                    .iter()
+                    .map(|row| {
+                        row.get(i)
+                            .and_then(|v| QualityFlag::from_str(v))
+                            .map_or(f64::NAN, |flag| flag.code())
+                    })
+                    .collect();
+                series_vec.push(Series::new(Self::quality_flag_column(header).into(), codes));
+            }
         }
+        Ok(series_vec)
+    }
 
+    pub fn process_file(
+        &mut self,
+        file_path: &str
+    ) -> Result<ProcessedFileData, FileProcessorError> {
+        self.report_progress("Reading file", 0);
+        let mut file_data = self.read_file(file_path)?;
+        self.report_progress("Parsing dates", 20);
+        let timestamp_column = self.identify_timestamp_column(&file_data)?;
+        self.time_col = Some(timestamp_column.clone());
+        let timestamp_format = match &self.timestamp_format {
+            Some(format) => format.clone(),
+            None => self.identify_timestamp_format(&file_data, &timestamp_column)?,
+        };
+        self.parse_dates(&mut file_data, &timestamp_column, &timestamp_format)?;
+        let invalid_timestamps = self.handle_invalid_timestamps(&mut file_data, &timestamp_column)?;
+        self.quality_col = self.identify_quality_column(&file_data, &timestamp_column);
+        if let Some(quality_col) = &self.quality_col {
+            info!("Using '{}' as the quality flag column", quality_col);
+        }
+        self.prune_sparse_columns(&mut file_data, &timestamp_column);
+        self.report_progress("Filling gaps", 40);
+        let (mut file_data_with_series, mut gap_count, mut infilled) =
+            self.create_timestamp_series(&file_data, &timestamp_column, "%Y-%m-%d %H:%M:%S")?;
+        let trimmed_rows = if self.trim_null_edges {
+            let (trimmed, trimmed_gaps) = self.trim_empty_edges(
+                &mut file_data_with_series,
+                &mut infilled
+            );
+            gap_count -= trimmed_gaps;
+            trimmed
+        } else {
+            0
+        };
+
+        self.report_progress("Building series", 60);
+        let mut series_vec = self.build_series(&file_data_with_series, &timestamp_column)?;
+        let infilled = Series::new(INFILLED_COLUMN.into(), infilled);
+        let raw_df = Self::raw_readings(&series_vec, &infilled, &timestamp_column)?;
+        if self.include_infilled_column || self.gap_fill != GapFillStrategy::Empty {
+            series_vec.push(infilled);
+        }
+
+        self.report_progress("Building DataFrame", 80);
         let df = DataFrame::new(series_vec)?;
         self.df = Some(df.clone());
 
@@ -540,50 +1662,46 @@ impl FileProcessor {
             start_timestamp: start,
             end_timestamp: end,
             gaps_filled: gap_count,
+            invalid_timestamps,
             interval: self.interval.unwrap(),
             column_mapping,
             monitor_type: self.monitor_type.clone(),
             site_id: self.site_info.get_site_id().into(),
             site_name: self.site_info.get_site_name().into(),
+            time_basis: match self.timezone {
+                Some(_) => UTC_TIME_BASIS,
+                None => NAIVE_TIME_BASIS,
+            }.to_string(),
+            interval_changes: self.interval_changes.clone(),
+            quality_column: self.quality_col.as_deref().map(Self::quality_flag_column),
+            trimmed_rows,
+            timestamp_format,
+            gap_fill: self.gap_fill,
+            raw_df,
         };
 
         // Update internal state
         self.df = Some(processed_data.df.clone());
         self.start_timestamp = Some(processed_data.start_timestamp.clone());
         self.end_timestamp = Some(processed_data.end_timestamp.clone());
+        self.report_progress("Complete", 100);
 
         Ok(processed_data)
     }
 
-    fn calculate_interval_from_df(
+    pub(crate) fn calculate_interval_from_df(
         &self,
         df: &DataFrame,
         time_col: &str
     ) -> Result<Duration, FileProcessorError> {
         let time_series = df.column(time_col)?;
-        let mut timestamps: Vec<NaiveDateTime> = time_series
+        let timestamps: Vec<NaiveDateTime> = time_series
             .datetime()?
             .as_datetime_iter()
             .filter_map(|opt_dt| opt_dt)
             .collect();
 
-        timestamps.sort_unstable();
-
-        let mut intervals = HashMap::new();
-        for window in timestamps.windows(2) {
-            if let [prev, next] = window {
-                let diff = *next - *prev;
-                *intervals.entry(diff).or_insert(0) += 1;
-            }
-        }
-
-        intervals
-            .into_iter()
-            .max_by_key(|&(_, count)| count)
-            .map(|(interval, _)| interval)
-            .ok_or_else(|| {
-                FileProcessorError::ParseError("Could not determine a mode interval".to_string())
-            })
+        Self::detect_interval(timestamps, "The selected range")
     }
 
     pub fn update_timestamps(
@@ -593,7 +1711,7 @@ impl FileProcessor {
     ) -> Result<UpdatedTimestampData, FileProcessorError> {
         // Check if DataFrame is loaded
         let df = self.df
-            .as_mut()
+            .as_ref()
             .ok_or(
                 FileProcessorError::ParseError(
                     "No data loaded. Cannot update timestamps.".to_string()
@@ -603,6 +1721,34 @@ impl FileProcessor {
         // Check if time column is identified
         let time_col = self.time_col.as_ref().ok_or(FileProcessorError::TimestampColumnNotFound)?;
 
+        let filtered_df = Self::filter_range(df, time_col, start_time, end_time)?;
+
+        // Update start and end timestamps
+        self.start_timestamp = Some(start_time.to_string());
+        self.end_timestamp = Some(end_time.to_string());
+
+        if self.interval.is_none() {
+            self.interval = Some(self.calculate_interval_from_df(&filtered_df, time_col)?);
+        }
+
+        self.df = Some(filtered_df);
+
+        Ok(UpdatedTimestampData {
+            start_timestamp: start_time.to_string(),
+            end_timestamp: end_time.to_string(),
+            interval: self.interval.unwrap(),
+            row_count: self.df.as_ref().unwrap().height(),
+        })
+    }
+
+    /// Returns the rows of `df` whose timestamps fall within `start_time` and `end_time`
+    /// inclusive, erroring if the range is inverted or contains no data.
+    pub fn filter_range(
+        df: &DataFrame,
+        time_col: &str,
+        start_time: &str,
+        end_time: &str
+    ) -> Result<DataFrame, FileProcessorError> {
         // Parse the new start and end times
         let new_start = NaiveDateTime::parse_from_str(start_time, "%Y-%m-%d %H:%M:%S").map_err(|_| {
             FileProcessorError::ParseError("Failed to parse start timestamp".to_string())
@@ -642,21 +1788,290 @@ impl FileProcessor {
             );
         }
 
-        // Update start and end timestamps
-        self.start_timestamp = Some(start_time.to_string());
-        self.end_timestamp = Some(end_time.to_string());
+        Ok(filtered_df)
+    }
+}
 
-        if self.interval.is_none() {
-            self.interval = Some(self.calculate_interval_from_df(&filtered_df, time_col)?);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> String {
+        format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name)
+    }
+
+    fn first_timestamp(file_data: &FileData) -> &str {
+        &file_data.data[0][0]
+    }
+
+    #[test]
+    fn reads_1900_workbook_dates() {
+        let mut processor = FileProcessor::new(None);
+        let file_data = processor.read_file(&fixture("excel_1900_dates.xlsx")).unwrap();
+        assert_eq!(first_timestamp(&file_data), "2024-01-01 00:00:00");
+        assert_eq!(file_data.data[1][0], "2024-01-01 00:15:00");
+    }
+
+    #[test]
+    fn detects_1904_workbook_dates() {
+        let mut processor = FileProcessor::new(None);
+        let file_data = processor.read_file(&fixture("excel_1904_dates.xlsx")).unwrap();
+        assert_eq!(first_timestamp(&file_data), "2028-01-02 00:00:00");
+    }
+
+    #[test]
+    fn configured_date_system_overrides_detection() {
+        let mut processor = FileProcessor::new(None);
+        processor
+            .apply_options(&ProcessingOptions {
+                excel_date_system: Some(ExcelDateSystem::Excel1900),
+                ..Default::default()
+            })
+            .unwrap();
+        let file_data = processor.read_file(&fixture("excel_1904_dates.xlsx")).unwrap();
+        assert_eq!(first_timestamp(&file_data), "2024-01-01 00:00:00");
+    }
+
+    #[test]
+    fn excel_1900_serials_skip_the_fictitious_leap_day() {
+        let mut processor = FileProcessor::new(None);
+        let mut file_data = FileData {
+            headers: vec!["Timestamp".to_string()],
+            data: ["59", "60", "61", "61.5"]
+                .iter()
+                .map(|serial| vec![serial.to_string()])
+                .collect(),
+        };
+        processor.convert_excel_timestamp(&mut file_data, ExcelDateSystem::Excel1900).unwrap();
+        let converted: Vec<&str> = file_data.data
+            .iter()
+            .map(|row| row[0].as_str())
+            .collect();
+        assert_eq!(converted, [
+            "1900-02-28 00:00:00",
+            "1900-03-01 00:00:00",
+            "1900-03-01 00:00:00",
+            "1900-03-01 12:00:00",
+        ]);
+    }
+
+    fn read_with_dates(processor: &mut FileProcessor, name: &str) -> FileData {
+        let mut file_data = processor.read_file(&fixture(name)).unwrap();
+        let format = processor.identify_timestamp_format(&file_data, "Timestamp").unwrap();
+        processor.parse_dates(&mut file_data, "Timestamp", &format).unwrap();
+        file_data
+    }
+
+    #[test]
+    fn drops_rows_with_malformed_timestamps() {
+        let mut processor = FileProcessor::new(None);
+        let mut file_data = read_with_dates(&mut processor, "malformed_timestamp.csv");
+        let dropped = processor.handle_invalid_timestamps(&mut file_data, "Timestamp").unwrap();
+        assert_eq!(dropped, 1);
+        assert_eq!(file_data.data.len(), 3);
+        assert!(file_data.data.iter().all(|row| row[0] != INVALID_DATE));
+    }
+
+    #[test]
+    fn rejects_malformed_timestamps_when_configured() {
+        let mut processor = FileProcessor::new(None);
+        processor
+            .apply_options(&ProcessingOptions {
+                invalid_timestamps: Some(InvalidTimestampPolicy::Error),
+                ..Default::default()
+            })
+            .unwrap();
+        let mut file_data = read_with_dates(&mut processor, "malformed_timestamp.csv");
+        let error = processor.handle_invalid_timestamps(&mut file_data, "Timestamp").unwrap_err();
+        assert!(error.to_string().contains("first at data row 3"));
+    }
+
+    #[test]
+    fn build_series_rejects_a_bad_timestamp() {
+        let processor = FileProcessor::new(None);
+        let file_data = FileData {
+            headers: vec!["Timestamp".to_string(), "Depth".to_string()],
+            data: vec![
+                vec!["2024-01-01 00:00:00".to_string(), "0.1".to_string()],
+                vec!["2024-01-01 00:15".to_string(), "0.2".to_string()]
+            ],
+        };
+        match processor.build_series(&file_data, "Timestamp") {
+            Err(FileProcessorError::ParseError(message)) => {
+                assert!(message.contains("at data row 2"));
+            }
+            other => panic!("expected a parse error, got {:?}", other.map(|s| s.len())),
         }
+    }
 
-        self.df = Some(filtered_df);
+    fn spaced(start: NaiveDateTime, minutes: &[i64]) -> Vec<NaiveDateTime> {
+        let mut timestamps = vec![start];
+        for m in minutes {
+            timestamps.push(*timestamps.last().unwrap() + Duration::minutes(*m));
+        }
+        timestamps
+    }
 
-        Ok(UpdatedTimestampData {
-            start_timestamp: start_time.to_string(),
-            end_timestamp: end_time.to_string(),
-            interval: self.interval.unwrap(),
-            row_count: self.df.as_ref().unwrap().height(),
-        })
+    #[test]
+    fn switch_to_a_multiple_of_the_interval_is_an_interval_change() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        let timestamps = spaced(start, &[5, 5, 5, 5, 5, 5, 15, 15, 15, 15]);
+        let changes = FileProcessor::find_interval_changes(&timestamps, Duration::minutes(5));
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].interval_seconds, 900);
+        assert_eq!(changes[0].start, "2024-01-01 00:30:00");
+        assert_eq!(changes[0].readings, 5);
+    }
+
+    #[test]
+    fn single_gap_is_not_an_interval_change() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        let timestamps = spaced(start, &[5, 5, 5, 30, 5, 5, 5]);
+        assert!(FileProcessor::find_interval_changes(&timestamps, Duration::minutes(5)).is_empty());
+    }
+
+    #[test]
+    fn interval_ties_prefer_a_common_interval_then_the_smaller() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        // 7 minutes (uncommon) and 10 minutes (common) twice each
+        let timestamps = spaced(start, &[7, 10, 7, 10]);
+        for _ in 0..10 {
+            assert_eq!(FileProcessor::mode_interval(&timestamps), Some(Duration::minutes(10)));
+        }
+        // Neither 7 nor 11 minutes is common, so the smaller wins
+        let timestamps = spaced(start, &[11, 7, 11, 7]);
+        for _ in 0..10 {
+            assert_eq!(FileProcessor::mode_interval(&timestamps), Some(Duration::minutes(7)));
+        }
+    }
+
+    #[test]
+    fn reads_a_gzipped_csv_like_the_plain_file() {
+        let mut processor = FileProcessor::new(None);
+        let plain = processor.read_file(&fixture("SITE1.csv")).unwrap();
+        let gzipped = processor.read_file(&fixture("SITE2.csv.gz")).unwrap();
+        assert_eq!(gzipped.headers, plain.headers);
+        assert_eq!(gzipped.data, plain.data);
+        assert_eq!(first_timestamp(&gzipped), "01/01/2024 00:00");
+    }
+
+    #[test]
+    fn units_row_is_merged_into_the_headers() {
+        let mut processor = FileProcessor::new(None);
+        let file_data = processor.read_file(&fixture("units_row.csv")).unwrap();
+        assert_eq!(file_data.headers, ["Timestamp", "Depth|m", "Velocity|m/s"]);
+        assert_eq!(file_data.data.len(), 3);
+    }
+
+    #[test]
+    fn units_row_is_found_above_decimal_comma_readings() {
+        let mut processor = FileProcessor::new(None);
+        let file_data = processor.read_file(&fixture("units_row_decimal_comma.txt")).unwrap();
+        assert_eq!(file_data.headers, ["Timestamp", "Depth|m", "Velocity|m/s"]);
+        assert_eq!(file_data.data.len(), 3);
+        assert_eq!(file_data.data[0][1..], ["0.10", "0.50"]);
+    }
+
+    #[test]
+    fn blank_first_reading_is_not_a_units_row() {
+        let mut processor = FileProcessor::new(None);
+        let file_data = processor.read_file(&fixture("blank_first_reading.csv")).unwrap();
+        assert_eq!(file_data.headers, ["Timestamp", "Depth", "Velocity"]);
+        assert_eq!(file_data.data.len(), 4);
+        assert_eq!(first_timestamp(&file_data), "01/01/2024 00:00");
+    }
+
+    fn readings_df(count: i64) -> DataFrame {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        let timestamps: Vec<NaiveDateTime> = (0..count)
+            .map(|i| start + Duration::minutes(15 * i))
+            .collect();
+        DataFrame::new(vec![Series::new("Timestamp".into(), timestamps)]).unwrap()
+    }
+
+    #[test]
+    fn single_reading_range_needs_the_interval_option() {
+        let processor = FileProcessor::new(None);
+        let error = processor.calculate_interval_from_df(&readings_df(1), "Timestamp").unwrap_err();
+        assert!(error.to_string().contains("single reading"), "{}", error);
+    }
+
+    #[test]
+    fn two_reading_range_uses_its_one_spacing() {
+        let processor = FileProcessor::new(None);
+        let interval = processor.calculate_interval_from_df(&readings_df(2), "Timestamp").unwrap();
+        assert_eq!(interval, Duration::minutes(15));
+    }
+
+    #[test]
+    fn gap_fill_only_estimates_hydraulic_columns() {
+        let mut processor = FileProcessor::new(None);
+        processor.set_gap_fill_strategy(GapFillStrategy::LinearInterpolate);
+        let processed = processor.process_file(&fixture("gap_mixed.csv")).unwrap();
+        let df = &processed.df;
+        assert_eq!(processed.gaps_filled, 1);
+
+        let depth = df.column("1_1|Depth|m").unwrap().f64().unwrap().get(2).unwrap();
+        assert!((depth - 0.3).abs() < 1e-9);
+        let rainfall = df.column("1_2|Rainfall|mm").unwrap().f64().unwrap().get(2);
+        assert!(rainfall.is_none_or(f64::is_nan));
+        assert_eq!(df.column("Code").unwrap().i64().unwrap().get(2), None);
+        // The estimate is still flagged without asking for the infilled column
+        let infilled: Vec<Option<bool>> = df
+            .column(INFILLED_COLUMN)
+            .unwrap()
+            .bool()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(infilled, [false, false, true, false, false].map(Some));
+    }
+
+    #[test]
+    fn status_codes_are_kept_beside_the_parsed_flags() {
+        let mut processor = FileProcessor::new(None);
+        let processed = processor.process_file(&fixture("status.csv")).unwrap();
+        let df = &processed.df;
+        assert_eq!(processed.quality_column.as_deref(), Some("Status_flag"));
+
+        let status: Vec<Option<i64>> = df
+            .column("Status")
+            .unwrap()
+            .i64()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(status, [0, 2, 17, 0].map(Some));
+        let flags: Vec<f64> = df
+            .column("Status_flag")
+            .unwrap()
+            .f64()
+            .unwrap()
+            .into_iter()
+            .map(Option::unwrap)
+            .collect();
+        assert_eq!(flags[..2], [0.0, 2.0]);
+        assert!(flags[2].is_nan());
+        assert_eq!(flags[3], 0.0);
+    }
+
+    #[test]
+    fn timezone_marks_the_timestamps_as_utc() {
+        let mut processor = FileProcessor::new(None);
+        let processed = processor.process_file(&fixture("SITE1.csv")).unwrap();
+        assert_eq!(processed.time_basis, NAIVE_TIME_BASIS);
+
+        processor.set_timezone("Europe/London").unwrap();
+        let processed = processor.process_file(&fixture("SITE1.csv")).unwrap();
+        assert_eq!(processed.time_basis, UTC_TIME_BASIS);
+    }
+
+    #[test]
+    fn thousands_separators_are_not_decimal_commas() {
+        let mut data = vec![
+            ["1,25", "1,234", "0,125", "-12,5", "1,2,3"].map(String::from).to_vec()
+        ];
+        FileProcessor::normalize_decimal_commas(&mut data);
+        assert_eq!(data[0], ["1.25", "1,234", "0.125", "-12.5", "1,2,3"]);
     }
 }
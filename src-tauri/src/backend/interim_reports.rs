@@ -1,5 +1,9 @@
 use crate::backend::backend::CommandHandler;
-use chrono::{Duration, NaiveDateTime};
+use crate::calculations::calculator::Calculator;
+use crate::calculations::calculator_factory::create_calculator;
+use crate::fdv::column_unit::is_depth_in_mm;
+use crate::fdv::rainfall_creator::RainfallUnit;
+use chrono::{Datelike, Duration, Months, NaiveDate, NaiveDateTime};
 use polars::prelude::*;
 use std::collections::HashMap;
 use std::error::Error;
@@ -10,6 +14,8 @@ pub enum InterimReportError {
     ColumnExtractionError(String),
     DataFrameError(String),
     InvalidMonitorType(String),
+    InvalidReportPeriod(String),
+    InvalidOption(String),
 }
 
 impl fmt::Display for InterimReportError {
@@ -18,6 +24,8 @@ impl fmt::Display for InterimReportError {
             InterimReportError::ColumnExtractionError(msg) => write!(f, "Column extraction error: {}", msg),
             InterimReportError::DataFrameError(msg) => write!(f, "DataFrame error: {}", msg),
             InterimReportError::InvalidMonitorType(msg) => write!(f, "Invalid monitor type: {}", msg),
+            InterimReportError::InvalidReportPeriod(msg) => write!(f, "Invalid report period: {}", msg),
+            InterimReportError::InvalidOption(msg) => write!(f, "Invalid option: {}", msg),
         }
     }
 }
@@ -32,7 +40,7 @@ pub enum MonitorType {
 }
 
 impl MonitorType {
-    fn from_str(s: &str) -> Result<Self, InterimReportError> {
+    pub fn from_str(s: &str) -> Result<Self, InterimReportError> {
         match s.to_lowercase().as_str() {
             "flow" => Ok(MonitorType::Flow),
             "depth" => Ok(MonitorType::Depth),
@@ -40,8 +48,234 @@ impl MonitorType {
             _ => Err(InterimReportError::InvalidMonitorType(format!("'{}' is not a valid monitor type", s))),
         }
     }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MonitorType::Flow => "Flow",
+            MonitorType::Depth => "Depth",
+            MonitorType::Rainfall => "Rainfall",
+        }
+    }
+
+    /// Names of the conversion commands that make sense for this monitor type.
+    pub fn available_conversions(&self) -> Vec<&'static str> {
+        match self {
+            MonitorType::Flow => vec!["create_fdv_flow", "generate_interim_reports"],
+            MonitorType::Depth => {
+                vec!["create_fdv_flow_constant_velocity", "generate_interim_reports"]
+            }
+            MonitorType::Rainfall => {
+                vec!["create_rainfall", "generate_interim_reports", "generate_rainfall_totals"]
+            }
+        }
+    }
+}
+
+/// Longest custom report period accepted, about ten years.
+pub const MAX_PERIOD_DAYS: i64 = 3660;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReportPeriod {
+    #[default]
+    Weekly,
+    Monthly,
+    Yearly,
+    Days(i64),
+}
+
+impl ReportPeriod {
+    pub fn from_str(s: &str, days: Option<i64>) -> Result<Self, InterimReportError> {
+        match s.to_lowercase().as_str() {
+            "weekly" => Ok(ReportPeriod::Weekly),
+            "monthly" => Ok(ReportPeriod::Monthly),
+            "yearly" | "annual" => Ok(ReportPeriod::Yearly),
+            "custom" | "days" => match days {
+                Some(d) if (1..=MAX_PERIOD_DAYS).contains(&d) => Ok(ReportPeriod::Days(d)),
+                _ => Err(InterimReportError::InvalidReportPeriod(format!(
+                    "a custom period must be between 1 and {} days",
+                    MAX_PERIOD_DAYS
+                ))),
+            },
+            _ => Err(InterimReportError::InvalidReportPeriod(format!("'{}' is not a valid report period", s))),
+        }
+    }
+
+    fn label(&self, index: usize) -> String {
+        match self {
+            ReportPeriod::Weekly => format!("Interim {}", index),
+            ReportPeriod::Monthly => format!("Month {}", index),
+            ReportPeriod::Yearly => format!("Year {}", index),
+            ReportPeriod::Days(days) => format!("Period {} ({} days)", index, days),
+        }
+    }
+
+    /// Short tag identifying the period starting at `period_start`, used in file names.
+    pub fn file_suffix(&self, period_start: NaiveDateTime) -> String {
+        match self {
+            ReportPeriod::Monthly => period_start.format("%Y-%m").to_string(),
+            ReportPeriod::Yearly => period_start.format("%Y").to_string(),
+            _ => period_start.format("%Y%m%d").to_string(),
+        }
+    }
+
+    pub fn period_end(&self, period_start: NaiveDateTime) -> NaiveDateTime {
+        let start_date = period_start.date();
+        let end_date = match self {
+            ReportPeriod::Weekly => start_date + Duration::days(6),
+            ReportPeriod::Monthly => {
+                start_date
+                    .with_day(1)
+                    .and_then(|first| first.checked_add_months(Months::new(1)))
+                    .map(|next_month| next_month - Duration::days(1))
+                    .unwrap_or(start_date)
+            }
+            ReportPeriod::Yearly => {
+                NaiveDate::from_ymd_opt(start_date.year(), 12, 31).unwrap_or(start_date)
+            }
+            ReportPeriod::Days(days) => Duration::try_days(days - 1)
+                .and_then(|length| start_date.checked_add_signed(length))
+                .unwrap_or(NaiveDate::MAX),
+        };
+        end_date.and_hms_opt(23, 59, 59).unwrap()
+    }
+}
+
+/// How days with fewer readings than the interval implies are treated in the daily summary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DailyCompleteness {
+    #[default]
+    Include,
+    Flag,
+    Drop,
+}
+
+impl DailyCompleteness {
+    pub fn from_str(s: &str) -> Result<Self, InterimReportError> {
+        match s.to_lowercase().as_str() {
+            "include" => Ok(DailyCompleteness::Include),
+            "flag" => Ok(DailyCompleteness::Flag),
+            "drop" => Ok(DailyCompleteness::Drop),
+            _ => Err(InterimReportError::InvalidOption(format!(
+                "'{}' is not a valid incomplete day option",
+                s
+            ))),
+        }
+    }
+}
+
+/// A per-day aggregation of the monitored value in the daily summary.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AggOp {
+    Mean,
+    Max,
+    Min,
+    Median,
+    Sum,
+    /// Percentile between 0 and 100, e.g. `Percentile(95.0)`.
+    Percentile(f64),
+}
+
+impl AggOp {
+    /// Parses `mean`, `max`, `min`, `median`, `sum` or a percentile as `p95`.
+    pub fn from_str(s: &str) -> Result<Self, InterimReportError> {
+        let s = s.trim().to_lowercase();
+        match s.as_str() {
+            "mean" | "average" => Ok(AggOp::Mean),
+            "max" => Ok(AggOp::Max),
+            "min" => Ok(AggOp::Min),
+            "median" => Ok(AggOp::Median),
+            "sum" | "total" => Ok(AggOp::Sum),
+            _ => s
+                .strip_prefix('p')
+                .and_then(|p| p.parse::<f64>().ok())
+                .filter(|p| (0.0..=100.0).contains(p))
+                .map(AggOp::Percentile)
+                .ok_or_else(|| {
+                    InterimReportError::InvalidOption(format!(
+                        "'{}' is not a valid daily aggregation",
+                        s
+                    ))
+                }),
+        }
+    }
+
+    /// Prefix of the output column, e.g. "Average" in "Average Level(m)".
+    fn label(&self) -> String {
+        match self {
+            AggOp::Mean => "Average".to_string(),
+            AggOp::Max => "Max".to_string(),
+            AggOp::Min => "Min".to_string(),
+            AggOp::Median => "Median".to_string(),
+            AggOp::Sum => "Total".to_string(),
+            AggOp::Percentile(p) => format!("P{}", p),
+        }
+    }
+
+    /// Order statistics skip NaN gap rows, which would otherwise sort as the largest values.
+    fn expr(&self, value: Expr) -> Expr {
+        match self {
+            AggOp::Mean => value.mean(),
+            AggOp::Max => value.max(),
+            AggOp::Min => value.min(),
+            AggOp::Sum => value.sum(),
+            AggOp::Median => value.clone().filter(value.is_not_nan()).median(),
+            AggOp::Percentile(p) => value
+                .clone()
+                .filter(value.is_not_nan())
+                .quantile(lit(p / 100.0), QuantileInterpolOptions::Linear),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InterimReportOptions {
+    pub period: ReportPeriod,
+    pub daily_completeness: DailyCompleteness,
+    pub include_empty_periods: bool,
+    pub pipe_shape: Option<String>,
+    pub pipe_size: Option<String>,
+    pub manning_n: Option<f64>,
+    pub pipe_gradient: Option<f64>,
+    /// Decimal places for summary values; `None` uses `DEFAULT_REPORT_DECIMALS`.
+    pub decimals: Option<u32>,
+    /// Appends the "Grand Total" row to the summaries.
+    pub include_grand_total: bool,
+    /// Per-day aggregations in the daily summary; `None` uses the monitor type's defaults.
+    pub daily_aggregations: Option<Vec<AggOp>>,
+}
+
+impl Default for InterimReportOptions {
+    fn default() -> Self {
+        InterimReportOptions {
+            period: ReportPeriod::default(),
+            daily_completeness: DailyCompleteness::default(),
+            include_empty_periods: false,
+            pipe_shape: None,
+            pipe_size: None,
+            manning_n: None,
+            pipe_gradient: None,
+            decimals: None,
+            include_grand_total: true,
+            daily_aggregations: None,
+        }
+    }
+}
+
+/// Pipe geometry and optional Manning parameters used for the flow QA columns.
+struct HydraulicCheck {
+    calculator: Box<dyn Calculator>,
+    manning_n: Option<f64>,
+    gradient: Option<f64>,
 }
 
+const NO_DATA_KEY: &str = "No Data";
+
+/// Decimal places kept in summary values unless the options say otherwise.
+pub const DEFAULT_REPORT_DECIMALS: u32 = 2;
+
+/// Fewest decimal places kept in metre columns, so levels keep millimetre precision.
+const METRE_REPORT_DECIMALS: u32 = 3;
+
 pub struct InterimReportGenerator {
     monitor_type: MonitorType,
     df: DataFrame,
@@ -50,6 +284,15 @@ pub struct InterimReportGenerator {
     flow_column: String,
     depth_column: String,
     rainfall_column: String,
+    rainfall_unit: RainfallUnit,
+    velocity_column: Option<String>,
+    daily_completeness: DailyCompleteness,
+    include_empty_periods: bool,
+    include_grand_total: bool,
+    daily_aggregations: Option<Vec<AggOp>>,
+    hydraulic_check: Option<HydraulicCheck>,
+    signed_flow: bool,
+    decimals: u32,
 }
 
 impl<'a> InterimReportGenerator {
@@ -102,6 +345,11 @@ impl<'a> InterimReportGenerator {
             _ => None,
         };
 
+        let velocity_column = match monitor_type {
+            MonitorType::Flow => extract_column_name("velocity").ok().flatten(),
+            _ => None,
+        };
+
         Ok(Self {
             monitor_type,
             df,
@@ -110,9 +358,178 @@ impl<'a> InterimReportGenerator {
             flow_column: flow_column.unwrap_or_default(),
             depth_column: depth_column.unwrap_or_default(),
             rainfall_column: rainfall_column.unwrap_or_default(),
+            rainfall_unit: backend.rainfall_unit,
+            velocity_column,
+            daily_completeness: DailyCompleteness::default(),
+            include_empty_periods: false,
+            include_grand_total: true,
+            daily_aggregations: None,
+            hydraulic_check: None,
+            signed_flow: backend.signed_flow,
+            decimals: DEFAULT_REPORT_DECIMALS,
         })
     }
 
+    /// Emits a "No Data" row for periods without readings instead of skipping them, so the
+    /// interim numbering follows calendar coverage.
+    pub fn set_include_empty_periods(&mut self, include_empty_periods: bool) {
+        self.include_empty_periods = include_empty_periods;
+    }
+
+    /// Leaves the "Grand Total" row off the summaries for templates that don't want it.
+    pub fn set_include_grand_total(&mut self, include_grand_total: bool) {
+        self.include_grand_total = include_grand_total;
+    }
+
+    /// Replaces the default daily summary aggregations of the monitored value.
+    pub fn set_daily_aggregations(&mut self, daily_aggregations: Vec<AggOp>) {
+        self.daily_aggregations = Some(daily_aggregations);
+    }
+
+    /// Daily summary aggregations of `value`, named `<op> <measure>`, e.g. "Max Level(m)".
+    /// Defaults to mean/max/min, or sum/max/min for rainfall.
+    fn daily_value_aggregations(&self, value: Expr, measure: &str) -> Vec<Expr> {
+        let default_ops = match self.monitor_type {
+            MonitorType::Rainfall => vec![AggOp::Sum, AggOp::Max, AggOp::Min],
+            _ => vec![AggOp::Mean, AggOp::Max, AggOp::Min],
+        };
+        self.daily_aggregations
+            .as_ref()
+            .unwrap_or(&default_ops)
+            .iter()
+            .map(|op| {
+                op.expr(value.clone())
+                    .alias(format!("{} {}", op.label(), measure))
+            })
+            .collect()
+    }
+
+    /// The column the monitor type reports on.
+    fn value_column(&self) -> &str {
+        match self.monitor_type {
+            MonitorType::Flow => &self.flow_column,
+            MonitorType::Depth => &self.depth_column,
+            MonitorType::Rainfall => &self.rainfall_column,
+        }
+    }
+
+    /// Gap-filled rows are present but hold NaN, so a period only has readings if the
+    /// monitored column has at least one real value.
+    fn has_readings(&self, period_data: &DataFrame) -> Result<bool, Box<dyn Error>> {
+        let values = period_data.column(self.value_column())?.cast(&DataType::Float64)?;
+        let has_readings = values.f64()?.into_iter().any(|v| v.is_some_and(|v| !v.is_nan()));
+        Ok(has_readings)
+    }
+
+    /// With signed flow, "Total Flow(m3)" is the net volume and gross volume is added.
+    fn summary_value_columns(&self) -> Vec<&'static str> {
+        match self.monitor_type {
+            MonitorType::Flow if self.signed_flow => {
+                vec!["Total Flow(m3)", "Gross Flow(m3)", "Max Flow(l/s)", "Min Flow(l/s)"]
+            }
+            MonitorType::Flow => vec!["Total Flow(m3)", "Max Flow(l/s)", "Min Flow(l/s)"],
+            MonitorType::Depth => vec!["Average Level(m)", "Max Level(m)", "Min Level(m)"],
+            MonitorType::Rainfall => {
+                vec!["Total Rainfall(mm)", "Max Rainfall(mm)", "Min Rainfall(mm)"]
+            }
+        }
+    }
+
+    pub fn set_decimals(&mut self, decimals: u32) {
+        self.decimals = decimals;
+    }
+
+    /// Decimal places for a summary column, by the unit in its name: metre columns keep
+    /// at least `METRE_REPORT_DECIMALS`.
+    fn decimals_for(&self, column: &str) -> u32 {
+        if column.ends_with("(m)") {
+            self.decimals.max(METRE_REPORT_DECIMALS)
+        } else {
+            self.decimals
+        }
+    }
+
+    fn round_value(&self, column: &str, value: f64) -> f64 {
+        let factor = (10f64).powi(self.decimals_for(column) as i32);
+        (value * factor).round() / factor
+    }
+
+    fn insert_value(&self, summary: &mut HashMap<String, String>, column: &str, value: f64) {
+        summary.insert(column.to_string(), self.round_value(column, value).to_string());
+    }
+
+    /// Rounds every float column, so summary tables don't carry long unrounded decimals
+    /// into the workbook or JSON preview.
+    fn round_float_columns(&self, df: DataFrame) -> PolarsResult<DataFrame> {
+        let rounded: Vec<Expr> = df
+            .get_columns()
+            .iter()
+            .filter(|s| s.dtype() == &DataType::Float64)
+            .map(|s| {
+                let name = s.name().as_str();
+                col(name).round(self.decimals_for(name))
+            })
+            .collect();
+        df.lazy().with_columns(rounded).collect()
+    }
+
+    pub fn set_daily_completeness(&mut self, daily_completeness: DailyCompleteness) {
+        self.daily_completeness = daily_completeness;
+    }
+
+    /// Enables the hydraulic radius and Manning velocity columns in the flow daily summary.
+    /// The Manning velocity is only added when both roughness and gradient are given.
+    pub fn set_hydraulic_check(
+        &mut self,
+        pipe_shape: &str,
+        pipe_size: &str,
+        manning_n: Option<f64>,
+        gradient: Option<f64>
+    ) -> Result<(), InterimReportError> {
+        let (calculator, _) = create_calculator(pipe_shape, pipe_size).map_err(|e| {
+            InterimReportError::InvalidOption(format!("Invalid pipe parameters: {}", e))
+        })?;
+        if manning_n.is_some_and(|n| n <= 0.0) || gradient.is_some_and(|s| s <= 0.0) {
+            return Err(
+                InterimReportError::InvalidOption(
+                    "Manning's n and pipe gradient must be greater than zero".to_string()
+                )
+            );
+        }
+        self.hydraulic_check = Some(HydraulicCheck { calculator, manning_n, gradient });
+        Ok(())
+    }
+
+    /// Per-reading hydraulic radius (A/P) and Manning velocity (R^(2/3)·S^(1/2)/n), with
+    /// nulls where the depth is missing, zero or the shape has no perimeter.
+    fn hydraulic_series(&self, check: &HydraulicCheck) -> Result<Vec<Series>, Box<dyn Error>> {
+        let depths = self.df.column(&self.depth_column)?.cast(&DataType::Float64)?;
+        let in_mm = is_depth_in_mm(&self.depth_column);
+
+        let radii: Vec<Option<f64>> = depths
+            .f64()?
+            .into_iter()
+            .map(|depth| {
+                let depth = depth.filter(|d| !d.is_nan())?;
+                let depth = if in_mm { depth / 1000.0 } else { depth };
+                let perimeter = check.calculator.wetted_perimeter(depth)?;
+                let area = check.calculator.wetted_area(depth).ok()?;
+                (perimeter > 0.0 && area > 0.0).then(|| area / perimeter)
+            })
+            .collect();
+
+        let mut series = Vec::new();
+        if let (Some(n), Some(gradient)) = (check.manning_n, check.gradient) {
+            let manning: Vec<Option<f64>> = radii
+                .iter()
+                .map(|r| r.map(|r| (r.powf(2.0 / 3.0) * gradient.sqrt()) / n))
+                .collect();
+            series.push(Series::new("_manning_velocity".into(), manning));
+        }
+        series.push(Series::new("_hydraulic_radius".into(), radii));
+        Ok(series)
+    }
+
     fn calculate_values(&mut self) -> Result<&DataFrame, Box<dyn Error>> {
         match self.monitor_type {
             MonitorType::Flow => {
@@ -149,27 +566,39 @@ impl<'a> InterimReportGenerator {
                 let max_flow: f64 = weekly_data.column(&self.flow_column)?.max()?.unwrap();
                 let min_flow: f64 = weekly_data.column(&self.flow_column)?.min()?.unwrap();
 
-                summary.insert("Total Flow(m3)".to_string(), total_flow.to_string());
-                summary.insert("Max Flow(l/s)".to_string(), max_flow.to_string());
-                summary.insert("Min Flow(l/s)".to_string(), min_flow.to_string());
+                self.insert_value(&mut summary, "Total Flow(m3)", total_flow);
+                if self.signed_flow {
+                    let gross_flow: f64 = weekly_data
+                        .column("m3")?
+                        .f64()?
+                        .into_iter()
+                        .flatten()
+                        .map(f64::abs)
+                        .sum();
+                    self.insert_value(&mut summary, "Gross Flow(m3)", gross_flow);
+                }
+                self.insert_value(&mut summary, "Max Flow(l/s)", max_flow);
+                self.insert_value(&mut summary, "Min Flow(l/s)", min_flow);
             }
             MonitorType::Depth => {
                 let avg_level: f64 = weekly_data.column(&self.depth_column)?.mean().unwrap();
                 let max_level: f64 = weekly_data.column(&self.depth_column)?.max()?.unwrap();
                 let min_level: f64 = weekly_data.column(&self.depth_column)?.min()?.unwrap();
 
-                summary.insert("Average Level(m)".to_string(), avg_level.to_string());
-                summary.insert("Max Level(m)".to_string(), max_level.to_string());
-                summary.insert("Min Level(m)".to_string(), min_level.to_string());
+                self.insert_value(&mut summary, "Average Level(m)", avg_level);
+                self.insert_value(&mut summary, "Max Level(m)", max_level);
+                self.insert_value(&mut summary, "Min Level(m)", min_level);
             }
             MonitorType::Rainfall => {
-                let total_rainfall: f64 = weekly_data.column(&self.rainfall_column)?.sum()?;
-                let max_rainfall: f64 = weekly_data.column(&self.rainfall_column)?.max()?.unwrap();
-                let min_rainfall: f64 = weekly_data.column(&self.rainfall_column)?.min()?.unwrap();
+                let depth_factor = self.rainfall_depth_factor();
+                let rainfall = weekly_data.column(&self.rainfall_column)?;
+                let total_rainfall: f64 = rainfall.sum::<f64>()? * depth_factor;
+                let max_rainfall: f64 = rainfall.max::<f64>()?.unwrap() * depth_factor;
+                let min_rainfall: f64 = rainfall.min::<f64>()?.unwrap() * depth_factor;
 
-                summary.insert("Total Rainfall(mm)".to_string(), total_rainfall.to_string());
-                summary.insert("Max Rainfall(mm)".to_string(), max_rainfall.to_string());
-                summary.insert("Min Rainfall(mm)".to_string(), min_rainfall.to_string());
+                self.insert_value(&mut summary, "Total Rainfall(mm)", total_rainfall);
+                self.insert_value(&mut summary, "Max Rainfall(mm)", max_rainfall);
+                self.insert_value(&mut summary, "Min Rainfall(mm)", min_rainfall);
             }
         }
 
@@ -180,6 +609,7 @@ impl<'a> InterimReportGenerator {
         &self,
         start_date: Option<String>,
         end_date: Option<String>,
+        period: ReportPeriod,
     ) -> Result<DataFrame, Box<dyn Error>> {
         let time_column = &self.time_column;
         let sorted_df = self
@@ -197,9 +627,7 @@ impl<'a> InterimReportGenerator {
         let mut current_date = start_date;
 
         while current_date <= end_date {
-            let week_end = (current_date.date() + Duration::days(6))
-                .and_hms_opt(23, 59, 59)
-                .unwrap();
+            let week_end = period.period_end(current_date);
 
             let weekly_data = sorted_df
                 .clone()
@@ -211,8 +639,23 @@ impl<'a> InterimReportGenerator {
                 )
                 .collect()?;
 
-            if !weekly_data.is_empty() {
+            let summary = if self.include_empty_periods && !self.has_readings(&weekly_data)? {
+                let mut summary: HashMap<String, String> = self
+                    .summary_value_columns()
+                    .into_iter()
+                    .map(|column| (column.to_string(), String::new()))
+                    .collect();
+                summary.insert(NO_DATA_KEY.to_string(), "No Data".to_string());
+                Some(summary)
+            } else if !weekly_data.is_empty() {
                 let mut summary = self.generate_weekly_summary(&weekly_data)?;
+                summary.insert(NO_DATA_KEY.to_string(), String::new());
+                Some(summary)
+            } else {
+                None
+            };
+
+            if let Some(mut summary) = summary {
                 summary.insert(
                     "Start Date".to_string(),
                     current_date.date().format("%Y-%m-%d").to_string(),
@@ -227,7 +670,7 @@ impl<'a> InterimReportGenerator {
             current_date = week_end + Duration::seconds(1);
         }
 
-        self.create_summary_dataframe(weekly_summaries)
+        self.create_summary_dataframe(weekly_summaries, period)
     }
 
     fn get_start_date(
@@ -275,6 +718,7 @@ impl<'a> InterimReportGenerator {
     fn create_summary_dataframe(
         &self,
         weekly_summaries: Vec<HashMap<String, String>>,
+        period: ReportPeriod,
     ) -> Result<DataFrame, Box<dyn Error>> {
         let mut series_vec: Vec<Series> = Vec::new();
 
@@ -290,8 +734,19 @@ impl<'a> InterimReportGenerator {
 
         let mut summary_df = DataFrame::new(series_vec)?;
         let n_rows = summary_df.height();
-        let interim_period: Vec<String> =
-            (0..n_rows).map(|x| format!("Interim {}", x + 1)).collect();
+        let no_data: Vec<bool> = weekly_summaries
+            .iter()
+            .map(|summary| summary.get(NO_DATA_KEY).is_some_and(|v| !v.is_empty()))
+            .collect();
+        let interim_period: Vec<String> = (0..n_rows)
+            .map(|x| {
+                if no_data.get(x).copied().unwrap_or(false) {
+                    format!("{} (No Data)", period.label(x + 1))
+                } else {
+                    period.label(x + 1)
+                }
+            })
+            .collect();
         let interim_series = Series::new("Interim Period".into(), interim_period);
         summary_df.with_column(interim_series)?;
 
@@ -305,37 +760,9 @@ impl<'a> InterimReportGenerator {
             )
             .collect()?;
 
-        let columns = match self.monitor_type {
-            MonitorType::Flow => vec![
-                "Interim Period",
-                "Date Range",
-                "Total Flow(m3)",
-                "Max Flow(l/s)",
-                "Min Flow(l/s)",
-            ],
-            MonitorType::Depth => vec![
-                "Interim Period",
-                "Date Range",
-                "Average Level(m)",
-                "Max Level(m)",
-                "Min Level(m)",
-            ],
-            MonitorType::Rainfall => vec![
-                "Interim Period",
-                "Date Range",
-                "Total Rainfall(mm)",
-                "Max Rainfall(mm)",
-                "Min Rainfall(mm)",
-            ],
-        };
-
-        let numeric_columns = match self.monitor_type {
-            MonitorType::Flow => vec!["Total Flow(m3)", "Max Flow(l/s)", "Min Flow(l/s)"],
-            MonitorType::Depth => vec!["Average Level(m)", "Max Level(m)", "Min Level(m)"],
-            MonitorType::Rainfall => {
-                vec!["Total Rainfall(mm)", "Max Rainfall(mm)", "Min Rainfall(mm)"]
-            }
-        };
+        let numeric_columns = self.summary_value_columns();
+        let mut columns = vec!["Interim Period", "Date Range"];
+        columns.extend(numeric_columns.iter().copied());
 
         let final_df = summary_df
             .select(columns)?
@@ -352,14 +779,29 @@ impl<'a> InterimReportGenerator {
     }
 
     pub fn calculate_daily_summary(&self) -> Result<DataFrame, Box<dyn Error>> {
-        let time_column = &self.time_column;
-
         let daily_summary = match self.monitor_type {
-            MonitorType::Flow => self.calculate_flow_summary(time_column)?,
-            MonitorType::Depth => self.calculate_depth_summary(time_column)?,
-            MonitorType::Rainfall => self.calculate_rainfall_summary(time_column)?,
+            MonitorType::Flow => {
+                let (df, aggregations, derived) = self.flow_summary_parts()?;
+                self.calculate_summary(df, aggregations, derived)?
+            }
+            MonitorType::Depth => self.calculate_summary(
+                self.df.clone(),
+                self.daily_value_aggregations(col(&self.depth_column), "Level(m)"),
+                Vec::new(),
+            )?,
+            MonitorType::Rainfall => {
+                let rainfall_depth =
+                    col(&self.rainfall_column) * lit(self.rainfall_depth_factor());
+                self.calculate_summary(
+                    self.df.clone(),
+                    self.daily_value_aggregations(rainfall_depth, "Rainfall(mm)"),
+                    Vec::new(),
+                )?
+            }
         };
 
+        let daily_summary = self.apply_daily_completeness(daily_summary)?;
+
         let formatted_daily_summary = daily_summary
             .lazy()
             .with_column(col("Date").dt().strftime("%d/%m/%Y"))
@@ -368,39 +810,69 @@ impl<'a> InterimReportGenerator {
         Ok(formatted_daily_summary)
     }
 
-    fn calculate_flow_summary(&self, time_column: &str) -> Result<DataFrame, Box<dyn Error>> {
-        let flow_column = &self.flow_column;
-        self.df
+    /// Flags or drops days with fewer readings than a full day at the current interval,
+    /// typically the partial first and last days of the record. Gap-filled rows hold no
+    /// reading, so only real values of the monitored column are counted.
+    fn apply_daily_completeness(&self, daily_summary: DataFrame) -> Result<DataFrame, Box<dyn Error>> {
+        if self.daily_completeness == DailyCompleteness::Include {
+            return Ok(daily_summary);
+        }
+
+        let interval_seconds = self.interval.num_seconds();
+        if interval_seconds <= 0 {
+            return Ok(daily_summary);
+        }
+        let expected_readings = (86400 / interval_seconds) as u32;
+
+        let readings_per_day = self
+            .df
             .clone()
             .lazy()
-            .with_column(col(time_column).dt().date().alias("Date"))
+            .with_column(col(&self.time_column).dt().date().alias("Date"))
             .group_by([col("Date")])
-            .agg([
-                col(flow_column).mean().alias("Average Flow(l/s)"),
-                col(flow_column).max().alias("Max Flow(l/s)"),
-                col(flow_column).min().alias("Min Flow(l/s)"),
-                col("m3").sum().alias("Flow (m3)"),
-            ])
-            .sort(
-                ["Date"],
-                SortMultipleOptions::new().with_order_descending(false),
-            )
-            .collect()
-            .map_err(|e| Box::new(e) as Box<dyn Error>)
+            .agg([col(self.value_column())
+                .cast(DataType::Float64)
+                .drop_nulls()
+                .is_not_nan()
+                .sum()
+                .alias("Readings")]);
+
+        let with_readings = daily_summary.lazy().join(
+            readings_per_day,
+            [col("Date")],
+            [col("Date")],
+            JoinArgs::new(JoinType::Left),
+        );
+
+        let result = match self.daily_completeness {
+            DailyCompleteness::Flag => with_readings
+                .with_column(
+                    col("Readings")
+                        .lt(lit(expected_readings))
+                        .alias("Incomplete"),
+                )
+                .drop(["Readings"]),
+            _ => with_readings
+                .filter(col("Readings").gt_eq(lit(expected_readings)))
+                .drop(["Readings"]),
+        };
+
+        Ok(result.collect()?)
     }
 
-    fn calculate_depth_summary(&self, time_column: &str) -> Result<DataFrame, Box<dyn Error>> {
-        let depth_column = &self.depth_column;
-        self.df
-            .clone()
-            .lazy()
-            .with_column(col(time_column).dt().date().alias("Date"))
+    /// Groups `df` by day and applies `aggregations`, then adds `derived` columns computed
+    /// from the aggregated ones. Every monitor type's daily summary goes through here.
+    fn calculate_summary(
+        &self,
+        df: DataFrame,
+        aggregations: Vec<Expr>,
+        derived: Vec<Expr>,
+    ) -> Result<DataFrame, Box<dyn Error>> {
+        df.lazy()
+            .with_column(col(&self.time_column).dt().date().alias("Date"))
             .group_by([col("Date")])
-            .agg([
-                col(depth_column).mean().alias("Average Level(m)"),
-                col(depth_column).max().alias("Max Level(m)"),
-                col(depth_column).min().alias("Min Level(m)"),
-            ])
+            .agg(aggregations)
+            .with_columns(derived)
             .sort(
                 ["Date"],
                 SortMultipleOptions::new().with_order_descending(false),
@@ -409,37 +881,78 @@ impl<'a> InterimReportGenerator {
             .map_err(|e| Box::new(e) as Box<dyn Error>)
     }
 
-    fn calculate_rainfall_summary(&self, time_column: &str) -> Result<DataFrame, Box<dyn Error>> {
-        let rainfall_column = &self.rainfall_column;
-        self.df
-            .clone()
-            .lazy()
-            .with_column(col(time_column).dt().date().alias("Date"))
-            .group_by([col("Date")])
-            .agg([
-                col(rainfall_column).sum().alias("Total Rainfall(mm)"),
-                col(rainfall_column).max().alias("Max Rainfall(mm)"),
-                col(rainfall_column).min().alias("Min Rainfall(mm)"),
-            ])
-            .sort(
-                ["Date"],
-                SortMultipleOptions::new().with_order_descending(false),
-            )
-            .collect()
-            .map_err(|e| Box::new(e) as Box<dyn Error>)
+    /// Flow data with the hydraulic QA columns added, its daily aggregations (value, volume
+    /// and QA) and the velocity ratio derived from them.
+    fn flow_summary_parts(&self) -> Result<(DataFrame, Vec<Expr>, Vec<Expr>), Box<dyn Error>> {
+        let flow_column = &self.flow_column;
+        let mut df = self.df.clone();
+        let mut aggregations = self.daily_value_aggregations(col(flow_column), "Flow(l/s)");
+        aggregations.push(col("m3").sum().alias("Flow (m3)"));
+        if self.signed_flow {
+            let magnitude = when(col("m3").lt(lit(0.0)))
+                .then(lit(0.0) - col("m3"))
+                .otherwise(col("m3"));
+            aggregations.push(magnitude.sum().alias("Gross Flow(m3)"));
+        }
+        let mut derived = Vec::new();
+
+        // Hydraulic QA columns, only when the pipe shape is known
+        if let Some(check) = &self.hydraulic_check {
+            for series in self.hydraulic_series(check)? {
+                df.with_column(series)?;
+            }
+            aggregations.push(col("_hydraulic_radius").mean().alias("Hydraulic Radius(m)"));
+
+            if let Some(velocity_column) = &self.velocity_column {
+                let velocity = col(velocity_column);
+                aggregations.push(
+                    velocity
+                        .clone()
+                        .filter(velocity.is_not_nan())
+                        .mean()
+                        .alias("Measured Velocity(m/s)")
+                );
+            }
+            if df.get_column_names().iter().any(|c| c.as_str() == "_manning_velocity") {
+                aggregations.push(col("_manning_velocity").mean().alias("Manning Velocity(m/s)"));
+                if self.velocity_column.is_some() {
+                    derived.push(
+                        (col("Measured Velocity(m/s)") / col("Manning Velocity(m/s)")).alias(
+                            "Velocity Ratio"
+                        )
+                    );
+                }
+            }
+        }
+
+        Ok((df, aggregations, derived))
     }
 
-    pub fn generate_report(&mut self) -> Result<(DataFrame, DataFrame, DataFrame), Box<dyn Error>> {
+    /// Multiplier converting a rainfall reading into mm fallen during its interval.
+    fn rainfall_depth_factor(&self) -> f64 {
+        self.rainfall_unit.depth_factor(self.interval.num_seconds() as f64 / 60.0)
+    }
+
+    pub fn generate_report(
+        &mut self,
+        period: ReportPeriod,
+    ) -> Result<(DataFrame, DataFrame, DataFrame), Box<dyn Error>> {
         self.calculate_values()?;
-        let summaries_df = self.generate_summaries(None, None)?;
+        let summaries_df = self.generate_summaries(None, None, period)?;
         let daily_summary = self.calculate_daily_summary()?;
 
-        let grand_total_row = self.calculate_grand_total(&summaries_df)?;
-
-        let summaries_with_total =
-            self.add_grand_total_to_summaries(summaries_df, grand_total_row)?;
+        let summaries_df = if self.include_grand_total {
+            let grand_total_row = self.calculate_grand_total(&summaries_df)?;
+            self.add_grand_total_to_summaries(summaries_df, grand_total_row)?
+        } else {
+            summaries_df
+        };
 
-        Ok((summaries_with_total, self.df.clone(), daily_summary))
+        Ok((
+            self.round_float_columns(summaries_df)?,
+            self.df.clone(),
+            self.round_float_columns(daily_summary)?,
+        ))
     }
 
     fn calculate_grand_total(&self, summaries_df: &DataFrame) -> Result<DataFrame, Box<dyn Error>> {
@@ -454,6 +967,12 @@ impl<'a> InterimReportGenerator {
                     "Total Flow(m3)".into(),
                     &[summaries_df.column("Total Flow(m3)")?.sum::<f64>()?],
                 ));
+                if self.signed_flow {
+                    grand_total_series.push(Series::new(
+                        "Gross Flow(m3)".into(),
+                        &[summaries_df.column("Gross Flow(m3)")?.sum::<f64>()?],
+                    ));
+                }
                 grand_total_series.push(Series::new(
                     "Max Flow(l/s)".into(),
                     &[summaries_df.column("Max Flow(l/s)")?.max::<f64>()?],
@@ -516,16 +1035,13 @@ impl<'a> InterimReportGenerator {
         let time_col = &self.time_column;
         let rainfall_col = &self.rainfall_column;
 
-        // Calculate the number of readings per hour based on the interval
-        let interval_seconds = self.interval.num_seconds();
-        let readings_per_hour = if interval_seconds > 0 {
-            3600 / interval_seconds
-        } else {
+        if self.interval.num_seconds() <= 0 {
             return Err(Box::new(std::io::Error::new(
                 std::io::ErrorKind::InvalidInput,
-                "Interval is invalid or too large, causing division by zero",
+                "Interval must be greater than zero to total rainfall",
             )));
-        };
+        }
+        let depth_factor = self.rainfall_depth_factor();
 
         // Daily totals
         let daily_totals = self
@@ -534,7 +1050,7 @@ impl<'a> InterimReportGenerator {
             .lazy()
             .group_by([col(time_col).dt().date().alias("Date")])
             .agg([
-                (col(rainfall_col).sum().fill_null(0.0) / lit(readings_per_hour))
+                (col(rainfall_col).sum().fill_null(0.0) * lit(depth_factor))
                     .alias("Daily Total (mm)"),
             ])
             .with_column(col("Daily Total (mm)"))
@@ -544,20 +1060,26 @@ impl<'a> InterimReportGenerator {
             )
             .collect()?;
 
-        // Weekly totals
+        // Weekly totals, grouped by the Monday starting each ISO week so weeks spanning New
+        // Year stay whole. 1970-01-01 (day 0) was a Thursday, three days after a Monday.
+        // "Days in Week" counts the days with data, exposing the partial first and last weeks.
+        let days_since_epoch = col("Date").cast(DataType::Int32);
+        let week_start = (days_since_epoch.clone() - (days_since_epoch + lit(3)) % lit(7))
+            .cast(DataType::Date)
+            .alias("Week Starting");
         let weekly_totals = daily_totals
             .clone()
             .lazy()
-            .with_column(col("Date").dt().weekday().alias("Weekday"))
-            .with_column(col("Date").dt().year().alias("Year"))
-            .with_column(col("Date").dt().week().alias("Week"))
-            .group_by([col("Year"), col("Week")])
+            .group_by([week_start])
             .agg([
                 col("Daily Total (mm)").sum().alias("Weekly Total (mm)"),
-                col("Date").min().alias("Week Starting"),
+                len().alias("Days in Week"),
+            ])
+            .select([
+                col("Week Starting"),
+                col("Weekly Total (mm)"),
+                col("Days in Week"),
             ])
-            .with_column(col("Week Starting").cast(DataType::Date))
-            .select([col("Week Starting"), col("Weekly Total (mm)")])
             .sort(
                 ["Week Starting"],
                 SortMultipleOptions::new().with_order_descending(false),
@@ -565,6 +1087,247 @@ impl<'a> InterimReportGenerator {
             .collect()?;
 
         Ok((daily_totals, weekly_totals))
-        //todo: need to fix first and last columns
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(day: u32, hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap().and_hms_opt(hour, 0, 0).unwrap()
+    }
+
+    /// Hourly readings of `value_column` for each listed January 2024 day (1st is a Monday).
+    fn handler(
+        monitor_type: &str,
+        value_column: (&str, &str),
+        days: &[u32],
+        value: impl Fn(u32, u32) -> f64,
+    ) -> CommandHandler {
+        let (key, name) = value_column;
+        let (timestamps, values): (Vec<NaiveDateTime>, Vec<f64>) = days
+            .iter()
+            .flat_map(|&day| (0..24).map(move |hour| (day, hour)))
+            .map(|(day, hour)| (at(day, hour), value(day, hour)))
+            .unzip();
+        let mut handler = CommandHandler::new();
+        handler.data_frame = Some(
+            DataFrame::new(vec![
+                Series::new("Timestamp".into(), timestamps),
+                Series::new(name.into(), values),
+            ])
+            .unwrap(),
+        );
+        handler.column_mapping = HashMap::from([
+            ("timestamp".to_string(), vec![("Timestamp".to_string(), 0, None, None)]),
+            (key.to_string(), vec![(name.to_string(), 1, None, None)]),
+        ]);
+        handler.monitor_type = monitor_type.to_string();
+        handler.time_col = Some("Timestamp".to_string());
+        handler.interval = Duration::hours(1);
+        handler
+    }
+
+    fn strings(df: &DataFrame, column: &str) -> Vec<String> {
+        df.column(column)
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .map(|v| v.unwrap_or_default().to_string())
+            .collect()
+    }
+
+    fn floats(df: &DataFrame, column: &str) -> Vec<Option<f64>> {
+        df.column(column)
+            .unwrap()
+            .cast(&DataType::Float64)
+            .unwrap()
+            .f64()
+            .unwrap()
+            .into_iter()
+            .collect()
+    }
+
+    #[test]
+    fn week_long_gap_is_reported_as_no_data() {
+        let days: Vec<u32> = (1..=7).chain(15..=21).collect();
+        let handler = handler("Depth", ("depth", "Depth_m"), &days, |_, _| 0.5);
+
+        let mut generator = InterimReportGenerator::new(&handler).unwrap();
+        generator.set_include_empty_periods(true);
+        let (summaries, _, _) = generator.generate_report(ReportPeriod::Weekly).unwrap();
+        assert_eq!(
+            strings(&summaries, "Interim Period"),
+            ["Interim 1", "Interim 2 (No Data)", "Interim 3", "Grand Total"]
+        );
+        assert_eq!(floats(&summaries, "Max Level(m)")[1], None);
+
+        let mut generator = InterimReportGenerator::new(&handler).unwrap();
+        let (summaries, _, _) = generator.generate_report(ReportPeriod::Weekly).unwrap();
+        assert_eq!(
+            strings(&summaries, "Interim Period"),
+            ["Interim 1", "Interim 2", "Grand Total"]
+        );
+    }
+
+    fn daily_rainfall_total(unit: RainfallUnit) -> f64 {
+        let mut handler = handler("Rainfall", ("rainfall", "Rainfall_mm"), &[1], |_, _| 2.0);
+        handler.interval = Duration::minutes(15);
+        handler.rainfall_unit = unit;
+        let generator = InterimReportGenerator::new(&handler).unwrap();
+        let (daily_totals, _) = generator.generate_rainfall_totals().unwrap();
+        floats(&daily_totals, "Daily Total (mm)")[0].unwrap()
+    }
+
+    #[test]
+    fn rainfall_totals_follow_the_rainfall_unit() {
+        // 24 readings of 2.0 at a 15 minute interval
+        assert_eq!(daily_rainfall_total(RainfallUnit::MmPerHour), 12.0);
+        assert_eq!(daily_rainfall_total(RainfallUnit::MmPerInterval), 48.0);
+        assert_eq!(daily_rainfall_total(RainfallUnit::Tips(0.5)), 24.0);
+    }
+
+    #[test]
+    fn weekly_totals_count_the_days_of_partial_weeks() {
+        // Thursday 4th to Tuesday 16th: four days, a full week, then two days
+        let days: Vec<u32> = (4..=16).collect();
+        let mut handler = handler("Rainfall", ("rainfall", "Rainfall_mm"), &days, |_, _| 1.0);
+        handler.rainfall_unit = RainfallUnit::MmPerInterval;
+        let generator = InterimReportGenerator::new(&handler).unwrap();
+        let (_, weekly_totals) = generator.generate_rainfall_totals().unwrap();
+
+        let week_starts = weekly_totals
+            .column("Week Starting")
+            .unwrap()
+            .cast(&DataType::String)
+            .unwrap();
+        assert_eq!(
+            strings(&DataFrame::new(vec![week_starts]).unwrap(), "Week Starting"),
+            ["2024-01-01", "2024-01-08", "2024-01-15"]
+        );
+        assert_eq!(floats(&weekly_totals, "Days in Week"), [Some(4.0), Some(7.0), Some(2.0)]);
+        assert_eq!(
+            floats(&weekly_totals, "Weekly Total (mm)"),
+            [Some(96.0), Some(168.0), Some(48.0)]
+        );
+    }
+
+    #[test]
+    fn metre_columns_keep_millimetre_precision() {
+        let handler = handler("Depth", ("depth", "Depth_m"), &[1], |_, _| 0.1234);
+        let mut generator = InterimReportGenerator::new(&handler).unwrap();
+        let (summaries, _, daily) = generator.generate_report(ReportPeriod::Weekly).unwrap();
+        assert_eq!(floats(&summaries, "Max Level(m)")[0], Some(0.123));
+        assert_eq!(floats(&daily, "Max Level(m)")[0], Some(0.123));
+
+        let mut generator = InterimReportGenerator::new(&handler).unwrap();
+        generator.set_decimals(4);
+        let (summaries, _, _) = generator.generate_report(ReportPeriod::Weekly).unwrap();
+        assert_eq!(floats(&summaries, "Max Level(m)")[0], Some(0.1234));
+    }
+
+    /// The daily summary, checked column by column against values worked out by hand for
+    /// days 1 and 2.
+    fn assert_daily_summary(mut generator: InterimReportGenerator, expected: &[(&str, [f64; 2])]) {
+        generator.calculate_values().unwrap();
+        let summary = generator.calculate_daily_summary().unwrap();
+        let mut columns = vec!["Date"];
+        columns.extend(expected.iter().map(|(column, _)| *column));
+        assert_eq!(summary.get_column_names(), columns);
+        for (column, values) in expected {
+            let actual = floats(&summary, column);
+            for (actual, expected) in actual.iter().zip(values) {
+                let actual = actual.unwrap();
+                assert!((actual - expected).abs() < 1e-9, "{}: {} != {}", column, actual, expected);
+            }
+        }
+    }
+
+    // The expected columns and values are those of the per-monitor-type summaries that
+    // calculate_summary replaced.
+    #[test]
+    fn flow_daily_summary_matches_the_previous_output() {
+        let handler = handler("Flow", ("flow", "Flow_ls"), &[1, 2], |day, _| 10.0 * day as f64);
+        assert_daily_summary(
+            InterimReportGenerator::new(&handler).unwrap(),
+            &[
+                ("Average Flow(l/s)", [10.0, 20.0]),
+                ("Max Flow(l/s)", [10.0, 20.0]),
+                ("Min Flow(l/s)", [10.0, 20.0]),
+                ("Flow (m3)", [864.0, 1728.0]),
+            ],
+        );
+    }
+
+    #[test]
+    fn depth_daily_summary_matches_the_previous_output() {
+        let handler = handler("Depth", ("depth", "Depth_m"), &[1, 2], |day, hour| {
+            day as f64 + hour as f64 / 100.0
+        });
+        assert_daily_summary(
+            InterimReportGenerator::new(&handler).unwrap(),
+            &[
+                ("Average Level(m)", [1.115, 2.115]),
+                ("Max Level(m)", [1.23, 2.23]),
+                ("Min Level(m)", [1.0, 2.0]),
+            ],
+        );
+    }
+
+    #[test]
+    fn rainfall_daily_summary_matches_the_previous_output() {
+        let mut handler = handler("Rainfall", ("rainfall", "Rainfall_mm"), &[1, 2], |day, hour| {
+            if hour < 6 { day as f64 } else { 0.0 }
+        });
+        handler.rainfall_unit = RainfallUnit::MmPerInterval;
+        assert_daily_summary(
+            InterimReportGenerator::new(&handler).unwrap(),
+            &[
+                ("Total Rainfall(mm)", [6.0, 12.0]),
+                ("Max Rainfall(mm)", [1.0, 2.0]),
+                ("Min Rainfall(mm)", [0.0, 0.0]),
+            ],
+        );
+    }
+
+    #[test]
+    fn outage_within_a_day_makes_it_incomplete() {
+        // Day 2 has a six hour gap filled with empty readings
+        let handler = handler("Depth", ("depth", "Depth_m"), &[1, 2, 3], |day, hour| {
+            if day == 2 && (6..12).contains(&hour) {
+                f64::NAN
+            } else {
+                0.5
+            }
+        });
+
+        let mut generator = InterimReportGenerator::new(&handler).unwrap();
+        generator.set_daily_completeness(DailyCompleteness::Flag);
+        let daily = generator.calculate_daily_summary().unwrap();
+        let incomplete: Vec<Option<bool>> = daily
+            .column("Incomplete")
+            .unwrap()
+            .bool()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(incomplete, [false, true, false].map(Some));
+    }
+
+    #[test]
+    fn custom_periods_are_bounded() {
+        assert_eq!(
+            ReportPeriod::from_str("days", Some(MAX_PERIOD_DAYS)).unwrap(),
+            ReportPeriod::Days(MAX_PERIOD_DAYS)
+        );
+        assert!(ReportPeriod::from_str("days", Some(0)).is_err());
+        assert!(ReportPeriod::from_str("days", Some(MAX_PERIOD_DAYS + 1)).is_err());
+        assert!(ReportPeriod::from_str("days", Some(i64::MAX)).is_err());
+
+        // Built directly, an overlong period runs to the end of the calendar
+        let end = ReportPeriod::Days(i64::MAX).period_end(at(1, 0));
+        assert_eq!(end.date(), NaiveDate::MAX);
     }
 }
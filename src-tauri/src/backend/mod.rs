@@ -1,5 +1,7 @@
 pub mod backend;
 pub mod batch_processing;
+pub mod combined_report;
 pub mod file_processor;
 pub mod interim_reports;
+pub mod site_config;
 pub mod site_info;
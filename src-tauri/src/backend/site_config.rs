@@ -0,0 +1,128 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::{ Path, PathBuf };
+
+/// File names looked for next to the batch input files, in order of preference.
+pub const SITE_CONFIG_FILES: [&str; 2] = ["sites.json", "sites.csv"];
+
+#[derive(Debug, thiserror::Error)]
+pub enum SiteConfigError {
+    #[error("I/O error: {0}")] IoError(#[from] std::io::Error),
+    #[error("JSON parsing error: {0}")] JsonError(#[from] serde_json::Error),
+    #[error("CSV parsing error: {0}")] CsvError(#[from] csv::Error),
+    #[error("Unsupported site config file: {0}")] UnsupportedFormat(String),
+}
+
+/// Per-site batch parameters. Any field left empty falls back to the batch defaults.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SiteParams {
+    #[serde(default, alias = "pipeShape")]
+    pub pipe_shape: Option<String>,
+    #[serde(default, alias = "pipeSize")]
+    pub pipe_size: Option<String>,
+    #[serde(default, alias = "monitorType")]
+    pub monitor_type: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SiteRow {
+    #[serde(alias = "siteId", alias = "site")]
+    site_id: String,
+    #[serde(default, alias = "pipeShape")]
+    pipe_shape: Option<String>,
+    #[serde(default, alias = "pipeSize")]
+    pipe_size: Option<String>,
+    #[serde(default, alias = "monitorType")]
+    monitor_type: Option<String>,
+}
+
+/// Site id → parameters, read from a `sites.json` or `sites.csv` sidecar.
+///
+/// JSON is an object keyed by site id, e.g. `{"SITE01": {"pipe_shape": "Circular",
+/// "pipe_size": "300"}}`. CSV has a `site_id,pipe_shape,pipe_size,monitor_type` header row;
+/// egg sizes containing commas must be quoted. Site ids match case-insensitively.
+#[derive(Debug, Clone, Default)]
+pub struct SiteConfig {
+    sites: HashMap<String, SiteParams>,
+}
+
+impl SiteConfig {
+    pub fn load(path: &Path) -> Result<Self, SiteConfigError> {
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_lowercase())
+            .unwrap_or_default();
+
+        let sites: Vec<(String, SiteParams)> = match extension.as_str() {
+            "json" => {
+                let parsed: HashMap<String, SiteParams> = serde_json::from_reader(
+                    File::open(path)?
+                )?;
+                parsed.into_iter().collect()
+            }
+            "csv" => {
+                let mut reader = csv::ReaderBuilder::new().trim(csv::Trim::All).from_path(path)?;
+                reader
+                    .deserialize::<SiteRow>()
+                    .map(|row| {
+                        row.map(|row| {
+                            let params = SiteParams {
+                                pipe_shape: row.pipe_shape,
+                                pipe_size: row.pipe_size,
+                                monitor_type: row.monitor_type,
+                            };
+                            (row.site_id, params)
+                        })
+                    })
+                    .collect::<Result<_, _>>()?
+            }
+            _ => {
+                return Err(SiteConfigError::UnsupportedFormat(path.to_string_lossy().to_string()));
+            }
+        };
+
+        let sites = sites
+            .into_iter()
+            .map(|(site_id, params)| (Self::key(&site_id), params.normalized()))
+            .collect();
+        Ok(SiteConfig { sites })
+    }
+
+    /// Returns the first sidecar config found in `dir`, if any.
+    pub fn find_in(dir: &Path) -> Option<PathBuf> {
+        SITE_CONFIG_FILES.iter()
+            .map(|name| dir.join(name))
+            .find(|path| path.is_file())
+    }
+
+    pub fn get(&self, site_id: &str) -> Option<&SiteParams> {
+        self.sites.get(&Self::key(site_id))
+    }
+
+    pub fn len(&self) -> usize {
+        self.sites.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sites.is_empty()
+    }
+
+    fn key(site_id: &str) -> String {
+        site_id.trim().to_lowercase()
+    }
+}
+
+impl SiteParams {
+    fn normalized(self) -> Self {
+        let non_empty = |value: Option<String>| {
+            value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+        };
+        SiteParams {
+            pipe_shape: non_empty(self.pipe_shape),
+            pipe_size: non_empty(self.pipe_size),
+            monitor_type: non_empty(self.monitor_type),
+        }
+    }
+}
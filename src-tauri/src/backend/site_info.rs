@@ -34,7 +34,15 @@ impl SiteInfo {
     }
 
     pub(crate) fn extract_from_filename(&mut self, filename: &str) -> Result<(), Box<dyn Error>> {
-        let name_without_ext = Path::new(filename)
+        let path = Path::new(filename);
+        // Look through a compression suffix, e.g. SITE1.csv.gz
+        let path = match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("gz") => {
+                Path::new(path.file_stem().ok_or("Invalid filename")?)
+            }
+            _ => path,
+        };
+        let name_without_ext = path
             .file_stem()
             .ok_or("Invalid filename")?
             .to_str()
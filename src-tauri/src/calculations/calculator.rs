@@ -4,6 +4,22 @@ use std::fmt;
 // Define the Calculator trait (equivalent to Python's ABC)
 pub trait Calculator {
     fn perform_calculation(&self, depth: f64, velocity: f64) -> Result<f64, CalculationError>;
+
+    /// Cross-sectional area of water in m² at the given depth in m.
+    fn wetted_area(&self, depth: f64) -> Result<f64, CalculationError> {
+        Ok(self.perform_calculation(depth, 1.0)? / 1000.0)
+    }
+
+    /// Length of pipe wall in contact with water in m at the given depth in m, for shapes
+    /// where it is known.
+    fn wetted_perimeter(&self, _depth: f64) -> Option<f64> {
+        None
+    }
+
+    /// Named dimensions derived by the constructor, used to show the user what was built.
+    fn geometry(&self) -> Vec<(&'static str, f64)> {
+        Vec::new()
+    }
 }
 
 // Custom error type
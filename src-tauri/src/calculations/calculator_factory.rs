@@ -0,0 +1,122 @@
+use super::calculator::{CalculationError, Calculator};
+use super::circular_calculator::CircularCalculator;
+use super::egg1_calculator::Egg1Calculator;
+use super::egg2_calculator::Egg2Calculator;
+use super::egg2a_calculator::Egg2ACalculator;
+use super::rectangular_calculator::RectangularCalculator;
+use super::two_circle_and_rectangle_calculator::TwoCircleAndRectangleCalculator;
+use super::u_shape_calculator::UShapeCalculator;
+
+fn parse_value(value: &str) -> Result<f64, CalculationError> {
+    value
+        .trim()
+        .parse::<f64>()
+        .map_err(|_| CalculationError::new(&format!("Invalid pipe parameter: '{}'", value)))
+}
+
+fn parse_values(pipe_size_param: &str, expected: usize) -> Result<Vec<f64>, CalculationError> {
+    let values = pipe_size_param
+        .split(',')
+        .map(parse_value)
+        .collect::<Result<Vec<f64>, _>>()?;
+    if values.len() < expected {
+        return Err(CalculationError::new(&format!(
+            "Expected {} comma-separated pipe parameters, got {}",
+            expected,
+            values.len()
+        )));
+    }
+    Ok(values)
+}
+
+/// Pipe shapes accepted by `create_calculator`.
+pub const SUPPORTED_PIPE_SHAPES: [&str; 7] = [
+    "Circular",
+    "Rectangular",
+    "Egg Type 1",
+    "Egg Type 2a",
+    "Egg Type 2",
+    "Two Circles and a Rectangle",
+    "U-Shape",
+];
+
+/// Builds the calculator for a pipe shape from the size string entered by the user.
+/// Circular, rectangular and U-shape sizes are in mm; the returned diameter/width is in metres.
+/// An empty size yields a calculator with sentinel dimensions, matching how depth-only
+/// conversions have always been handled.
+pub fn create_calculator(
+    pipe_type: &str,
+    pipe_size_param: &str,
+) -> Result<(Box<dyn Calculator>, Option<f64>), CalculationError> {
+    let has_size = !pipe_size_param.trim().is_empty();
+
+    let calculator: (Box<dyn Calculator>, Option<f64>) = match pipe_type {
+        "Circular" => {
+            if has_size {
+                let pipe_size = parse_value(pipe_size_param)? / 1000.0;
+                (Box::new(CircularCalculator::new(pipe_size / 2.0)?), Some(pipe_size))
+            } else {
+                (Box::new(CircularCalculator::new(-0.5)?), None) // Use -0.5 to indicate invalid radius
+            }
+        }
+        "Rectangular" => {
+            if has_size {
+                let pipe_size = parse_value(pipe_size_param)? / 1000.0;
+                (Box::new(RectangularCalculator::new(pipe_size)?), Some(pipe_size))
+            } else {
+                (Box::new(RectangularCalculator::new(-1.0)?), None) // Use -1.0 to indicate invalid size
+            }
+        }
+        "Egg Type 1" => {
+            if has_size {
+                let egg_params = parse_values(pipe_size_param, 3)?;
+                let calculator = Egg1Calculator::new(egg_params[0], egg_params[1], egg_params[2])?;
+                (Box::new(calculator), None)
+            } else {
+                (Box::new(Egg1Calculator::new(-1.0, -1.0, -1.0)?), None) // Use -1.0 to indicate invalid params
+            }
+        }
+        "Egg Type 2a" => {
+            if has_size {
+                let egg_params = parse_values(pipe_size_param, 3)?;
+                let calculator = Egg2ACalculator::new(egg_params[0], egg_params[1], egg_params[2])?;
+                (Box::new(calculator), None)
+            } else {
+                (Box::new(Egg2ACalculator::new(-1.0, -1.0, -1.0)?), None) // Use -1.0 to indicate invalid params
+            }
+        }
+        "Egg Type 2" => {
+            if has_size {
+                let egg_height = parse_value(pipe_size_param)?;
+                (Box::new(Egg2Calculator::new(egg_height)?), None)
+            } else {
+                (Box::new(Egg2Calculator::new(-1.0)?), None) // Use -1.0 to indicate invalid height
+            }
+        }
+        "Two Circles and a Rectangle" => {
+            if has_size {
+                // Sizes are given as "height,width"
+                let params = parse_values(pipe_size_param, 2)?;
+                let (height, width) = (params[0], params[1]);
+                (Box::new(TwoCircleAndRectangleCalculator::from_width_height(width, height)?), None)
+            } else {
+                (Box::new(TwoCircleAndRectangleCalculator::new(-1.0, -1.0)?), None) // Use -1.0 to indicate invalid params
+            }
+        }
+        "U-Shape" => {
+            if has_size {
+                // Sizes are given as "width,height"; the width is the invert diameter
+                let params = parse_values(pipe_size_param, 2)?;
+                let (width, height) = (params[0] / 1000.0, params[1] / 1000.0);
+                (Box::new(UShapeCalculator::from_width_height(width, height)?), Some(width))
+            } else {
+                (Box::new(UShapeCalculator::new(-1.0, -1.0)?), None) // Use -1.0 to indicate invalid params
+            }
+        }
+        _ => {
+            return Err(CalculationError::new(&format!("Unsupported pipe type: {}", pipe_type)));
+        }
+    };
+
+    Ok(calculator)
+}
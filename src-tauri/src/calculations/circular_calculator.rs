@@ -55,4 +55,14 @@ impl Calculator for CircularCalculator {
     fn perform_calculation(&self, depth: f64, velocity: f64) -> Result<f64, CalculationError> {
         Ok(self.calculate_flow_value(depth, velocity))
     }
+
+    fn wetted_perimeter(&self, depth: f64) -> Option<f64> {
+        let depth = depth.clamp(0.0, self.pipe_radius * 2.0);
+        let theta = 2.0 * ((self.pipe_radius - depth) / self.pipe_radius).acos();
+        Some(self.pipe_radius * theta)
+    }
+
+    fn geometry(&self) -> Vec<(&'static str, f64)> {
+        vec![("radius", self.pipe_radius)]
+    }
 }
@@ -66,4 +66,12 @@ impl Calculator for Egg2Calculator {
     fn perform_calculation(&self, depth: f64, velocity: f64) -> Result<f64, CalculationError> {
         self.perform_egg_calculation(depth, velocity)
     }
+
+    fn wetted_perimeter(&self, depth: f64) -> Option<f64> {
+        Some(self.egg_wetted_perimeter(depth))
+    }
+
+    fn geometry(&self) -> Vec<(&'static str, f64)> {
+        self.egg_geometry()
+    }
 }
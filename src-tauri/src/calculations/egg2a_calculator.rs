@@ -58,4 +58,30 @@ impl Calculator for Egg2ACalculator {
         let result = area * velocity * 1000.0;
         Ok(result.max(0.0))
     }
+
+    fn wetted_perimeter(&self, depth: f64) -> Option<f64> {
+        let [_, perimeter] = WettedAreaCalculationHelper::area(
+            self.height,
+            self.radius1,
+            self.radius2,
+            self.radius3,
+            self.h1,
+            self.h2,
+            self.offset,
+            depth,
+        );
+        Some(perimeter)
+    }
+
+    fn geometry(&self) -> Vec<(&'static str, f64)> {
+        vec![
+            ("height", self.height),
+            ("radius1", self.radius1),
+            ("radius2", self.radius2),
+            ("radius3", self.radius3),
+            ("offset", self.offset),
+            ("height1", self.h1),
+            ("height2", self.h2),
+        ]
+    }
 }
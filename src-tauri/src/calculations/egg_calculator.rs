@@ -10,6 +10,32 @@ pub trait EggCalculator: Calculator {
     fn height1(&self) -> f64;
     fn height2(&self) -> f64;
 
+    fn egg_wetted_perimeter(&self, depth: f64) -> f64 {
+        let [_, perimeter] = WettedAreaCalculationHelper::area(
+            self.height(),
+            self.radius1(),
+            self.radius2(),
+            self.radius3(),
+            self.height1(),
+            self.height2(),
+            self.offset(),
+            depth,
+        );
+        perimeter
+    }
+
+    fn egg_geometry(&self) -> Vec<(&'static str, f64)> {
+        vec![
+            ("height", self.height()),
+            ("radius1", self.radius1()),
+            ("radius2", self.radius2()),
+            ("radius3", self.radius3()),
+            ("offset", self.offset()),
+            ("height1", self.height1()),
+            ("height2", self.height2()),
+        ]
+    }
+
     fn perform_egg_calculation(&self, depth: f64, velocity: f64) -> Result<f64, CalculationError> {
         let [area, _] = WettedAreaCalculationHelper::area(
             self.height(),
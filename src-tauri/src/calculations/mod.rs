@@ -1,4 +1,5 @@
 pub mod calculator;
+pub mod calculator_factory;
 pub mod circular_calculator;
 pub mod egg1_calculator;
 pub mod egg2_calculator;
@@ -7,4 +8,5 @@ pub mod egg_calculator;
 pub mod r3_calculator;
 pub mod rectangular_calculator;
 pub mod two_circle_and_rectangle_calculator;
+pub mod u_shape_calculator;
 pub mod wetted_area_calculation_helper;
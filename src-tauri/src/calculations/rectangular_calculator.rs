@@ -21,4 +21,12 @@ impl Calculator for RectangularCalculator {
         let flow = depth * velocity * self.channel_width * 1000.0;
         Ok(flow.max(0.0))
     }
+
+    fn wetted_perimeter(&self, depth: f64) -> Option<f64> {
+        Some(self.channel_width + 2.0 * depth.max(0.0))
+    }
+
+    fn geometry(&self) -> Vec<(&'static str, f64)> {
+        vec![("width", self.channel_width)]
+    }
 }
@@ -16,10 +16,24 @@ pub struct TwoCircleAndRectangleCalculator {
 }
 
 impl TwoCircleAndRectangleCalculator {
+    /// Same as `from_width_height`; note the width comes first.
     pub fn new(width: f64, height: f64) -> Result<Self, CalculationError> {
+        Self::from_width_height(width, height)
+    }
+
+    /// A rectangle of `width` with semicircles of diameter `width` top and bottom, `height`
+    /// tall overall. The straight sides are `height - width` long, so the height must be
+    /// at least the width.
+    pub fn from_width_height(width: f64, height: f64) -> Result<Self, CalculationError> {
         if width.is_nan() || height.is_nan() || width <= 0.0 || height <= 0.0 {
             return Err(CalculationError::new("Invalid width or height."));
         }
+        if height < width {
+            return Err(CalculationError::new(&format!(
+                "Height ({}) must be at least the width ({}) for a two circle and rectangle pipe.",
+                height, width
+            )));
+        }
 
         Ok(TwoCircleAndRectangleCalculator { height, width })
     }
@@ -64,4 +78,50 @@ impl Calculator for TwoCircleAndRectangleCalculator {
 
         Ok(flow)
     }
+
+    fn wetted_perimeter(&self, depth: f64) -> Option<f64> {
+        let r = self.width / 2.0;
+        let depth = depth.clamp(0.0, self.height);
+        let perimeter = if depth < r {
+            2.0 * r * ((r - depth) / r).acos()
+        } else if depth < self.height - r {
+            PI * r + 2.0 * (depth - r)
+        } else {
+            let above_centre = depth - (self.height - r);
+            PI * r + 2.0 * (self.height - self.width) + 2.0 * r * (above_centre / r).asin()
+        };
+        Some(perimeter)
+    }
+
+    fn geometry(&self) -> Vec<(&'static str, f64)> {
+        vec![("width", self.width), ("height", self.height)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calculations::calculator_factory::create_calculator;
+
+    #[test]
+    fn height_below_width_is_rejected() {
+        assert!(TwoCircleAndRectangleCalculator::from_width_height(1.0, 0.5).is_err());
+        assert!(TwoCircleAndRectangleCalculator::from_width_height(0.0, 1.0).is_err());
+        assert!(TwoCircleAndRectangleCalculator::from_width_height(1.0, 1.0).is_ok());
+    }
+
+    #[test]
+    fn full_pipe_flow_covers_both_semicircles_and_the_rectangle() {
+        let calculator = TwoCircleAndRectangleCalculator::from_width_height(1.0, 2.0).unwrap();
+        let expected = (PI * 0.25 + 1.0) * 1000.0;
+        let flow = calculator.perform_calculation(2.0, 1.0).unwrap();
+        assert!((flow - expected).abs() < 1e-9, "{} != {}", flow, expected);
+    }
+
+    #[test]
+    fn factory_sizes_are_height_then_width() {
+        let (calculator, _) = create_calculator("Two Circles and a Rectangle", "2,1").unwrap();
+        assert_eq!(calculator.geometry(), vec![("width", 1.0), ("height", 2.0)]);
+        assert!(create_calculator("Two Circles and a Rectangle", "1,2").is_err());
+    }
 }
@@ -0,0 +1,107 @@
+use super::calculator::{CalculationError, Calculator};
+use super::circular_calculator::CircularCalculator;
+use std::f64::consts::PI;
+
+/// A channel with a semicircular invert and vertical walls rising from its springing line.
+pub struct UShapeCalculator {
+    channel_width: f64,
+    channel_height: f64,
+    radius: f64,
+    invert: CircularCalculator,
+}
+
+impl UShapeCalculator {
+    /// Builds the channel without checking its size, so the factory can stand one in when
+    /// no size was entered. Use `from_width_height` for entered sizes.
+    pub fn new(width: f64, height: f64) -> Result<Self, CalculationError> {
+        if width.is_nan() {
+            return Err(CalculationError::new("Channel Width Invalid."));
+        }
+        if height.is_nan() {
+            return Err(CalculationError::new("Channel Height Invalid."));
+        }
+
+        let radius = width / 2.0;
+        Ok(UShapeCalculator {
+            channel_width: width,
+            channel_height: height,
+            radius,
+            invert: CircularCalculator::new(radius)?,
+        })
+    }
+
+    /// A channel `width` across, which is also the invert diameter, and `height` deep in
+    /// total. Both must be positive and the height at least the invert radius.
+    pub fn from_width_height(width: f64, height: f64) -> Result<Self, CalculationError> {
+        if width.is_nan() || height.is_nan() || width <= 0.0 || height <= 0.0 {
+            return Err(CalculationError::new(&format!(
+                "Channel width ({}) and height ({}) must be positive.",
+                width, height
+            )));
+        }
+        if height < width / 2.0 {
+            return Err(CalculationError::new(
+                "Channel height must be at least half the width.",
+            ));
+        }
+        Self::new(width, height)
+    }
+
+    fn calculate_flow_value(&self, depth_value: f64, velocity_value: f64) -> f64 {
+        if depth_value <= 0.0 {
+            0.0
+        } else if depth_value <= self.radius {
+            self.invert
+                .perform_calculation(depth_value, velocity_value)
+                .unwrap_or(0.0)
+        } else {
+            let wall_depth = depth_value.min(self.channel_height) - self.radius;
+            let area = PI * self.radius.powi(2) / 2.0 + wall_depth * self.channel_width;
+            area * velocity_value * 1000.0
+        }
+    }
+}
+
+impl Calculator for UShapeCalculator {
+    fn perform_calculation(&self, depth: f64, velocity: f64) -> Result<f64, CalculationError> {
+        Ok(self.calculate_flow_value(depth, velocity))
+    }
+
+    fn wetted_perimeter(&self, depth: f64) -> Option<f64> {
+        if depth <= self.radius {
+            return self.invert.wetted_perimeter(depth);
+        }
+        let wall_depth = depth.min(self.channel_height) - self.radius;
+        Some(PI * self.radius + 2.0 * wall_depth)
+    }
+
+    fn geometry(&self) -> Vec<(&'static str, f64)> {
+        vec![
+            ("width", self.channel_width),
+            ("height", self.channel_height),
+            ("radius", self.radius),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calculations::calculator_factory::create_calculator;
+
+    #[test]
+    fn non_positive_sizes_are_rejected() {
+        assert!(UShapeCalculator::from_width_height(0.0, 0.5).is_err());
+        assert!(UShapeCalculator::from_width_height(0.6, -1.0).is_err());
+        assert!(UShapeCalculator::from_width_height(0.6, 0.2).is_err());
+        assert!(UShapeCalculator::from_width_height(0.6, 0.3).is_ok());
+    }
+
+    #[test]
+    fn factory_rejects_a_zero_size_but_not_a_missing_one() {
+        assert!(create_calculator("U-Shape", "0,500").is_err());
+        assert!(create_calculator("U-Shape", "600,0").is_err());
+        assert!(create_calculator("U-Shape", "600,1200").is_ok());
+        assert!(create_calculator("U-Shape", "").is_ok());
+    }
+}
@@ -0,0 +1,62 @@
+use chrono::{ Duration, NaiveDateTime, NaiveTime };
+
+/// Days of antecedent rainfall held in the rainfall header, as `0_ANT_RAIN`..`30_ANT_RAIN`.
+pub const ANTECEDENT_DAYS: usize = 30;
+
+/// Value written for an antecedent total that can't be computed.
+const UNKNOWN_RAIN: f64 = -1.0;
+
+/// Largest total the `F5.1` constant fields can hold.
+const MAX_RAIN: f64 = 999.9;
+
+/// Rainfall depth (mm) fallen before `start` for N = 0..=30. N = 0 covers the start day up
+/// to `start`; N ≥ 1 the N days immediately before `start`. A total is `None` unless
+/// `readings` (timestamp, depth in mm) reach back to the start of its window; missing
+/// depths inside a covered window count as no rain, as in the data records.
+pub fn antecedent_rainfall(
+    readings: &[(NaiveDateTime, Option<f64>)],
+    start: NaiveDateTime
+) -> Vec<Option<f64>> {
+    let prior: Vec<&(NaiveDateTime, Option<f64>)> = readings
+        .iter()
+        .filter(|(timestamp, _)| *timestamp < start)
+        .collect();
+    let earliest = prior
+        .iter()
+        .map(|(timestamp, _)| *timestamp)
+        .min();
+
+    (0..=ANTECEDENT_DAYS)
+        .map(|days| {
+            let window_start = if days == 0 {
+                start.date().and_time(NaiveTime::MIN)
+            } else {
+                start - Duration::days(days as i64)
+            };
+            if earliest.is_none_or(|earliest| earliest > window_start) {
+                return None;
+            }
+            let total = prior
+                .iter()
+                .filter(|(timestamp, _)| *timestamp >= window_start)
+                .filter_map(|(_, depth)| depth.filter(|d| d.is_finite()))
+                .sum::<f64>();
+            Some(total)
+        })
+        .collect()
+}
+
+fn format_rain(total: Option<f64>) -> String {
+    format!("{:<5.1}", total.map_or(UNKNOWN_RAIN, |total| total.min(MAX_RAIN)))
+}
+
+/// Constants block lines holding the location and antecedent totals: the location with
+/// `0_ANT_RAIN`, then 15 totals per line. Unknown totals are written as `-1.0`.
+pub fn antecedent_lines(totals: &[Option<f64>]) -> Vec<String> {
+    let value = |days: usize| format_rain(totals.get(days).copied().flatten());
+    vec![
+        format!("{:<21}{}", "UNKNOWN", value(0)),
+        (1..=15).map(value).collect(),
+        (16..=ANTECEDENT_DAYS).map(value).collect()
+    ]
+}
@@ -0,0 +1,41 @@
+/// Units recognised as a column's `|unit` suffix.
+const UNIT_SUFFIXES: [&str; 9] = ["m", "mm", "cm", "l/s", "m3/s", "m/s", "cm/s", "mm/s", "mm/hr"];
+
+/// Splits a `name|unit` column into its base name and unit, when the suffix is a known unit.
+pub fn split_unit(column: &str) -> Option<(&str, &str)> {
+    let (base, suffix) = column.rsplit_once('|')?;
+    let suffix = suffix.trim();
+    UNIT_SUFFIXES
+        .iter()
+        .any(|unit| unit.eq_ignore_ascii_case(suffix))
+        .then_some((base, suffix))
+}
+
+/// Whether a depth column holds millimetres. Its `|unit` suffix decides when it has one, so
+/// a `Depth_mm` column relabelled as metres reads as metres; otherwise the name is searched.
+pub fn is_depth_in_mm(column: &str) -> bool {
+    match split_unit(column) {
+        Some((_, unit)) => unit.eq_ignore_ascii_case("mm"),
+        None => column.contains("mm"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unit_suffix_decides_over_the_name() {
+        assert!(is_depth_in_mm("1_2|Depth|mm"));
+        assert!(!is_depth_in_mm("Depth_mm|m"));
+        assert!(is_depth_in_mm("Depth_mm"));
+        assert!(!is_depth_in_mm("Depth_m"));
+    }
+
+    #[test]
+    fn unknown_suffix_is_part_of_the_name() {
+        assert_eq!(split_unit("Depth_mm|m"), Some(("Depth_mm", "m")));
+        assert_eq!(split_unit("Site|Depth"), None);
+        assert_eq!(split_unit("Depth"), None);
+    }
+}
@@ -0,0 +1,16 @@
+/// Longest comment line written, keeping within the 80 column FDV record width.
+const MAX_COMMENT_LEN: usize = 80;
+
+/// Builds the `*`-prefixed comment lines written at the top of the constants block: a line
+/// naming the converter version, followed by the user's lines. Line breaks inside a line
+/// start a new comment line and overlong lines are truncated.
+pub fn comment_lines(lines: &[String]) -> Vec<String> {
+    let version = format!("Created by {} v{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+    std::iter::once(version.as_str())
+        .chain(lines.iter().flat_map(|line| line.lines()))
+        .map(|line| {
+            let line: String = format!("* {}", line.trim_end());
+            line.chars().take(MAX_COMMENT_LEN).collect()
+        })
+        .collect()
+}
@@ -0,0 +1,33 @@
+/// Quality status written to FDV headers. Ordered so that combining statuses keeps the
+/// most processed one: any estimate outranks an edit, which outranks raw data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum DataStatus {
+    #[default]
+    Raw,
+    Edited,
+    Estimated,
+}
+
+impl DataStatus {
+    pub fn from_str(s: &str) -> Result<Self, String> {
+        match s.to_lowercase().as_str() {
+            "raw" => Ok(DataStatus::Raw),
+            "edited" => Ok(DataStatus::Edited),
+            "estimated" => Ok(DataStatus::Estimated),
+            _ => Err(format!("'{}' is not a valid data status", s)),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DataStatus::Raw => "RAW",
+            DataStatus::Edited => "EDITED",
+            DataStatus::Estimated => "ESTIMATED",
+        }
+    }
+
+    /// Header line declaring the status, written after the identifier.
+    pub fn header_line(&self) -> String {
+        format!("**DATA_STATUS:           1,{}", self.as_str())
+    }
+}
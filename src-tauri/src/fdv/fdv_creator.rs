@@ -3,16 +3,24 @@ use polars::prelude::*;
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::{ self, BufWriter, Write };
-use std::path::Path;
+use std::path::{ Path, PathBuf };
 use thiserror::Error;
 
 use crate::calculations::calculator::{ CalculationError, Calculator };
-use crate::calculations::circular_calculator::CircularCalculator;
-use crate::calculations::egg1_calculator::Egg1Calculator;
-use crate::calculations::egg2_calculator::Egg2Calculator;
-use crate::calculations::egg2a_calculator::Egg2ACalculator;
-use crate::calculations::rectangular_calculator::RectangularCalculator;
-use crate::calculations::two_circle_and_rectangle_calculator::TwoCircleAndRectangleCalculator;
+use crate::calculations::calculator_factory::create_calculator;
+use crate::fdv::column_unit::is_depth_in_mm;
+use crate::fdv::comment::comment_lines;
+use crate::fdv::data_status::DataStatus;
+use crate::fdv::field_layout::{
+    format_line,
+    record_length_line,
+    FieldSpec,
+};
+use crate::fdv::identifier::fdv_identifier;
+use crate::fdv::interval_check::check_interval;
+use crate::fdv::overwrite::{ resolve_output_path, OverwritePolicy };
+use crate::fdv::padding::pad_to_full_days;
+use crate::fdv::quality::apply_quality_flags;
 
 #[derive(Error, Debug)]
 pub enum FDVFlowCreatorError {
@@ -23,6 +31,146 @@ pub enum FDVFlowCreatorError {
     #[error("Parse error: {0}")] ParseError(#[from] ParseError),
 }
 
+/// Flow (l/s), depth (mm) and velocity (m/s) fields of the standard flow record.
+const FLOW_FIELDS: [FieldSpec; 3] = [
+    FieldSpec::integer(5),
+    FieldSpec::integer(5),
+    FieldSpec { width: 5, decimals: 2, format_decimals: false },
+];
+
+/// Depth (mm) field of the depth only record.
+const DEPTH_FIELDS: [FieldSpec; 1] = [FieldSpec::integer(5)];
+
+/// Layout of the flow output. `FixedWidth` is standard FDV; the delimited styles write one
+/// `timestamp,flow,depth,velocity` row per reading for tools that can't read packed FDV.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputStyle {
+    #[default]
+    FixedWidth,
+    Comma,
+    Tab,
+}
+
+impl OutputStyle {
+    pub fn from_str(s: &str) -> Result<Self, String> {
+        match s.to_lowercase().as_str() {
+            "fdv" | "fixed" | "fixed_width" => Ok(OutputStyle::FixedWidth),
+            "csv" | "comma" => Ok(OutputStyle::Comma),
+            "tsv" | "tab" => Ok(OutputStyle::Tab),
+            _ => Err(format!("'{}' is not a valid output style", s)),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutputStyle::FixedWidth => "fdv",
+            OutputStyle::Comma => "csv",
+            OutputStyle::Tab => "tsv",
+        }
+    }
+
+    fn separator(&self) -> Option<char> {
+        match self {
+            OutputStyle::FixedWidth => None,
+            OutputStyle::Comma => Some(','),
+            OutputStyle::Tab => Some('\t'),
+        }
+    }
+}
+
+/// Unit of the written flow. Calculators work in l/s; `CubicMetresPerSecond` scales their
+/// output for rivers and trunk sewers, which are conventionally reported in m3/s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FlowUnit {
+    #[default]
+    LitresPerSecond,
+    CubicMetresPerSecond,
+}
+
+impl FlowUnit {
+    pub fn from_str(s: &str) -> Result<Self, String> {
+        match s.to_lowercase().as_str() {
+            "l/s" | "lps" | "litres_per_second" => Ok(FlowUnit::LitresPerSecond),
+            "m3/s" | "m³/s" | "cumecs" | "cubic_metres_per_second" => {
+                Ok(FlowUnit::CubicMetresPerSecond)
+            }
+            _ => Err(format!("'{}' is not a valid flow unit", s)),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FlowUnit::LitresPerSecond => "l/s",
+            FlowUnit::CubicMetresPerSecond => "m3/s",
+        }
+    }
+
+    /// Unit as written in the FDV `**UNITS` header.
+    fn header_unit(&self) -> &'static str {
+        match self {
+            FlowUnit::LitresPerSecond => "L/S",
+            FlowUnit::CubicMetresPerSecond => "M3/S",
+        }
+    }
+
+    /// Multiplier turning a calculated flow in l/s into this unit.
+    pub(crate) fn factor(&self) -> f64 {
+        match self {
+            FlowUnit::LitresPerSecond => 1.0,
+            FlowUnit::CubicMetresPerSecond => 0.001,
+        }
+    }
+
+    /// Flow field of the fixed-width record. m3/s is written to the litre (three decimals)
+    /// in a six character field, filling the 80 column record and fitting up to 99.999 m3/s.
+    fn field_spec(&self) -> FieldSpec {
+        match self {
+            FlowUnit::LitresPerSecond => FLOW_FIELDS[0],
+            FlowUnit::CubicMetresPerSecond => FieldSpec::real(6, 3),
+        }
+    }
+
+    /// Decimal places of the flow in delimited output.
+    fn delimited_decimals(&self) -> usize {
+        match self {
+            FlowUnit::LitresPerSecond => 2,
+            FlowUnit::CubicMetresPerSecond => 5,
+        }
+    }
+}
+
+/// What to do when the velocity column for a flow conversion is absent. Writing zero
+/// velocity would silently produce all-zero flow, so the default is to refuse.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum MissingVelocity {
+    #[default]
+    Error,
+    ConstantVelocity(f64),
+    DepthOnly,
+}
+
+impl MissingVelocity {
+    pub fn from_str(s: &str, velocity: Option<f64>) -> Result<Self, String> {
+        match s.to_lowercase().as_str() {
+            "error" => Ok(MissingVelocity::Error),
+            "constant" | "constant_velocity" => match velocity {
+                Some(v) if v.is_finite() && v >= 0.0 => Ok(MissingVelocity::ConstantVelocity(v)),
+                _ => Err("A non-negative constant velocity is required".to_string()),
+            },
+            "depth" | "depth_only" => Ok(MissingVelocity::DepthOnly),
+            _ => Err(format!("'{}' is not a valid missing velocity mode", s)),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MissingVelocity::Error => "error",
+            MissingVelocity::ConstantVelocity(_) => "constant",
+            MissingVelocity::DepthOnly => "depth_only",
+        }
+    }
+}
+
 pub struct FDVFlowCreator {
     timestamp_col: Option<String>,
     header_lines: Vec<String>,
@@ -37,6 +185,26 @@ pub struct FDVFlowCreator {
     depth_null_readings: usize,
     velocity_null_readings: usize,
     value_count: usize,
+    constant_velocity: Option<f64>,
+    data_status: DataStatus,
+    quality_col: Option<String>,
+    suppress_bad_quality: bool,
+    quality_flagged: usize,
+    output_style: OutputStyle,
+    comments: Vec<String>,
+    missing_velocity: MissingVelocity,
+    depth_only: bool,
+    signed_flow: bool,
+    forward_volume: f64,
+    reverse_volume: f64,
+    pad_to_full_days: bool,
+    padding_readings: usize,
+    field_specs: Vec<FieldSpec>,
+    flow_unit: FlowUnit,
+    no_data_value: Option<f64>,
+    no_data_readings: usize,
+    overwrite_policy: OverwritePolicy,
+    output_file_path: Option<PathBuf>,
 }
 
 impl FDVFlowCreator {
@@ -68,32 +236,251 @@ impl FDVFlowCreator {
             depth_null_readings: 0,
             velocity_null_readings: 0,
             value_count: 0,
+            constant_velocity: None,
+            data_status: DataStatus::Raw,
+            quality_col: None,
+            suppress_bad_quality: false,
+            quality_flagged: 0,
+            output_style: OutputStyle::FixedWidth,
+            comments: Vec::new(),
+            missing_velocity: MissingVelocity::Error,
+            depth_only: false,
+            signed_flow: false,
+            forward_volume: 0.0,
+            reverse_volume: 0.0,
+            pad_to_full_days: false,
+            padding_readings: 0,
+            field_specs: FLOW_FIELDS.to_vec(),
+            flow_unit: FlowUnit::LitresPerSecond,
+            no_data_value: None,
+            no_data_readings: 0,
+            overwrite_policy: OverwritePolicy::default(),
+            output_file_path: None,
         }
     }
     pub fn set_pipe_dia(&mut self, pipe_dia: f64) {
         self.header_lines[11] = format!("{:7.3} UNKNOWN", pipe_dia);
     }
     pub fn set_site_name(&mut self, site_name: &str) {
-        let truncated_name = if site_name.len() > 15 { &site_name[..15] } else { site_name };
-        self.header_lines[1] = format!(
-            "**IDENTIFIER:            1,{}",
-            truncated_name.to_uppercase()
-        );
+        self.header_lines[1] = format!("**IDENTIFIER:            1,{}", fdv_identifier(site_name));
     }
     pub fn set_calculator(&mut self, calculator: Box<dyn Calculator>) {
         self.calculator = Some(calculator);
     }
 
+    pub fn set_data_status(&mut self, data_status: DataStatus) {
+        self.data_status = data_status;
+    }
+
+    /// Reads per-reading quality codes from `quality_col`. With `suppress` set, readings
+    /// flagged bad are written as nulls; otherwise they are only counted.
+    pub fn set_quality_column(&mut self, quality_col: &str, suppress: bool) {
+        self.quality_col = Some(quality_col.to_string());
+        self.suppress_bad_quality = suppress;
+    }
+
+    /// Free-text lines (e.g. operator, processing date) written as comments at the start
+    /// of the constants block, after a line naming the converter version.
+    pub fn set_comment(&mut self, lines: Vec<String>) {
+        self.comments = lines;
+    }
+
+    pub fn set_missing_velocity(&mut self, missing_velocity: MissingVelocity) {
+        self.missing_velocity = missing_velocity;
+    }
+
+    /// Writes depth as the only field, 15 readings per line, for sites without velocity.
+    pub fn set_depth_only(&mut self) {
+        self.depth_only = true;
+        self.header_lines[2] = "**FIELD:                 1,DEPTH".to_string();
+        self.header_lines[3] = "**UNITS:                 1,MM".to_string();
+        self.field_specs = DEPTH_FIELDS.to_vec();
+    }
+
+    /// Writes flow in `flow_unit`, updating the `**UNITS` header and the flow field to
+    /// match. Volumes are unaffected.
+    pub fn set_flow_unit(&mut self, flow_unit: FlowUnit) {
+        self.flow_unit = flow_unit;
+        if !self.depth_only {
+            self.header_lines[3] = format!(
+                "**UNITS:                 3,{},MM,M/S",
+                flow_unit.header_unit()
+            );
+            self.field_specs[0] = flow_unit.field_spec();
+        }
+    }
+
+    pub fn flow_unit(&self) -> FlowUnit {
+        self.flow_unit
+    }
+
+    /// Writes `value` in every field of readings whose depth (or velocity, unless constant)
+    /// is missing, so gaps aren't mistaken for measured zero flow. `None` keeps writing
+    /// missing readings as zero. The value must fit the narrowest field.
+    pub fn set_no_data_value(&mut self, value: Option<f64>) -> Result<(), FDVFlowCreatorError> {
+        if let Some(value) = value {
+            let fits = self.field_specs.iter().all(|spec| spec.fits_sentinel(value));
+            if !value.is_finite() || !fits {
+                return Err(
+                    FDVFlowCreatorError::InvalidParameter(
+                        format!("No-data value {} does not fit the FDV fields", value)
+                    )
+                );
+            }
+        }
+        self.no_data_value = value;
+        Ok(())
+    }
+
+    /// Number of readings written as the no-data value.
+    pub fn get_no_data_readings(&self) -> usize {
+        self.no_data_readings
+    }
+
+    /// How velocity was obtained for the last conversion: "column", "constant" or
+    /// "depth_only".
+    pub fn velocity_source(&self) -> &'static str {
+        if self.depth_only {
+            "depth_only"
+        } else if self.constant_velocity.is_some() {
+            "constant"
+        } else {
+            "column"
+        }
+    }
+
+    pub fn data_status(&self) -> DataStatus {
+        self.data_status
+    }
+
+    fn records_per_line(&self) -> usize {
+        if self.depth_only { 15 } else { 5 }
+    }
+
+    /// Applies the missing velocity mode when no constant velocity is set and the
+    /// velocity column is absent from the DataFrame.
+    fn resolve_missing_velocity(&mut self) -> Result<(), FDVFlowCreatorError> {
+        if self.constant_velocity.is_some() || self.depth_only {
+            return Ok(());
+        }
+        let has_velocity = match (self.df.as_ref(), self.velocity_col.as_deref()) {
+            (Some(df), Some(velocity_col)) => {
+                df.get_column_names()
+                    .iter()
+                    .any(|&col| col == velocity_col)
+            }
+            _ => false,
+        };
+        if has_velocity {
+            return Ok(());
+        }
+
+        match self.missing_velocity {
+            MissingVelocity::Error => {
+                return Err(
+                    FDVFlowCreatorError::InvalidParameter(
+                        "Velocity column not found. Choose a constant velocity or depth-only \
+                         output rather than writing zero flow."
+                            .to_string()
+                    )
+                );
+            }
+            MissingVelocity::ConstantVelocity(velocity) => {
+                log::warn!("Velocity column not found. Using a constant {} m/s.", velocity);
+                self.set_constant_velocity(velocity);
+                self.data_status = self.data_status.max(DataStatus::Estimated);
+            }
+            MissingVelocity::DepthOnly => {
+                log::warn!("Velocity column not found. Writing depth only.");
+                self.set_depth_only();
+            }
+        }
+        Ok(())
+    }
+
+    /// Keeps the sign of negative velocities so reverse flow is written as negative l/s.
+    /// Otherwise the calculators clamp reverse flow to zero.
+    pub fn set_signed_flow(&mut self, signed_flow: bool) {
+        self.signed_flow = signed_flow;
+    }
+
+    /// Forward and reverse volume in m³ written by the last conversion. Reverse volume is
+    /// a magnitude and is only non-zero with signed flow.
+    pub fn get_volumes(&self) -> (f64, f64) {
+        (self.forward_volume, self.reverse_volume)
+    }
+
+    /// Extends the record to start at midnight and end at the last interval of its final
+    /// day. Added readings are written as nulls (zero) and counted as null readings.
+    pub fn set_pad_to_full_days(&mut self, pad_to_full_days: bool) {
+        self.pad_to_full_days = pad_to_full_days;
+    }
+
+    /// Number of readings added by `set_pad_to_full_days`.
+    pub fn get_padding_readings(&self) -> usize {
+        self.padding_readings
+    }
+
+    fn pad_record(&mut self) -> Result<(), FDVFlowCreatorError> {
+        if !self.pad_to_full_days {
+            return Ok(());
+        }
+        let (Some(df), Some(timestamp_col), Some(start), Some(end), Some(interval)) = (
+            self.df.as_ref(),
+            self.timestamp_col.as_deref(),
+            self.start_ts,
+            self.end_ts,
+            self.interval,
+        ) else {
+            return Ok(());
+        };
+        let (padded, start, end, added) = pad_to_full_days(
+            df,
+            timestamp_col,
+            start,
+            end,
+            interval
+        )?;
+        self.df = Some(padded);
+        self.start_ts = Some(start);
+        self.end_ts = Some(end);
+        self.padding_readings = added;
+        Ok(())
+    }
+
+    pub fn set_output_style(&mut self, output_style: OutputStyle) {
+        self.output_style = output_style;
+    }
+
+    /// Uses a fixed velocity for every reading instead of the velocity column. The
+    /// resulting flow is an estimate.
+    pub fn set_constant_velocity(&mut self, velocity: f64) {
+        self.constant_velocity = Some(velocity);
+    }
+
     pub fn set_dataframe(&mut self, df: DataFrame) {
         self.df = Some(df);
     }
 
+    /// Decides what `open_output_file` does if the file already exists. Set it before
+    /// `set_parameters`, which opens the file.
+    pub fn set_overwrite_policy(&mut self, overwrite_policy: OverwritePolicy) {
+        self.overwrite_policy = overwrite_policy;
+    }
+
     pub fn open_output_file(&mut self, output_file: &str) -> Result<(), FDVFlowCreatorError> {
-        let file = File::create(Path::new(output_file))?;
+        let path = resolve_output_path(output_file, self.overwrite_policy)?;
+        let file = File::create(&path)?;
         self.output_file = Some(BufWriter::new(file));
+        self.output_file_path = Some(path);
         Ok(())
     }
 
+    /// Path actually written, which differs from the requested one after a rename.
+    pub fn output_file_path(&self) -> Option<&Path> {
+        self.output_file_path.as_deref()
+    }
+
     pub fn set_starting_time(&mut self, starting_time: &str) -> Result<(), FDVFlowCreatorError> {
         self.start_ts = Some(NaiveDateTime::parse_from_str(starting_time, "%Y-%m-%d %H:%M:%S")?);
         Ok(())
@@ -109,11 +496,39 @@ impl FDVFlowCreator {
     }
 
     fn write_header(&mut self) -> io::Result<()> {
+        if let Some(sep) = self.output_style.separator() {
+            if let Some(ref mut writer) = self.output_file {
+                if self.depth_only {
+                    writeln!(writer, "Timestamp{}Depth(mm)", sep)?;
+                } else {
+                    writeln!(
+                        writer,
+                        "Timestamp{0}Flow({1}){0}Depth(mm){0}Velocity(m/s)",
+                        sep,
+                        self.flow_unit.as_str()
+                    )?;
+                }
+            }
+            return Ok(());
+        }
+        let records_per_line = self.records_per_line();
+        self.header_lines[4] = format_line(&self.field_specs, records_per_line);
+        self.header_lines[5] = record_length_line(&self.field_specs, records_per_line);
         if let Some(ref mut writer) = self.output_file {
-            for line in &self.header_lines {
+            for (i, line) in self.header_lines.iter().enumerate() {
                 writeln!(writer, "{}", line)?;
+                if i == 1 {
+                    writeln!(writer, "{}", self.data_status.header_line())?;
+                }
+                if line == "*CSTART" {
+                    for comment in comment_lines(&self.comments) {
+                        writeln!(writer, "{}", comment)?;
+                    }
+                }
             }
             let interval_in_minutes = self.interval.unwrap();
+            // FDV headers are GMT. Series built with a processor timezone are already UTC;
+            // otherwise the logger clock is assumed to be GMT and written unchanged.
             let start_str = self.start_ts.unwrap().format("%Y%m%d%H%M").to_string();
             let end_str = self.end_ts.unwrap().format("%Y%m%d%H%M").to_string();
             writeln!(writer, "{} {}   {}", start_str, end_str, interval_in_minutes)?;
@@ -123,16 +538,74 @@ impl FDVFlowCreator {
     }
 
     fn write_tail(&mut self) -> io::Result<()> {
+        if self.output_style != OutputStyle::FixedWidth {
+            return Ok(());
+        }
         if let Some(ref mut writer) = self.output_file {
             writeln!(writer, "\n*END")?;
         }
         Ok(())
     }
 
-    fn write_output(&mut self, depth: f64, velocity: f64, result: f64) -> io::Result<()> {
+    fn write_output(
+        &mut self,
+        depth: f64,
+        velocity: f64,
+        result: f64,
+        missing: bool
+    ) -> io::Result<()> {
+        let no_data = self.no_data_value.filter(|_| missing);
+        if no_data.is_some() {
+            self.no_data_readings += 1;
+        }
+        if let Some(sep) = self.output_style.separator() {
+            // Readings are evenly spaced from the start, as in the FDV layout
+            let offset = chrono::Duration::minutes(
+                self.interval.unwrap_or(0) * ((self.value_count as i64) - 1)
+            );
+            let timestamp = self.start_ts.map(|start| start + offset).unwrap_or_default();
+            if let Some(ref mut writer) = self.output_file {
+                let timestamp = timestamp.format("%Y-%m-%d %H:%M:%S");
+                if let Some(no_data) = no_data {
+                    let fields = if self.depth_only { 1 } else { 3 };
+                    write!(writer, "{}", timestamp)?;
+                    for _ in 0..fields {
+                        write!(writer, "{}{}", sep, no_data)?;
+                    }
+                    writeln!(writer)?;
+                } else if self.depth_only {
+                    writeln!(writer, "{}{}{:.0}", timestamp, sep, (depth * 1000.0).round())?;
+                } else {
+                    writeln!(
+                        writer,
+                        "{1}{0}{2:.5$}{0}{3:.0}{0}{4:.2}",
+                        sep,
+                        timestamp,
+                        result,
+                        (depth * 1000.0).round(),
+                        velocity,
+                        self.flow_unit.delimited_decimals()
+                    )?;
+                }
+            }
+            self.value_count += 1;
+            return Ok(());
+        }
+        let records_per_line = self.records_per_line();
         if let Some(ref mut writer) = self.output_file {
-            write!(writer, "{:5.0}{:5.0}{:5.2}", result, (depth * 1000.0).round(), velocity)?;
-            if self.value_count % 5 == 0 {
+            let depth_mm = (depth * 1000.0).round();
+            if let Some(no_data) = no_data {
+                for spec in &self.field_specs {
+                    spec.write_sentinel(writer, no_data)?;
+                }
+            } else if self.depth_only {
+                self.field_specs[0].write(writer, depth_mm)?;
+            } else {
+                self.field_specs[0].write(writer, result)?;
+                self.field_specs[1].write(writer, depth_mm)?;
+                self.field_specs[2].write(writer, velocity)?;
+            }
+            if self.value_count % records_per_line == 0 {
                 writeln!(writer)?;
             }
             self.value_count += 1;
@@ -178,17 +651,45 @@ impl FDVFlowCreator {
         }
 
         if
+            self.constant_velocity.is_none() &&
             !df
                 .get_column_names()
                 .iter()
                 .any(|&col| col == velocity_col)
         {
-            log::warn!("Velocity column '{}' not found. Using 0.0 for all values.", velocity_col);
+            if !self.depth_only {
+                log::warn!(
+                    "Velocity column '{}' not found. Using 0.0 for all values.",
+                    velocity_col
+                );
+            }
             df.with_column(Series::new(velocity_col.into(), vec![0.0f64; df.height()]))?;
         }
 
+        // Readings without a depth, or a velocity when one is read, before nulls are zeroed
+        let is_missing = |s: &Series| -> PolarsResult<Vec<bool>> {
+            Ok(
+                s
+                    .cast(&DataType::Float64)?
+                    .f64()?
+                    .into_iter()
+                    .map(|v| v.is_none_or(|v| v.is_nan()))
+                    .collect()
+            )
+        };
+        let mut missing = is_missing(df.column(depth_col)?)?;
+        if self.constant_velocity.is_none() && !self.depth_only {
+            let velocity_missing = is_missing(df.column(velocity_col)?)?;
+            for (missing, velocity) in missing.iter_mut().zip(velocity_missing) {
+                *missing |= velocity;
+            }
+        }
+
         self.depth_null_readings = df.column(depth_col)?.null_count();
-        self.velocity_null_readings = df.column(velocity_col)?.null_count();
+        self.velocity_null_readings = match self.constant_velocity {
+            Some(_) => 0,
+            None => df.column(velocity_col)?.null_count(),
+        };
 
         // Handle the Result inside the closure
         df.apply(depth_col, |s| {
@@ -197,15 +698,19 @@ impl FDVFlowCreator {
                 Err(_) => s.clone(),
             }
         })?;
-        df.apply(velocity_col, |s| {
-            match s.fill_null(FillNullStrategy::Zero) {
-                Ok(filled) => filled,
-                Err(_) => s.clone(),
+        let velocity_series = match self.constant_velocity {
+            Some(velocity) => Series::new(velocity_col.into(), vec![velocity; df.height()]),
+            None => {
+                df.apply(velocity_col, |s| {
+                    match s.fill_null(FillNullStrategy::Zero) {
+                        Ok(filled) => filled,
+                        Err(_) => s.clone(),
+                    }
+                })?;
+                df.column(velocity_col)?.clone()
             }
-        })?;
-
+        };
         let depth_series = df.column(depth_col)?.clone();
-        let velocity_series = df.column(velocity_col)?.clone();
 
         let calculator = self.calculator
             .as_ref()
@@ -213,25 +718,34 @@ impl FDVFlowCreator {
                 FDVFlowCreatorError::InvalidParameter("Calculator not set".to_string())
             })?;
 
+        // Whole-number logs are read as integers; the calculations need floats
         let depth_values: Vec<f64> = depth_series
+            .cast(&DataType::Float64)?
             .f64()?
             .into_iter()
             .map(|v| v.unwrap_or(0.0))
             .collect();
         let velocity_values: Vec<f64> = velocity_series
+            .cast(&DataType::Float64)?
             .f64()?
             .into_iter()
             .map(|v| v.unwrap_or(0.0))
             .collect();
 
+        let signed_flow = self.signed_flow;
         let results: Vec<_> = depth_values
             .iter()
             .zip(velocity_values.iter())
             .map(|(&depth, &velocity)| {
-                let depth = if depth_col.contains("mm") { depth / 1000.0 } else { depth };
+                let depth = if is_depth_in_mm(depth_col) { depth / 1000.0 } else { depth };
 
                 if depth == 0.0 || velocity == 0.0 {
                     Ok((depth, velocity, 0.0))
+                } else if signed_flow && velocity < 0.0 {
+                    // Calculators work on magnitudes, so reverse flow is negated afterwards
+                    calculator
+                        .perform_calculation(depth, -velocity)
+                        .map(|result| (depth, velocity, -result))
                 } else {
                     calculator
                         .perform_calculation(depth, velocity)
@@ -240,11 +754,20 @@ impl FDVFlowCreator {
             })
             .collect::<Result<Vec<_>, _>>()?;
 
-        for (depth, velocity, result) in results {
-            self.write_output(depth, velocity, result)?;
+        let seconds = (self.interval.unwrap_or(0) * 60) as f64;
+        for ((depth, velocity, result), missing) in results.into_iter().zip(missing) {
+            let volume = (result * seconds) / 1000.0;
+            if volume < 0.0 {
+                self.reverse_volume -= volume;
+            } else if volume > 0.0 {
+                self.forward_volume += volume;
+            }
+            self.write_output(depth, velocity, result * self.flow_unit.factor(), missing)?;
         }
 
-        if self.value_count % 5 != 0 {
+        let records_per_line = self.records_per_line();
+        let partial_line = self.value_count % records_per_line != 0;
+        if self.output_style == OutputStyle::FixedWidth && partial_line {
             if let Some(ref mut writer) = self.output_file {
                 writeln!(writer)?;
             }
@@ -256,6 +779,36 @@ impl FDVFlowCreator {
         (self.depth_null_readings, self.velocity_null_readings)
     }
 
+    /// Number of readings nulled because their quality flag was bad.
+    pub fn get_quality_suppressed(&self) -> usize {
+        if self.suppress_bad_quality { self.quality_flagged } else { 0 }
+    }
+
+    /// Suppressing readings is an edit, so the header status is raised to at least EDITED.
+    fn apply_quality_flags(&mut self) -> Result<(), FDVFlowCreatorError> {
+        let (Some(df), Some(quality_col)) = (self.df.as_mut(), self.quality_col.as_deref()) else {
+            return Ok(());
+        };
+        let value_cols: Vec<&str> = [self.depth_col.as_deref(), self.velocity_col.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect();
+        self.quality_flagged = apply_quality_flags(
+            df,
+            quality_col,
+            &value_cols,
+            self.suppress_bad_quality
+        )?;
+        if self.quality_flagged > 0 {
+            if self.suppress_bad_quality {
+                self.data_status = self.data_status.max(DataStatus::Edited);
+            } else {
+                log::warn!("{} readings flagged bad were kept", self.quality_flagged);
+            }
+        }
+        Ok(())
+    }
+
     pub fn validate_parameters(&self) -> Result<(), &'static str> {
         if self.start_ts.is_none() {
             return Err("Starting time is not set. Use set_starting_time() method.");
@@ -266,6 +819,9 @@ impl FDVFlowCreator {
         if self.interval.is_none() {
             return Err("Interval is not set. Use set_interval() method.");
         }
+        if self.interval.is_some_and(|interval| interval <= 0) {
+            return Err("Interval must be greater than zero minutes.");
+        }
         if self.output_file.is_none() {
             return Err("Output file is not set. Use open_output_file() method.");
         }
@@ -275,6 +831,17 @@ impl FDVFlowCreator {
         Ok(())
     }
 
+    fn check_data_interval(&self) -> Result<(), FDVFlowCreatorError> {
+        let (Some(df), Some(timestamp_col), Some(interval)) = (
+            self.df.as_ref(),
+            self.timestamp_col.as_deref().filter(|c| !c.is_empty()),
+            self.interval,
+        ) else {
+            return Ok(());
+        };
+        check_interval(df, timestamp_col, interval).map_err(FDVFlowCreatorError::InvalidParameter)
+    }
+
     pub fn set_parameters(
         &mut self,
         df: DataFrame,
@@ -300,74 +867,17 @@ impl FDVFlowCreator {
 
         self.depth_col = Some(col_names["depth"].clone());
         self.timestamp_col = Some(col_names["timestamp"].clone());
-        self.velocity_col = col_names.get("velocity_col").cloned();
+        self.velocity_col = col_names
+            .get("velocity")
+            .or_else(|| col_names.get("velocity_col"))
+            .cloned();
 
         self.set_pipe_dia(-1.0);
 
-        let calculator: Box<dyn Calculator> = match pipe_type {
-            "Circular" => {
-                if !pipe_size_param.is_empty() {
-                    let pipe_size = pipe_size_param.parse::<f64>()? / 1000.0;
-                    self.set_pipe_dia(pipe_size);
-                    Box::new(CircularCalculator::new(pipe_size / 2.0)?)
-                } else {
-                    Box::new(CircularCalculator::new(-0.5)?) // Use -0.5 to indicate invalid radius
-                }
-            }
-            "Rectangular" => {
-                if !pipe_size_param.is_empty() {
-                    let pipe_size = pipe_size_param.parse::<f64>()? / 1000.0;
-                    self.set_pipe_dia(pipe_size);
-                    Box::new(RectangularCalculator::new(pipe_size)?)
-                } else {
-                    Box::new(RectangularCalculator::new(-1.0)?) // Use -1.0 to indicate invalid size
-                }
-            }
-            "Egg Type 1" => {
-                if !pipe_size_param.is_empty() {
-                    let egg_params: Vec<f64> = pipe_size_param
-                        .split(',')
-                        .map(|s| s.parse::<f64>().unwrap())
-                        .collect();
-                    Box::new(Egg1Calculator::new(egg_params[0], egg_params[1], egg_params[2])?)
-                } else {
-                    Box::new(Egg1Calculator::new(-1.0, -1.0, -1.0)?) // Use -1.0 to indicate invalid params
-                }
-            }
-            "Egg Type 2a" => {
-                if !pipe_size_param.is_empty() {
-                    let egg_params: Vec<f64> = pipe_size_param
-                        .split(',')
-                        .map(|s| s.parse::<f64>().unwrap())
-                        .collect();
-                    Box::new(Egg2ACalculator::new(egg_params[0], egg_params[1], egg_params[2])?)
-                } else {
-                    Box::new(Egg2ACalculator::new(-1.0, -1.0, -1.0)?) // Use -1.0 to indicate invalid params
-                }
-            }
-            "Egg Type 2" => {
-                if !pipe_size_param.is_empty() {
-                    let egg_height = pipe_size_param.parse::<f64>()?;
-                    Box::new(Egg2Calculator::new(egg_height)?)
-                } else {
-                    Box::new(Egg2Calculator::new(-1.0)?) // Use -1.0 to indicate invalid height
-                }
-            }
-            "Two Circles and a Rectangle" => {
-                if !pipe_size_param.is_empty() {
-                    let params: Vec<f64> = pipe_size_param
-                        .split(',')
-                        .map(|s| s.parse::<f64>().unwrap())
-                        .collect();
-                    Box::new(TwoCircleAndRectangleCalculator::new(params[1], params[0])?)
-                } else {
-                    Box::new(TwoCircleAndRectangleCalculator::new(-1.0, -1.0)?) // Use -1.0 to indicate invalid params
-                }
-            }
-            _ => {
-                return Err(format!("Unsupported pipe type: {}", pipe_type).into());
-            }
-        };
+        let (calculator, pipe_dia) = create_calculator(pipe_type, pipe_size_param)?;
+        if let Some(pipe_dia) = pipe_dia {
+            self.set_pipe_dia(pipe_dia);
+        }
 
         self.set_calculator(calculator);
 
@@ -378,6 +888,12 @@ impl FDVFlowCreator {
         self
             .validate_parameters()
             .map_err(|e| FDVFlowCreatorError::InvalidParameter(e.to_string()))?;
+        self.check_data_interval()?;
+        self.pad_record()?;
+        self.apply_quality_flags()?;
+        self.resolve_missing_velocity()?;
+        // Depth only output has its own field layout, which the no-data value must fit too
+        self.set_no_data_value(self.no_data_value)?;
 
         self.write_header()?;
 
@@ -400,3 +916,190 @@ impl FDVFlowCreator {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fdv::fdv_reader::FdvReader;
+
+    const READINGS: usize = 8;
+
+    /// 15 minute readings from midnight with the given depths (m) and, optionally, a
+    /// constant 0.5 m/s velocity column.
+    fn readings(depths: Vec<Option<f64>>, with_velocity: bool) -> DataFrame {
+        let start = NaiveDateTime::parse_from_str("2024-01-01 00:00:00", "%Y-%m-%d %H:%M:%S")
+            .unwrap();
+        let timestamps: Vec<NaiveDateTime> = (0..depths.len() as i64)
+            .map(|i| start + chrono::Duration::minutes(15 * i))
+            .collect();
+        let mut columns = vec![
+            Series::new("Timestamp".into(), timestamps),
+            Series::new("Depth".into(), &depths)
+        ];
+        if with_velocity {
+            columns.push(Series::new("Velocity".into(), vec![0.5; depths.len()]));
+        }
+        DataFrame::new(columns).unwrap()
+    }
+
+    /// Writes a 15 minute flow FDV for a 300 mm circular pipe to a temp file, letting the
+    /// test configure the creator first, and returns the path.
+    fn write_fdv(name: &str, configure: impl FnOnce(&mut FDVFlowCreator)) -> String {
+        let depths = (0..READINGS).map(|i| Some(0.1 + (i as f64) * 0.01)).collect();
+        let df = readings(depths, true);
+        let col_names = HashMap::from([
+            ("timestamp".to_string(), "Timestamp".to_string()),
+            ("depth".to_string(), "Depth".to_string()),
+            ("velocity".to_string(), "Velocity".to_string()),
+        ]);
+        let path = std::env
+            ::temp_dir()
+            .join(format!("fdv_creator_{}_{}.fdv", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let path = path.to_string_lossy().to_string();
+
+        let mut creator = FDVFlowCreator::new();
+        creator
+            .set_parameters(
+                df,
+                "SITE1",
+                "2024-01-01 00:00:00",
+                "2024-01-01 01:45:00",
+                15,
+                &path,
+                &col_names,
+                "Circular",
+                "300"
+            )
+            .unwrap();
+        configure(&mut creator);
+        creator.create_fdv_flow().unwrap();
+        path
+    }
+
+    #[test]
+    fn zero_interval_fails_validation() {
+        let mut creator = FDVFlowCreator::new();
+        creator.set_starting_time("2024-01-01 00:00:00").unwrap();
+        creator.set_ending_time("2024-01-02 00:00:00").unwrap();
+        creator.set_interval(0);
+        assert_eq!(
+            creator.validate_parameters(),
+            Err("Interval must be greater than zero minutes.")
+        );
+    }
+
+    #[test]
+    fn comments_are_written_and_skipped_when_read_back() {
+        let path = write_fdv("comments", |creator| {
+            creator.set_comment(
+                vec!["Operator: J Smith".to_string(), "202301010000 202301020000 5".to_string()]
+            );
+        });
+        let reader = FdvReader::open(&path).unwrap();
+        let lines = &reader.header().lines;
+        let cstart = lines
+            .iter()
+            .position(|l| l == "*CSTART")
+            .unwrap();
+        assert!(lines[cstart + 1].starts_with("* Created by"));
+        assert_eq!(lines[cstart + 2], "* Operator: J Smith");
+        assert_eq!(reader.header().interval_minutes, 15);
+        assert_eq!(reader.header().expected_records(), READINGS);
+
+        let summary = reader.summarize().unwrap();
+        assert_eq!(summary.record_count, READINGS);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn record_length_header_matches_the_data_lines() {
+        let path = write_fdv("record_length", |_| {});
+        let content = std::fs::read_to_string(&path).unwrap();
+        let record_length: usize = content
+            .lines()
+            .find_map(|line| line.strip_prefix("**RECORD_LENGTH:"))
+            .and_then(|value| value.trim().strip_prefix("I2,"))
+            .unwrap()
+            .parse()
+            .unwrap();
+        let first_data_line = content
+            .lines()
+            .skip_while(|line| !line.starts_with("*CEND"))
+            .nth(1)
+            .unwrap();
+        assert_eq!(first_data_line.len(), record_length);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn cubic_metres_per_second_keeps_litre_precision() {
+        let path = write_fdv("m3s", |creator| {
+            creator.set_flow_unit(FlowUnit::CubicMetresPerSecond);
+        });
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert!(lines.contains(&"**UNITS:                 3,M3/S,MM,M/S"));
+        assert!(lines.contains(&"**FORMAT:                4,F6.3,I5,F5,[5]"));
+        assert!(lines.contains(&"**RECORD_LENGTH:         I2,80"));
+
+        let (calculator, _) = create_calculator("Circular", "300").unwrap();
+        let flow = calculator.perform_calculation(0.1, 0.5).unwrap() / 1000.0;
+        let first_data_line = lines
+            .iter()
+            .skip_while(|line| !line.starts_with("*CEND"))
+            .nth(1)
+            .unwrap();
+        assert_eq!(first_data_line.len(), 80);
+        assert_eq!(&first_data_line[..6], format!("{:>6.3}", flow));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn litres_per_second_header_is_unchanged() {
+        let path = write_fdv("lps", |creator| creator.set_flow_unit(FlowUnit::LitresPerSecond));
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert!(lines.contains(&"**UNITS:                 3,L/S,MM,M/S"));
+        assert!(lines.contains(&"**FORMAT:                3,2I5,F5,[5]"));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    fn first_data_line(path: &str) -> String {
+        std::fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .skip_while(|line| !line.starts_with("*CEND"))
+            .nth(1)
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn missing_readings_are_written_as_the_no_data_value() {
+        let path = write_fdv("no_data", |creator| {
+            let depths = vec![Some(0.1), Some(0.0), None, Some(0.1), Some(0.1)];
+            creator.set_dataframe(readings(depths, true));
+            creator.set_ending_time("2024-01-01 01:00:00").unwrap();
+            creator.set_no_data_value(Some(-9999.0)).unwrap();
+        });
+        let line = first_data_line(&path);
+        // A measured zero depth stays zero flow; only the gap gets the sentinel
+        assert_eq!(&line[15..30], "    0    0 0.50");
+        assert_eq!(&line[30..45], "-9999-9999-9999");
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn no_data_value_is_written_in_depth_only_output() {
+        let path = write_fdv("no_data_depth_only", |creator| {
+            let depths = vec![Some(0.1), None, Some(0.1)];
+            creator.set_dataframe(readings(depths, false));
+            creator.set_ending_time("2024-01-01 00:30:00").unwrap();
+            creator.set_missing_velocity(MissingVelocity::DepthOnly);
+            creator.set_no_data_value(Some(-9999.0)).unwrap();
+        });
+        assert_eq!(first_data_line(&path), "  100-9999  100");
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,308 @@
+use chrono::{Duration, NaiveDateTime};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Lines};
+use std::path::Path;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum FdvReaderError {
+    #[error("IO error: {0}")]
+    IoError(#[from] io::Error),
+    #[error("Invalid header: {0}")]
+    InvalidHeader(String),
+    #[error("Parse error: {0}")]
+    ParseError(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct FdvHeader {
+    pub identifier: String,
+    pub fields: Vec<String>,
+    pub units: Vec<String>,
+    pub field_widths: Vec<usize>,
+    pub records_per_line: usize,
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub interval_minutes: i64,
+    pub lines: Vec<String>,
+}
+
+impl FdvHeader {
+    pub fn expected_records(&self) -> usize {
+        if self.interval_minutes <= 0 || self.end < self.start {
+            return 0;
+        }
+        ((self.end - self.start).num_minutes() / self.interval_minutes) as usize + 1
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FieldStats {
+    pub count: usize,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub sum: f64,
+}
+
+impl FieldStats {
+    fn add(&mut self, value: f64) {
+        self.count += 1;
+        self.sum += value;
+        self.min = Some(self.min.map_or(value, |m| m.min(value)));
+        self.max = Some(self.max.map_or(value, |m| m.max(value)));
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        if self.count > 0 {
+            Some(self.sum / self.count as f64)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FdvSummary {
+    pub header: FdvHeader,
+    pub record_count: usize,
+    pub field_stats: Vec<FieldStats>,
+}
+
+/// Reads an FDV file one line at a time. The header is parsed up front and the packed
+/// value lines are then yielded as `(timestamp, values)` records.
+pub struct FdvReader {
+    header: FdvHeader,
+    lines: Lines<BufReader<File>>,
+    pending: Vec<f64>,
+    next_ts: NaiveDateTime,
+    finished: bool,
+}
+
+impl FdvReader {
+    pub fn open(path: &str) -> Result<Self, FdvReaderError> {
+        let file = File::open(Path::new(path))?;
+        let mut lines = BufReader::new(file).lines();
+        let header = Self::parse_header(&mut lines)?;
+        let next_ts = header.start;
+
+        Ok(FdvReader {
+            header,
+            lines,
+            pending: Vec::new(),
+            next_ts,
+            finished: false,
+        })
+    }
+
+    pub fn header(&self) -> &FdvHeader {
+        &self.header
+    }
+
+    fn parse_header(lines: &mut Lines<BufReader<File>>) -> Result<FdvHeader, FdvReaderError> {
+        let mut header_lines = Vec::new();
+        let mut constant_lines = Vec::new();
+        let mut in_constants = false;
+
+        for line in lines.by_ref() {
+            let line = line?;
+            let trimmed = line.trim();
+            header_lines.push(line.clone());
+
+            if trimmed.starts_with("*CEND") {
+                break;
+            } else if trimmed.starts_with("*CSTART") {
+                in_constants = true;
+            } else if in_constants && !trimmed.starts_with('*') {
+                constant_lines.push(trimmed.to_string());
+            }
+        }
+
+        if !header_lines.iter().any(|l| l.trim().starts_with("*CEND")) {
+            return Err(FdvReaderError::InvalidHeader("missing *CEND line".to_string()));
+        }
+
+        let identifier = Self::header_values(&header_lines, "**IDENTIFIER:")
+            .first()
+            .cloned()
+            .unwrap_or_default();
+        let fields = Self::header_values(&header_lines, "**FIELD:");
+        let units = Self::header_values(&header_lines, "**UNITS:");
+        let format = Self::header_values(&header_lines, "**FORMAT:");
+        let (field_widths, records_per_line) = Self::parse_format(&format)?;
+
+        if fields.len() != field_widths.len() {
+            return Err(FdvReaderError::InvalidHeader(format!(
+                "{} fields declared but format describes {}",
+                fields.len(),
+                field_widths.len()
+            )));
+        }
+
+        let (start, end, interval_minutes) = constant_lines
+            .iter()
+            .rev()
+            .find_map(|line| Self::parse_time_constants(line))
+            .ok_or_else(|| {
+                FdvReaderError::InvalidHeader("start/end/interval constants not found".to_string())
+            })?;
+
+        Ok(FdvHeader {
+            identifier,
+            fields,
+            units,
+            field_widths,
+            records_per_line,
+            start,
+            end,
+            interval_minutes,
+            lines: header_lines,
+        })
+    }
+
+    /// Returns the comma-separated values of a `**KEY:  n,a,b` line, without the count.
+    fn header_values(header_lines: &[String], key: &str) -> Vec<String> {
+        header_lines
+            .iter()
+            .find(|l| l.starts_with(key))
+            .map(|l| {
+                l[key.len()..]
+                    .trim()
+                    .split(',')
+                    .skip(1)
+                    .map(|v| v.trim().to_string())
+                    .filter(|v| !v.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Parses a format such as `2I5,F5,[5]` into per-field widths and records per line.
+    fn parse_format(format: &[String]) -> Result<(Vec<usize>, usize), FdvReaderError> {
+        let mut widths = Vec::new();
+        let mut records_per_line = 1;
+
+        for token in format {
+            if let Some(count) = token.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+                records_per_line = count.parse().map_err(|_| {
+                    FdvReaderError::InvalidHeader(format!("invalid record count: {}", token))
+                })?;
+                continue;
+            }
+
+            let repeat_end = token.find(|c: char| !c.is_ascii_digit()).unwrap_or(0);
+            let repeat = if repeat_end > 0 { token[..repeat_end].parse().unwrap_or(1) } else { 1 };
+            let width_str: String = token[repeat_end..]
+                .chars()
+                .skip_while(|c| c.is_ascii_alphabetic())
+                .take_while(|c| c.is_ascii_digit())
+                .collect();
+            let width: usize = width_str.parse().map_err(|_| {
+                FdvReaderError::InvalidHeader(format!("invalid field format: {}", token))
+            })?;
+            widths.extend(std::iter::repeat(width).take(repeat));
+        }
+
+        if widths.is_empty() {
+            return Err(FdvReaderError::InvalidHeader("no field formats found".to_string()));
+        }
+
+        Ok((widths, records_per_line))
+    }
+
+    fn parse_time_constants(line: &str) -> Option<(NaiveDateTime, NaiveDateTime, i64)> {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 3 {
+            return None;
+        }
+        let start = NaiveDateTime::parse_from_str(parts[0], "%Y%m%d%H%M").ok()?;
+        let end = NaiveDateTime::parse_from_str(parts[1], "%Y%m%d%H%M").ok()?;
+        let interval = parts[2].parse().ok()?;
+        Some((start, end, interval))
+    }
+
+    /// Splits a packed value line into numbers using the fixed field widths.
+    fn parse_value_line(&self, line: &str) -> Result<Vec<f64>, FdvReaderError> {
+        let mut values = Vec::new();
+        let mut pos = 0;
+        let mut field = 0;
+
+        while pos < line.len() {
+            let width = self.header.field_widths[field % self.header.field_widths.len()];
+            let end = (pos + width).min(line.len());
+            let chunk = line.get(pos..end).unwrap_or("").trim();
+            if !chunk.is_empty() {
+                let value = chunk.parse::<f64>().map_err(|_| {
+                    FdvReaderError::ParseError(format!("invalid value '{}'", chunk))
+                })?;
+                values.push(value);
+            } else if end < line.len() {
+                values.push(0.0);
+            }
+            pos = end;
+            field += 1;
+        }
+
+        Ok(values)
+    }
+
+    /// Streams every record, keeping only per-field statistics in memory.
+    pub fn summarize(mut self) -> Result<FdvSummary, FdvReaderError> {
+        let mut field_stats = vec![FieldStats::default(); self.header.fields.len()];
+        let mut record_count = 0;
+
+        for record in self.by_ref() {
+            let (_, values) = record?;
+            for (stats, value) in field_stats.iter_mut().zip(values) {
+                stats.add(value);
+            }
+            record_count += 1;
+        }
+
+        Ok(FdvSummary {
+            header: self.header,
+            record_count,
+            field_stats,
+        })
+    }
+}
+
+impl Iterator for FdvReader {
+    type Item = Result<(NaiveDateTime, Vec<f64>), FdvReaderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let field_count = self.header.fields.len();
+
+        while self.pending.len() < field_count && !self.finished {
+            match self.lines.next() {
+                Some(Ok(line)) => {
+                    if line.trim().starts_with("*END") {
+                        self.finished = true;
+                    } else if !line.trim().is_empty() {
+                        match self.parse_value_line(&line) {
+                            Ok(values) => self.pending.extend(values),
+                            Err(e) => {
+                                self.finished = true;
+                                return Some(Err(e));
+                            }
+                        }
+                    }
+                }
+                Some(Err(e)) => {
+                    self.finished = true;
+                    return Some(Err(e.into()));
+                }
+                None => self.finished = true,
+            }
+        }
+
+        if self.pending.len() < field_count || field_count == 0 {
+            return None;
+        }
+
+        let values: Vec<f64> = self.pending.drain(..field_count).collect();
+        let timestamp = self.next_ts;
+        self.next_ts += Duration::minutes(self.header.interval_minutes);
+
+        Some(Ok((timestamp, values)))
+    }
+}
@@ -0,0 +1,105 @@
+use std::io::{ self, Write };
+
+/// Width and precision of one packed FDV field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldSpec {
+    pub width: usize,
+    /// Decimal places written; zero writes the field as an integer (`I`).
+    pub decimals: usize,
+    /// Whether the header format states the decimals (`F15.1`) or only the width (`F5`).
+    pub format_decimals: bool,
+}
+
+impl FieldSpec {
+    pub const fn integer(width: usize) -> Self {
+        FieldSpec { width, decimals: 0, format_decimals: false }
+    }
+
+    pub const fn real(width: usize, decimals: usize) -> Self {
+        FieldSpec { width, decimals, format_decimals: true }
+    }
+
+    /// Format code for the header, e.g. `I5`, `F5` or `F15.1`.
+    fn code(&self) -> String {
+        if self.decimals == 0 {
+            format!("I{}", self.width)
+        } else if self.format_decimals {
+            format!("F{}.{}", self.width, self.decimals)
+        } else {
+            format!("F{}", self.width)
+        }
+    }
+
+    /// Whether a sentinel such as `-9999` fits the field as written by `write_sentinel`.
+    pub fn fits_sentinel(&self, value: f64) -> bool {
+        value.to_string().len() <= self.width
+    }
+
+    /// Writes a no-data sentinel right-aligned and as given, ignoring the field's decimals
+    /// so that e.g. `-9999` still fits a five character `F5` field.
+    pub fn write_sentinel(&self, writer: &mut impl Write, value: f64) -> io::Result<()> {
+        write!(writer, "{:>width$}", value, width = self.width)
+    }
+
+    /// Writes `value` right-aligned in the field.
+    pub fn write(&self, writer: &mut impl Write, value: f64) -> io::Result<()> {
+        write!(writer, "{:>width$.decimals$}", value, width = self.width, decimals = self.decimals)
+    }
+}
+
+/// Characters in one data line holding `records_per_line` records of `fields`.
+pub fn record_length(fields: &[FieldSpec], records_per_line: usize) -> usize {
+    fields
+        .iter()
+        .map(|field| field.width)
+        .sum::<usize>() * records_per_line
+}
+
+/// `**FORMAT:` header line, grouping adjacent identical fields, e.g. `3,2I5,F5,[5]`.
+pub fn format_line(fields: &[FieldSpec], records_per_line: usize) -> String {
+    let mut groups: Vec<(usize, String)> = Vec::new();
+    for code in fields.iter().map(FieldSpec::code) {
+        match groups.last_mut() {
+            Some((count, last)) if *last == code => {
+                *count += 1;
+            }
+            _ => groups.push((1, code)),
+        }
+    }
+    let mut items: Vec<String> = groups
+        .into_iter()
+        .map(|(count, code)| if count > 1 { format!("{}{}", count, code) } else { code })
+        .collect();
+    items.push(format!("[{}]", records_per_line));
+    format!("{:<25}{},{}", "**FORMAT:", items.len(), items.join(","))
+}
+
+/// `**RECORD_LENGTH:` header line matching the data lines written.
+pub fn record_length_line(fields: &[FieldSpec], records_per_line: usize) -> String {
+    format!("{:<25}I2,{}", "**RECORD_LENGTH:", record_length(fields, records_per_line))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FLOW: [FieldSpec; 3] = [
+        FieldSpec::integer(5),
+        FieldSpec::integer(5),
+        FieldSpec { width: 5, decimals: 2, format_decimals: false },
+    ];
+
+    #[test]
+    fn header_lines_describe_the_standard_flow_record() {
+        assert_eq!(format_line(&FLOW, 5), "**FORMAT:                3,2I5,F5,[5]");
+        assert_eq!(record_length_line(&FLOW, 5), "**RECORD_LENGTH:         I2,75");
+    }
+
+    #[test]
+    fn wider_field_updates_the_record_length() {
+        let mut fields = FLOW;
+        fields[0] = FieldSpec::real(6, 3);
+        assert_eq!(format_line(&fields, 5), "**FORMAT:                4,F6.3,I5,F5,[5]");
+        assert_eq!(record_length_line(&fields, 5), "**RECORD_LENGTH:         I2,80");
+    }
+}
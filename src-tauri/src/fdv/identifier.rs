@@ -0,0 +1,39 @@
+use deunicode::deunicode;
+
+/// Maximum identifier length allowed in an FDV header.
+pub const MAX_IDENTIFIER_LEN: usize = 15;
+
+/// Turns a site name into an FDV identifier: transliterated to ASCII, uppercased and
+/// truncated to 15 characters, so the file stays ASCII as the format requires.
+pub fn fdv_identifier(site_name: &str) -> String {
+    let ascii_name = if site_name.is_ascii() {
+        site_name.to_string()
+    } else {
+        let transliterated = deunicode(site_name);
+        log::info!("Site name '{}' transliterated to '{}' for FDV output", site_name, transliterated);
+        transliterated
+    };
+
+    ascii_name
+        .chars()
+        .filter(|c| c.is_ascii() && !c.is_ascii_control())
+        .take(MAX_IDENTIFIER_LEN)
+        .collect::<String>()
+        .to_uppercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accented_site_name_is_transliterated() {
+        assert_eq!(fdv_identifier("Bétheny Rd Écluse"), "BETHENY RD ECLU");
+    }
+
+    #[test]
+    fn ascii_site_name_is_uppercased_and_truncated() {
+        assert_eq!(fdv_identifier("Main Street Outfall"), "MAIN STREET OUT");
+        assert!(fdv_identifier("Ørsted Weir").is_ascii());
+    }
+}
@@ -0,0 +1,81 @@
+use polars::prelude::*;
+
+/// Returns the typical spacing of the timestamp column in milliseconds, taken as the median
+/// difference between consecutive readings so the odd gap or duplicate doesn't skew it.
+fn data_interval_ms(df: &DataFrame, timestamp_col: &str) -> PolarsResult<Option<i64>> {
+    let timestamps = df
+        .column(timestamp_col)?
+        .cast(&DataType::Datetime(TimeUnit::Milliseconds, None))?
+        .cast(&DataType::Int64)?;
+    let values: Vec<i64> = timestamps.i64()?.into_iter().flatten().collect();
+
+    let mut diffs: Vec<i64> = values
+        .windows(2)
+        .map(|pair| pair[1] - pair[0])
+        .filter(|diff| *diff > 0)
+        .collect();
+    if diffs.is_empty() {
+        return Ok(None);
+    }
+    diffs.sort_unstable();
+    Ok(Some(diffs[diffs.len() / 2]))
+}
+
+/// Checks that the DataFrame is spaced at the interval the FDV header will declare. FDV
+/// values carry no timestamps, so writing rows at a different spacing shifts every reading
+/// after the first.
+pub fn check_interval(
+    df: &DataFrame,
+    timestamp_col: &str,
+    interval_minutes: i64
+) -> Result<(), String> {
+    let Some(actual_ms) = data_interval_ms(df, timestamp_col).map_err(|e| e.to_string())? else {
+        return Ok(());
+    };
+
+    let expected_ms = interval_minutes * 60_000;
+    if actual_ms != expected_ms {
+        return Err(
+            format!(
+                "Data interval is {} but the FDV interval is {} minute(s). FDV files cannot \
+                 describe this spacing; resample the data to {} minute(s) before converting.",
+                describe_ms(actual_ms),
+                interval_minutes,
+                interval_minutes
+            )
+        );
+    }
+    Ok(())
+}
+
+fn describe_ms(ms: i64) -> String {
+    if ms % 60_000 == 0 {
+        format!("{} minute(s)", ms / 60_000)
+    } else {
+        format!("{} second(s)", (ms as f64) / 1000.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{ Duration, NaiveDate };
+
+    fn readings_every(minutes: i64) -> DataFrame {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        let timestamps: Vec<_> = (0..8).map(|i| start + Duration::minutes(i * minutes)).collect();
+        DataFrame::new(vec![Series::new("Timestamp".into(), timestamps)]).unwrap()
+    }
+
+    #[test]
+    fn coarser_fdv_interval_is_rejected() {
+        let error = check_interval(&readings_every(2), "Timestamp", 15).unwrap_err();
+        assert!(error.contains("Data interval is 2 minute(s)"), "{}", error);
+        assert!(error.contains("resample the data to 15 minute(s)"), "{}", error);
+    }
+
+    #[test]
+    fn matching_interval_is_accepted() {
+        assert!(check_interval(&readings_every(15), "Timestamp", 15).is_ok());
+    }
+}
@@ -1,2 +1,13 @@
+pub mod antecedent;
+pub mod column_unit;
+pub mod comment;
+pub mod data_status;
 pub mod fdv_creator;
+pub mod fdv_reader;
+pub mod field_layout;
+pub mod identifier;
+pub mod interval_check;
+pub mod overwrite;
+pub mod padding;
+pub mod quality;
 pub mod rainfall_creator;
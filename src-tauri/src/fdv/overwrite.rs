@@ -0,0 +1,145 @@
+use std::io;
+use std::path::{ Path, PathBuf };
+
+/// What the FDV creators do when the output file already exists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverwritePolicy {
+    /// Replace the existing file, logging a warning.
+    #[default]
+    Overwrite,
+    /// Refuse to write, leaving the existing file untouched.
+    Error,
+    /// Write alongside it as `<name>_1.fdv`, `<name>_2.fdv`, ...
+    Rename,
+}
+
+impl OverwritePolicy {
+    pub fn from_str(s: &str) -> Result<Self, String> {
+        match s.to_lowercase().as_str() {
+            "overwrite" => Ok(OverwritePolicy::Overwrite),
+            "error" => Ok(OverwritePolicy::Error),
+            "rename" => Ok(OverwritePolicy::Rename),
+            _ => Err(format!("'{}' is not a valid overwrite policy", s)),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OverwritePolicy::Overwrite => "overwrite",
+            OverwritePolicy::Error => "error",
+            OverwritePolicy::Rename => "rename",
+        }
+    }
+}
+
+/// Path the output should be created at under `policy`: `path` itself unless it exists and
+/// the policy renames, or an `AlreadyExists` error if the policy forbids overwriting.
+pub fn resolve_output_path(path: &str, policy: OverwritePolicy) -> io::Result<PathBuf> {
+    let path = Path::new(path);
+    if !path.exists() {
+        return Ok(path.to_path_buf());
+    }
+    match policy {
+        OverwritePolicy::Overwrite => {
+            log::warn!("Overwriting existing output file {}", path.display());
+            Ok(path.to_path_buf())
+        }
+        OverwritePolicy::Error =>
+            Err(
+                io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("Output file {} already exists", path.display())
+                )
+            ),
+        OverwritePolicy::Rename => {
+            let stem = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let extension = path
+                .extension()
+                .map(|ext| format!(".{}", ext.to_string_lossy()))
+                .unwrap_or_default();
+            let mut n = 1;
+            loop {
+                let renamed = path.with_file_name(format!("{}_{}{}", stem, n, extension));
+                if !renamed.exists() {
+                    log::info!("{} exists, writing {} instead", path.display(), renamed.display());
+                    return Ok(renamed);
+                }
+                n += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    const POLICIES: [OverwritePolicy; 3] = [
+        OverwritePolicy::Overwrite,
+        OverwritePolicy::Error,
+        OverwritePolicy::Rename,
+    ];
+
+    /// An existing `SITE1.fdv` alone in a scratch directory for one test.
+    fn existing_output(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("overwrite_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("SITE1.fdv");
+        fs::write(&path, "existing").unwrap();
+        path
+    }
+
+    #[test]
+    fn new_path_is_used_under_every_policy() {
+        let existing = existing_output("new_path");
+        let path = existing.with_file_name("SITE2.fdv");
+        for policy in POLICIES {
+            let resolved = resolve_output_path(path.to_str().unwrap(), policy).unwrap();
+            assert_eq!(resolved, path);
+        }
+        fs::remove_dir_all(existing.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn overwrite_returns_the_existing_path() {
+        let path = existing_output("overwrite");
+        let resolved = resolve_output_path(path.to_str().unwrap(), OverwritePolicy::Overwrite);
+        assert_eq!(resolved.unwrap(), path);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn error_refuses_an_existing_path() {
+        let path = existing_output("error");
+        let error = resolve_output_path(path.to_str().unwrap(), OverwritePolicy::Error);
+        assert_eq!(error.unwrap_err().kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "existing");
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn rename_takes_the_next_free_suffix() {
+        let path = existing_output("rename");
+        let renamed = resolve_output_path(path.to_str().unwrap(), OverwritePolicy::Rename);
+        assert_eq!(renamed.unwrap(), path.with_file_name("SITE1_1.fdv"));
+
+        fs::write(path.with_file_name("SITE1_1.fdv"), "existing").unwrap();
+        let renamed = resolve_output_path(path.to_str().unwrap(), OverwritePolicy::Rename);
+        assert_eq!(renamed.unwrap(), path.with_file_name("SITE1_2.fdv"));
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn policies_round_trip_through_their_names() {
+        for policy in POLICIES {
+            assert_eq!(OverwritePolicy::from_str(policy.as_str()), Ok(policy));
+        }
+        assert_eq!(OverwritePolicy::from_str("RENAME"), Ok(OverwritePolicy::Rename));
+        assert!(OverwritePolicy::from_str("append").is_err());
+    }
+}
@@ -0,0 +1,65 @@
+use chrono::{ Duration, NaiveDateTime, NaiveTime };
+use polars::prelude::*;
+
+use crate::backend::file_processor::INFILLED_COLUMN;
+
+/// Rows for `timestamps` matching the columns of `df`. Readings are null, so the creators
+/// count and write them as missing readings; the infilled column, if present, is set.
+fn padding_rows(
+    df: &DataFrame,
+    timestamp_col: &str,
+    timestamps: Vec<NaiveDateTime>
+) -> PolarsResult<DataFrame> {
+    let n = timestamps.len();
+    let columns = df
+        .get_columns()
+        .iter()
+        .map(|s| {
+            let name = s.name().clone();
+            if s.name().as_str() == timestamp_col {
+                Series::new(name, timestamps.clone()).cast(s.dtype())
+            } else if s.name().as_str() == INFILLED_COLUMN {
+                Ok(BooleanChunked::full(name, true, n).into_series())
+            } else {
+                Ok(Series::full_null(name, n, s.dtype()))
+            }
+        })
+        .collect::<PolarsResult<Vec<_>>>()?;
+    DataFrame::new(columns)
+}
+
+/// Extends a record spanning `start` to `end` to whole days: readings are added back to
+/// midnight on the first day and forward to the last interval before midnight after the
+/// last day. Returns the padded DataFrame, its new start and end, and the readings added.
+///
+/// Padding stays on the record's interval grid, so a record whose readings are not
+/// aligned to midnight starts at the first aligned slot after it.
+pub fn pad_to_full_days(
+    df: &DataFrame,
+    timestamp_col: &str,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    interval_minutes: i64
+) -> PolarsResult<(DataFrame, NaiveDateTime, NaiveDateTime, usize)> {
+    let interval = Duration::minutes(interval_minutes);
+    let day_start = start.date().and_time(NaiveTime::MIN);
+    let day_end = end.date().and_time(NaiveTime::MIN) + Duration::days(1);
+
+    let leading = ((start - day_start).num_minutes() / interval_minutes) as i32;
+    let trailing = (((day_end - end).num_minutes() - 1) / interval_minutes) as i32;
+    if leading == 0 && trailing == 0 {
+        return Ok((df.clone(), start, end, 0));
+    }
+
+    let padded_start = start - interval * leading;
+    let padded_end = end + interval * trailing;
+    let before = (0..leading).map(|i| padded_start + interval * i).collect();
+    let after = (1..=trailing).map(|i| end + interval * i).collect();
+
+    let mut padded = padding_rows(df, timestamp_col, before)?;
+    padded.vstack_mut(df)?;
+    padded.vstack_mut(&padding_rows(df, timestamp_col, after)?)?;
+    padded.align_chunks();
+
+    Ok((padded, padded_start, padded_end, (leading + trailing) as usize))
+}
@@ -0,0 +1,72 @@
+use polars::prelude::*;
+
+/// Per-reading quality flag carried in a source quality/status column. Flags are stored in
+/// the DataFrame as numeric codes so they survive gap filling alongside the measurements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QualityFlag {
+    Good,
+    Suspect,
+    Bad,
+}
+
+impl QualityFlag {
+    /// Parses the common textual and numeric spellings used by loggers. Blank or unknown
+    /// values are treated as unflagged.
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "good" | "g" | "ok" | "valid" | "0" => Some(QualityFlag::Good),
+            "suspect" | "s" | "questionable" | "doubtful" | "1" => Some(QualityFlag::Suspect),
+            "bad" | "b" | "invalid" | "fail" | "2" => Some(QualityFlag::Bad),
+            _ => None,
+        }
+    }
+
+    pub fn code(&self) -> f64 {
+        match self {
+            QualityFlag::Good => 0.0,
+            QualityFlag::Suspect => 1.0,
+            QualityFlag::Bad => 2.0,
+        }
+    }
+}
+
+/// Counts readings flagged bad in `quality_col` and, when `suppress` is set, nulls those
+/// readings in each of `value_cols` so the creators treat them as missing. Value columns
+/// absent from the DataFrame are skipped.
+pub fn apply_quality_flags(
+    df: &mut DataFrame,
+    quality_col: &str,
+    value_cols: &[&str],
+    suppress: bool
+) -> PolarsResult<usize> {
+    let bad: Vec<bool> = df
+        .column(quality_col)?
+        .cast(&DataType::Float64)?
+        .f64()?
+        .into_iter()
+        .map(|code| code == Some(QualityFlag::Bad.code()))
+        .collect();
+    let bad_count = bad
+        .iter()
+        .filter(|&&b| b)
+        .count();
+    if !suppress || bad_count == 0 {
+        return Ok(bad_count);
+    }
+
+    for &col in value_cols {
+        if !df.get_column_names().iter().any(|&c| c == col) {
+            continue;
+        }
+        let values: Vec<Option<f64>> = df
+            .column(col)?
+            .cast(&DataType::Float64)?
+            .f64()?
+            .into_iter()
+            .zip(&bad)
+            .map(|(value, &is_bad)| if is_bad { None } else { value })
+            .collect();
+        df.with_column(Series::new(col.into(), values))?;
+    }
+    Ok(bad_count)
+}
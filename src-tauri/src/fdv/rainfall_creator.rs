@@ -1,9 +1,18 @@
+use crate::fdv::antecedent::{antecedent_lines, antecedent_rainfall};
+use crate::fdv::comment::comment_lines;
+use crate::fdv::data_status::DataStatus;
+use crate::fdv::field_layout::{format_line, record_length_line, FieldSpec};
+use crate::fdv::identifier::fdv_identifier;
+use crate::fdv::interval_check::check_interval;
+use crate::fdv::overwrite::{resolve_output_path, OverwritePolicy};
+use crate::fdv::padding::pad_to_full_days;
+use crate::fdv::quality::apply_quality_flags;
 use chrono::NaiveDateTime;
 use polars::prelude::*;
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::{self, BufWriter, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -18,6 +27,111 @@ pub enum FDVRainfallCreatorError {
     ParseError(#[from] chrono::ParseError),
 }
 
+/// Rainfall intensity (mm/hr) field of the standard rainfall record.
+const RAINFALL_FIELD: FieldSpec = FieldSpec::real(15, 1);
+
+/// Index of the first constants line holding the location and antecedent rainfall.
+const ANTECEDENT_LINE: usize = 20;
+
+/// Readings packed onto each data line.
+const RECORDS_PER_LINE: usize = 5;
+
+/// Default tipping bucket resolution in mm per tip.
+pub const DEFAULT_TIP_RESOLUTION: f64 = 0.2;
+
+/// How the values in the rainfall column should be interpreted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RainfallInputMode {
+    /// Values are already intensities in mm/hr.
+    Intensity,
+    /// Values are a running total of bucket tips.
+    CumulativeTips,
+    /// Values are the number of tips in each interval.
+    IncrementalTips,
+}
+
+impl RainfallInputMode {
+    pub fn from_str(s: &str) -> Result<Self, FDVRainfallCreatorError> {
+        match s.to_lowercase().as_str() {
+            "intensity" => Ok(RainfallInputMode::Intensity),
+            "cumulative" | "cumulative_tips" => Ok(RainfallInputMode::CumulativeTips),
+            "incremental" | "incremental_tips" => Ok(RainfallInputMode::IncrementalTips),
+            _ => Err(FDVRainfallCreatorError::InvalidParameter(format!(
+                "'{}' is not a valid rainfall input mode",
+                s
+            ))),
+        }
+    }
+}
+
+/// Unit of the values in the rainfall column. Every rainfall aggregation converts through
+/// this so totals, summaries and the FDV intensities agree on what a reading means.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum RainfallUnit {
+    /// Intensity in mm/hr. Depth per reading = value × interval minutes / 60.
+    #[default]
+    MmPerHour,
+    /// Depth in mm fallen during the interval. Intensity = value × 60 / interval minutes.
+    MmPerInterval,
+    /// Bucket tips during the interval, each worth the given mm. Depth = value × resolution,
+    /// intensity = value × resolution × 60 / interval minutes.
+    Tips(f64),
+}
+
+impl RainfallUnit {
+    pub fn from_str(
+        s: &str,
+        tip_resolution: Option<f64>,
+    ) -> Result<Self, FDVRainfallCreatorError> {
+        match s.to_lowercase().as_str() {
+            "mm/hr" | "mm_per_hour" | "intensity" => Ok(RainfallUnit::MmPerHour),
+            "mm" | "mm/interval" | "mm_per_interval" => Ok(RainfallUnit::MmPerInterval),
+            "tips" => {
+                let resolution = tip_resolution.unwrap_or(DEFAULT_TIP_RESOLUTION);
+                if !resolution.is_finite() || resolution <= 0.0 {
+                    return Err(FDVRainfallCreatorError::InvalidParameter(format!(
+                        "Invalid tip resolution: {}",
+                        resolution
+                    )));
+                }
+                Ok(RainfallUnit::Tips(resolution))
+            }
+            _ => Err(FDVRainfallCreatorError::InvalidParameter(format!(
+                "'{}' is not a valid rainfall unit",
+                s
+            ))),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RainfallUnit::MmPerHour => "mm/hr",
+            RainfallUnit::MmPerInterval => "mm/interval",
+            RainfallUnit::Tips(_) => "tips",
+        }
+    }
+
+    /// Multiplier turning one reading into the mm fallen during its interval.
+    pub fn depth_factor(&self, interval_minutes: f64) -> f64 {
+        match self {
+            RainfallUnit::MmPerHour => interval_minutes / 60.0,
+            RainfallUnit::MmPerInterval => 1.0,
+            RainfallUnit::Tips(resolution) => *resolution,
+        }
+    }
+
+    /// Multiplier turning one reading into an intensity in mm/hr.
+    pub fn intensity_factor(&self, interval_minutes: f64) -> f64 {
+        match self {
+            RainfallUnit::MmPerHour => 1.0,
+            _ if interval_minutes > 0.0 => {
+                self.depth_factor(interval_minutes) * 60.0 / interval_minutes
+            }
+            _ => self.depth_factor(interval_minutes),
+        }
+    }
+}
+
 pub struct FDVRainfallCreator {
     timestamp_col: Option<String>,
     rainfall_col: Option<String>,
@@ -31,6 +145,20 @@ pub struct FDVRainfallCreator {
     value_count: usize,
     drain_size: usize,
     output_buffer: Vec<f64>,
+    rainfall_unit: RainfallUnit,
+    cumulative: bool,
+    data_status: DataStatus,
+    quality_col: Option<String>,
+    suppress_bad_quality: bool,
+    quality_flagged: usize,
+    comments: Vec<String>,
+    pad_to_full_days: bool,
+    padding_readings: usize,
+    field_spec: FieldSpec,
+    antecedent_df: Option<DataFrame>,
+    antecedent_rainfall: Vec<Option<f64>>,
+    overwrite_policy: OverwritePolicy,
+    written_path: Option<PathBuf>,
 }
 
 impl FDVRainfallCreator {
@@ -81,28 +209,199 @@ impl FDVRainfallCreator {
             value_count: 0,
             drain_size: 10,
             output_buffer: Vec::new(),
+            rainfall_unit: RainfallUnit::MmPerHour,
+            cumulative: false,
+            data_status: DataStatus::Raw,
+            quality_col: None,
+            suppress_bad_quality: false,
+            quality_flagged: 0,
+            comments: Vec::new(),
+            pad_to_full_days: false,
+            padding_readings: 0,
+            field_spec: RAINFALL_FIELD,
+            antecedent_df: None,
+            antecedent_rainfall: Vec::new(),
+            overwrite_policy: OverwritePolicy::default(),
+            written_path: None,
+        }
+    }
+
+    pub fn set_input_mode(&mut self, input_mode: RainfallInputMode, tip_resolution: f64) {
+        self.rainfall_unit = match input_mode {
+            RainfallInputMode::Intensity => RainfallUnit::MmPerHour,
+            RainfallInputMode::CumulativeTips | RainfallInputMode::IncrementalTips => {
+                RainfallUnit::Tips(tip_resolution)
+            }
+        };
+        self.cumulative = input_mode == RainfallInputMode::CumulativeTips;
+    }
+
+    pub fn set_data_status(&mut self, data_status: DataStatus) {
+        self.data_status = data_status;
+    }
+
+    /// Reads per-reading quality codes from `quality_col`. With `suppress` set, readings
+    /// flagged bad are written as nulls; otherwise they are only counted.
+    pub fn set_quality_column(&mut self, quality_col: &str, suppress: bool) {
+        self.quality_col = Some(quality_col.to_string());
+        self.suppress_bad_quality = suppress;
+    }
+
+    /// Free-text lines (e.g. operator, processing date) written as comments at the start
+    /// of the constants block, after a line naming the converter version.
+    pub fn set_comment(&mut self, lines: Vec<String>) {
+        self.comments = lines;
+    }
+
+    /// Extends the record to start at midnight and end at the last interval of its final
+    /// day. Added readings are written as zero and counted as null readings.
+    pub fn set_pad_to_full_days(&mut self, pad_to_full_days: bool) {
+        self.pad_to_full_days = pad_to_full_days;
+    }
+
+    /// Number of readings added by `set_pad_to_full_days`.
+    pub fn get_padding_readings(&self) -> usize {
+        self.padding_readings
+    }
+
+    /// Readings from before the record, with the same timestamp and rainfall columns, used
+    /// for the antecedent rainfall constants. Without it, only rows of the record's own
+    /// DataFrame that fall before the start are used.
+    pub fn set_antecedent_data(&mut self, df: DataFrame) {
+        self.antecedent_df = Some(df);
+    }
+
+    /// Antecedent rainfall totals (mm) written in the header, for 0 to 30 days before the
+    /// start; `None` where the data didn't reach back far enough.
+    pub fn get_antecedent_rainfall(&self) -> &[Option<f64>] {
+        &self.antecedent_rainfall
+    }
+
+    /// Computes the antecedent rainfall totals and writes them into the constants block in
+    /// place of the `-1.0` placeholders.
+    fn fill_antecedent_rainfall(&mut self) -> Result<(), FDVRainfallCreatorError> {
+        let (Some(timestamp_col), Some(rainfall_col), Some(start), Some(interval)) = (
+            self.timestamp_col.as_deref(),
+            self.rainfall_col.as_deref(),
+            self.start_ts,
+            self.interval,
+        ) else {
+            return Ok(());
+        };
+        let Some(df) = self.antecedent_df.as_ref().or(self.df.as_ref()) else {
+            return Ok(());
+        };
+
+        let timestamps: Vec<Option<NaiveDateTime>> =
+            df.column(timestamp_col)?.datetime()?.as_datetime_iter().collect();
+        let values: Vec<Option<f64>> = df
+            .column(rainfall_col)?
+            .cast(&DataType::Float64)?
+            .f64()?
+            .into_iter()
+            .collect();
+        let hours = interval as f64 / 60.0;
+        let readings: Vec<(NaiveDateTime, Option<f64>)> = timestamps
+            .into_iter()
+            .zip(self.to_intensities(values))
+            .filter_map(|(timestamp, intensity)| {
+                timestamp.map(|timestamp| (timestamp, intensity.map(|i| i * hours)))
+            })
+            .collect();
+
+        self.antecedent_rainfall = antecedent_rainfall(&readings, start);
+        for (offset, line) in antecedent_lines(&self.antecedent_rainfall)
+            .into_iter()
+            .enumerate()
+        {
+            self.header_lines[ANTECEDENT_LINE + offset] = line;
+        }
+        Ok(())
+    }
+
+    fn pad_record(&mut self) -> Result<(), FDVRainfallCreatorError> {
+        if !self.pad_to_full_days {
+            return Ok(());
         }
+        let (Some(df), Some(timestamp_col), Some(start), Some(end), Some(interval)) = (
+            self.df.as_ref(),
+            self.timestamp_col.as_deref(),
+            self.start_ts,
+            self.end_ts,
+            self.interval,
+        ) else {
+            return Ok(());
+        };
+        let (padded, start, end, added) =
+            pad_to_full_days(df, timestamp_col, start, end, interval)?;
+        self.df = Some(padded);
+        self.start_ts = Some(start);
+        self.end_ts = Some(end);
+        self.padding_readings = added;
+        Ok(())
+    }
+
+    pub fn set_rainfall_unit(&mut self, rainfall_unit: RainfallUnit) {
+        self.rainfall_unit = rainfall_unit;
+        self.cumulative = false;
+    }
+
+    /// Converts the raw column values to intensities in mm/hr according to the rainfall unit.
+    /// A cumulative tip counter is first differenced into tips per interval, treating a drop
+    /// as a counter reset.
+    fn to_intensities(&self, values: Vec<Option<f64>>) -> Vec<Option<f64>> {
+        let interval_minutes = self.interval.unwrap_or(0) as f64;
+        let factor = self.rainfall_unit.intensity_factor(interval_minutes);
+
+        let per_interval: Vec<Option<f64>> = if self.cumulative {
+            let mut previous: Option<f64> = None;
+            values
+                .into_iter()
+                .map(|v| {
+                    let current = v.filter(|c| !c.is_nan())?;
+                    let tips = match previous {
+                        Some(p) if current >= p => current - p,
+                        Some(_) => current,
+                        None => 0.0,
+                    };
+                    previous = Some(current);
+                    Some(tips)
+                })
+                .collect()
+        } else {
+            values
+        };
+
+        per_interval.into_iter().map(|v| v.map(|v| v * factor)).collect()
     }
 
     pub fn set_dataframe(&mut self, df: DataFrame) {
         self.df = Some(df);
     }
 
+    /// Decides what `open_output_path` does if the file already exists. Set it before
+    /// `set_parameters`, which opens the file.
+    pub fn set_overwrite_policy(&mut self, overwrite_policy: OverwritePolicy) {
+        self.overwrite_policy = overwrite_policy;
+    }
+
     pub fn open_output_path(&mut self, output_path: &str) -> Result<(), FDVRainfallCreatorError> {
-        let file = File::create(Path::new(output_path))?;
+        let path = resolve_output_path(output_path, self.overwrite_policy)?;
+        let file = File::create(&path)?;
         self.output_path = Some(BufWriter::new(file));
+        self.written_path = Some(path);
         Ok(())
     }
 
+    /// Path actually written, which differs from the requested one after a rename.
+    pub fn written_path(&self) -> Option<&Path> {
+        self.written_path.as_deref()
+    }
+
     pub fn set_site_name(&mut self, site_name: &str) {
-        let truncated_name = if site_name.len() > 15 {
-            &site_name[..15]
-        } else {
-            site_name
-        };
         self.header_lines[1] = format!(
             "**IDENTIFIER:            1,{}",
-            truncated_name.to_uppercase()
+            fdv_identifier(site_name)
         );
     }
 
@@ -130,9 +429,19 @@ impl FDVRainfallCreator {
     }
 
     fn header(&mut self) -> io::Result<()> {
+        self.header_lines[4] = format_line(&[self.field_spec], RECORDS_PER_LINE);
+        self.header_lines[5] = record_length_line(&[self.field_spec], RECORDS_PER_LINE);
         if let Some(ref mut writer) = self.output_path {
-            for line in &self.header_lines {
+            for (i, line) in self.header_lines.iter().enumerate() {
                 writeln!(writer, "{}", line)?;
+                if i == 1 {
+                    writeln!(writer, "{}", self.data_status.header_line())?;
+                }
+                if line == "*CSTART" {
+                    for comment in comment_lines(&self.comments) {
+                        writeln!(writer, "{}", comment)?;
+                    }
+                }
             }
             let interval_in_minutes = self.interval.unwrap();
             let start_str = self.start_ts.unwrap().format("%Y%m%d%H%M").to_string();
@@ -149,7 +458,7 @@ impl FDVRainfallCreator {
 
     fn write_tail(&mut self) -> io::Result<()> {
         if let Some(ref mut writer) = self.output_path {
-            if (self.value_count - 1) % 5 != 0 {
+            if (self.value_count - 1) % RECORDS_PER_LINE != 0 {
                 writeln!(writer)?;
             }
             writeln!(writer, "\n*END")?;
@@ -161,8 +470,8 @@ impl FDVRainfallCreator {
         if let Some(ref mut writer) = self.output_path {
             while self.output_buffer.len() > drain_size {
                 let sample = self.output_buffer.remove(0);
-                write!(writer, "{:15.1}", sample)?;
-                if self.value_count % 5 == 0 {
+                self.field_spec.write(writer, sample)?;
+                if self.value_count % RECORDS_PER_LINE == 0 {
                     writeln!(writer)?;
                 }
                 self.value_count += 1;
@@ -229,7 +538,13 @@ impl FDVRainfallCreator {
 
         let rainfall_series = df.column(rainfall_col)?.clone();
         self.null_readings = df.column(rainfall_col)?.null_count();
-        let rainfall_values: Vec<Option<f64>> = rainfall_series.f64()?.into_iter().collect();
+        // Tip counts are read as integers; intensities are worked out as floats
+        let rainfall_values: Vec<Option<f64>> = rainfall_series
+            .cast(&DataType::Float64)?
+            .f64()?
+            .into_iter()
+            .collect();
+        let rainfall_values = self.to_intensities(rainfall_values);
 
         for value in rainfall_values {
             match value {
@@ -247,6 +562,36 @@ impl FDVRainfallCreator {
         self.null_readings
     }
 
+    /// Number of readings nulled because their quality flag was bad.
+    pub fn get_quality_suppressed(&self) -> usize {
+        if self.suppress_bad_quality {
+            self.quality_flagged
+        } else {
+            0
+        }
+    }
+
+    /// Suppressing readings is an edit, so the header status is raised to at least EDITED.
+    fn apply_quality_flags(&mut self) -> Result<(), FDVRainfallCreatorError> {
+        let (Some(df), Some(quality_col), Some(rainfall_col)) = (
+            self.df.as_mut(),
+            self.quality_col.as_deref(),
+            self.rainfall_col.as_deref(),
+        ) else {
+            return Ok(());
+        };
+        self.quality_flagged =
+            apply_quality_flags(df, quality_col, &[rainfall_col], self.suppress_bad_quality)?;
+        if self.quality_flagged > 0 {
+            if self.suppress_bad_quality {
+                self.data_status = self.data_status.max(DataStatus::Edited);
+            } else {
+                log::warn!("{} readings flagged bad were kept", self.quality_flagged);
+            }
+        }
+        Ok(())
+    }
+
     pub fn validate_params(&self) -> Result<(), &'static str> {
         if self.start_ts.is_none() {
             return Err("Starting time is not set. Use set_starting_time() method.");
@@ -257,6 +602,9 @@ impl FDVRainfallCreator {
         if self.interval.is_none() {
             return Err("Interval is not set. Use set_interval() method.");
         }
+        if self.interval.is_some_and(|interval| interval <= 0) {
+            return Err("Interval must be greater than zero minutes.");
+        }
         if self.output_path.is_none() {
             return Err("Output file is not set. Use open_output_path() method.");
         }
@@ -266,6 +614,17 @@ impl FDVRainfallCreator {
         Ok(())
     }
 
+    fn check_data_interval(&self) -> Result<(), FDVRainfallCreatorError> {
+        let timestamp_col = self.timestamp_col.as_deref().filter(|c| !c.is_empty());
+        let (Some(df), Some(timestamp_col), Some(interval)) =
+            (self.df.as_ref(), timestamp_col, self.interval)
+        else {
+            return Ok(());
+        };
+        check_interval(df, timestamp_col, interval)
+            .map_err(FDVRainfallCreatorError::InvalidParameter)
+    }
+
     pub fn set_parameters(
         &mut self,
         df: DataFrame,
@@ -296,6 +655,10 @@ impl FDVRainfallCreator {
     pub fn create_fdv_rainfall(&mut self) -> Result<(), FDVRainfallCreatorError> {
         self.validate_params()
             .map_err(|e| FDVRainfallCreatorError::InvalidParameter(e.to_string()))?;
+        self.check_data_interval()?;
+        self.pad_record()?;
+        self.apply_quality_flags()?;
+        self.fill_antecedent_rainfall()?;
 
         self.header()?;
 
@@ -321,3 +684,91 @@ impl FDVRainfallCreator {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn intensities(mode: RainfallInputMode, tip_resolution: f64, values: &[f64]) -> Vec<f64> {
+        let mut creator = FDVRainfallCreator::new();
+        creator.set_interval(5);
+        creator.set_input_mode(mode, tip_resolution);
+        creator
+            .to_intensities(values.iter().map(|&v| Some(v)).collect())
+            .into_iter()
+            .map(|v| v.unwrap())
+            .collect()
+    }
+
+    fn assert_close(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-9, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn cumulative_tips_are_differenced_into_intensities() {
+        let values = intensities(RainfallInputMode::CumulativeTips, 0.2, &[10.0, 11.0, 13.0, 13.0]);
+        assert_close(&values, &[0.0, 2.4, 4.8, 0.0]);
+    }
+
+    #[test]
+    fn cumulative_counter_reset_counts_from_zero() {
+        let values = intensities(RainfallInputMode::CumulativeTips, 0.2, &[10.0, 12.0, 1.0]);
+        assert_close(&values, &[0.0, 4.8, 2.4]);
+    }
+
+    #[test]
+    fn incremental_tips_are_scaled_into_intensities() {
+        let values = intensities(RainfallInputMode::IncrementalTips, 0.2, &[0.0, 1.0, 2.0, 0.0]);
+        assert_close(&values, &[0.0, 2.4, 4.8, 0.0]);
+    }
+
+    #[test]
+    fn rainfall_unit_conversions_over_a_15_minute_interval() {
+        let mm_per_hour = RainfallUnit::from_str("mm/hr", None).unwrap();
+        assert_eq!(mm_per_hour.depth_factor(15.0), 0.25);
+        assert_eq!(mm_per_hour.intensity_factor(15.0), 1.0);
+
+        let mm_per_interval = RainfallUnit::from_str("mm", None).unwrap();
+        assert_eq!(mm_per_interval.depth_factor(15.0), 1.0);
+        assert_eq!(mm_per_interval.intensity_factor(15.0), 4.0);
+
+        let tips = RainfallUnit::from_str("tips", Some(0.5)).unwrap();
+        assert_eq!(tips, RainfallUnit::Tips(0.5));
+        assert_eq!(tips.depth_factor(15.0), 0.5);
+        assert_eq!(tips.intensity_factor(15.0), 2.0);
+    }
+
+    #[test]
+    fn rainfall_unit_rejects_unknown_units_and_bad_resolutions() {
+        assert!(RainfallUnit::from_str("inches", None).is_err());
+        assert!(RainfallUnit::from_str("tips", Some(0.0)).is_err());
+        assert_eq!(
+            RainfallUnit::from_str("tips", None).unwrap(),
+            RainfallUnit::Tips(DEFAULT_TIP_RESOLUTION)
+        );
+    }
+
+    #[test]
+    fn zero_interval_fails_validation() {
+        let mut creator = FDVRainfallCreator::new();
+        creator.set_starting_time("2024-01-01 00:00:00").unwrap();
+        creator.set_ending_time("2024-01-02 00:00:00").unwrap();
+        creator.set_interval(0);
+        assert_eq!(
+            creator.validate_params(),
+            Err("Interval must be greater than zero minutes.")
+        );
+    }
+
+    #[test]
+    fn tip_resolution_scales_intensities() {
+        // One tip in 5 minutes is 2.4 mm/hr at 0.2 mm and 6.0 mm/hr at 0.5 mm
+        let values = intensities(RainfallInputMode::IncrementalTips, 0.2, &[1.0, 2.0]);
+        assert_close(&values, &[2.4, 4.8]);
+        let values = intensities(RainfallInputMode::IncrementalTips, 0.5, &[1.0, 2.0]);
+        assert_close(&values, &[6.0, 12.0]);
+    }
+}
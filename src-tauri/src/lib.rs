@@ -6,7 +6,9 @@ mod utils;
 use log::LevelFilter;
 use tauri_plugin_updater::UpdaterExt;
 use utils::commands::*;
-use utils::logger::{get_recent_logs, set_console_logging, set_frontend_logging, Logger};
+use utils::logger::{
+    export_logs, get_recent_logs, set_console_logging, set_frontend_logging, Logger,
+};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -32,7 +34,7 @@ pub fn run() {
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
-            greet,
+            version_info,
             process_file,
             update_timestamps,
             clear_command_handler_state,
@@ -46,7 +48,66 @@ pub fn run() {
             calculate_r3,
             run_batch_process,
             generate_interim_reports,
-            generate_rainfall_totals
+            generate_rainfall_totals,
+            suggest_pipe_size,
+            column_histogram,
+            validate_fdv_file,
+            reprocess_with_options,
+            depth_velocity_pairs,
+            export_logs,
+            create_fdv_flow_constant_velocity,
+            read_fdv_header,
+            detect_flatlines,
+            data_quality_summary,
+            set_monitor_type,
+            validate_pipe_params,
+            run_batch_from_directory,
+            get_state,
+            save_combined_report,
+            create_fdv_flow_split,
+            detect_interval_changes,
+            set_data_status,
+            timeseries_preview,
+            set_quality_suppression,
+            apply_datum_offset,
+            total_over_range,
+            set_flow_output_style,
+            null_timestamps,
+            preview_trim,
+            interim_reports_json,
+            daily_completeness,
+            set_fdv_comment,
+            find_duplicate_timestamps,
+            set_missing_velocity_mode,
+            write_provenance,
+            supported_timestamp_formats,
+            add_timestamp_format,
+            set_signed_flow,
+            depth_velocity_correlation,
+            set_pad_to_full_days,
+            load_fdv,
+            estimate_dwf,
+            process_files,
+            check_expected_interval,
+            antecedent_rainfall,
+            set_flow_unit,
+            peak_reading,
+            create_both_outputs,
+            coverage_summary,
+            set_tip_resolution,
+            shift_timestamps,
+            detect_clock_drift,
+            set_column_unit,
+            create_fdv_flow_yearly,
+            estimate_infiltration,
+            set_flow_no_data_value,
+            readings_below_limit,
+            save_raw_vs_filled,
+            set_overwrite_policy,
+            flow_extremes,
+            set_write_checksums,
+            pipe_size_sanity_check,
+            describe_active_calculator
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
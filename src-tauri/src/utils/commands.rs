@@ -1,26 +1,67 @@
 use crate::backend::backend::CommandHandler;
-use serde_json::Value;
+use crate::backend::interim_reports::{
+    AggOp,
+    DailyCompleteness,
+    InterimReportOptions,
+    ReportPeriod,
+};
+use serde::Serialize;
+use serde_json::{ json, Value };
 use std::path::Path;
-use std::sync::Mutex;
-use tauri::State;
+use std::sync::{ Arc, Mutex };
+use tauri::{ AppHandle, Emitter, State };
 
 pub struct AppState {
     command_handler: Mutex<CommandHandler>,
 }
 
+#[derive(Clone, Serialize)]
+struct ProcessProgress {
+    phase: String,
+    percent: u8,
+}
+
 #[tauri::command]
-pub fn greet(name: &str) -> String {
-    format!("Hello, {}! You've been greeted from Rust!", name)
+pub fn version_info(state: State<'_, AppState>) -> Result<String, String> {
+    let command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.version_info()
 }
 
 #[tauri::command]
-pub async fn process_file(state: State<'_, AppState>, file_path: String) -> Result<String, String> {
+pub async fn process_file(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    file_path: String
+) -> Result<String, String> {
     let mut command_handler = state.command_handler
         .lock()
         .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.set_progress_callback(
+        Some(
+            Arc::new(move |phase: &str, percent: u8| {
+                let progress = ProcessProgress { phase: phase.to_string(), percent };
+                if let Err(e) = app.emit("process_progress", progress) {
+                    log::warn!("Failed to emit process progress: {}", e);
+                }
+            })
+        )
+    );
     command_handler.process_file(&file_path)
 }
 
+#[tauri::command]
+pub async fn reprocess_with_options(
+    state: State<'_, AppState>,
+    options: Value
+) -> Result<String, String> {
+    let mut command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.reprocess_with_options(options)
+}
+
 #[tauri::command]
 pub async fn update_timestamps(
     state: State<'_, AppState>,
@@ -62,6 +103,422 @@ pub async fn update_site_name(
     command_handler.update_site_name(site_name)
 }
 
+#[tauri::command]
+pub fn get_state(state: State<'_, AppState>) -> Result<String, String> {
+    let command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.get_state()
+}
+
+#[tauri::command]
+pub fn set_data_status(state: State<'_, AppState>, data_status: String) -> Result<String, String> {
+    let mut command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.set_data_status(&data_status)
+}
+
+#[tauri::command]
+pub fn set_quality_suppression(
+    state: State<'_, AppState>,
+    suppress: bool
+) -> Result<String, String> {
+    let mut command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.set_quality_suppression(suppress)
+}
+
+#[tauri::command]
+pub fn apply_datum_offset(
+    state: State<'_, AppState>,
+    offset_m: f64,
+    clamp_negative: Option<bool>
+) -> Result<String, String> {
+    let mut command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.apply_datum_offset(offset_m, clamp_negative.unwrap_or(true))
+}
+
+#[tauri::command]
+pub fn preview_trim(
+    state: State<'_, AppState>,
+    start: String,
+    end: String
+) -> Result<String, String> {
+    let command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.preview_trim(&start, &end)
+}
+
+#[tauri::command]
+pub fn total_over_range(
+    state: State<'_, AppState>,
+    start: String,
+    end: String
+) -> Result<String, String> {
+    let command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.total_over_range(&start, &end)
+}
+
+#[tauri::command]
+pub fn supported_timestamp_formats(state: State<'_, AppState>) -> Result<String, String> {
+    let command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.supported_timestamp_formats()
+}
+
+#[tauri::command]
+pub fn add_timestamp_format(state: State<'_, AppState>, format: String) -> Result<String, String> {
+    let mut command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.add_timestamp_format(&format)
+}
+
+#[tauri::command]
+pub fn set_signed_flow(state: State<'_, AppState>, enabled: bool) -> Result<String, String> {
+    let mut command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.set_signed_flow(enabled)
+}
+
+#[tauri::command]
+pub fn depth_velocity_correlation(state: State<'_, AppState>) -> Result<String, String> {
+    let command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.depth_velocity_correlation()
+}
+
+#[tauri::command]
+pub fn set_pad_to_full_days(state: State<'_, AppState>, enabled: bool) -> Result<String, String> {
+    let mut command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.set_pad_to_full_days(enabled)
+}
+
+#[tauri::command]
+pub fn load_fdv(state: State<'_, AppState>, path: String) -> Result<String, String> {
+    let mut command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.load_fdv(&path)
+}
+
+#[tauri::command]
+pub fn estimate_dwf(
+    state: State<'_, AppState>,
+    window_start: Option<String>,
+    window_end: Option<String>,
+    dry_days: Option<Vec<String>>
+) -> Result<String, String> {
+    let command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.estimate_dwf(
+        window_start.as_deref(),
+        window_end.as_deref(),
+        dry_days.as_deref()
+    )
+}
+
+#[tauri::command]
+pub async fn process_files(
+    state: State<'_, AppState>,
+    paths: Vec<String>
+) -> Result<String, String> {
+    let mut command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.process_files(paths)
+}
+
+#[tauri::command]
+pub fn check_expected_interval(
+    state: State<'_, AppState>,
+    expected_minutes: i64
+) -> Result<String, String> {
+    let command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.check_expected_interval(expected_minutes)
+}
+
+#[tauri::command]
+pub fn antecedent_rainfall(
+    state: State<'_, AppState>,
+    rainfall_col: Option<String>
+) -> Result<String, String> {
+    let command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.antecedent_rainfall(rainfall_col.as_deref())
+}
+
+#[tauri::command]
+pub fn peak_reading(state: State<'_, AppState>) -> Result<String, String> {
+    let command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.peak_reading()
+}
+
+#[tauri::command]
+pub fn create_both_outputs(
+    state: State<'_, AppState>,
+    flow_path: String,
+    depth_path: String,
+    depth_col: String,
+    velocity_col: Option<String>,
+    pipe_shape: String,
+    pipe_size: String
+) -> Result<String, String> {
+    let mut command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.create_both_outputs(
+        &flow_path,
+        &depth_path,
+        &depth_col,
+        &velocity_col.as_deref(),
+        &pipe_shape,
+        &pipe_size
+    )
+}
+
+#[tauri::command]
+pub fn coverage_summary(state: State<'_, AppState>) -> Result<String, String> {
+    let command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.coverage_summary()
+}
+
+#[tauri::command]
+pub fn shift_timestamps(
+    state: State<'_, AppState>,
+    offset_seconds: i64
+) -> Result<String, String> {
+    let mut command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.shift_timestamps(offset_seconds)
+}
+
+#[tauri::command]
+pub fn detect_clock_drift(state: State<'_, AppState>) -> Result<String, String> {
+    let command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.detect_clock_drift()
+}
+
+#[tauri::command]
+pub fn set_column_unit(
+    state: State<'_, AppState>,
+    column: String,
+    unit: String
+) -> Result<String, String> {
+    let mut command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.set_column_unit(&column, &unit)
+}
+
+#[tauri::command]
+pub fn create_fdv_flow_yearly(
+    state: State<'_, AppState>,
+    output_dir: String,
+    depth_col: String,
+    velocity_col: Option<String>,
+    pipe_shape: String,
+    pipe_size: String
+) -> Result<String, String> {
+    let mut command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.create_fdv_flow_yearly(
+        &output_dir,
+        &depth_col,
+        &velocity_col.as_deref(),
+        &pipe_shape,
+        &pipe_size
+    )
+}
+
+#[tauri::command]
+pub fn estimate_infiltration(
+    state: State<'_, AppState>,
+    window_start: Option<String>,
+    window_end: Option<String>
+) -> Result<String, String> {
+    let command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.estimate_infiltration(window_start.as_deref(), window_end.as_deref())
+}
+
+#[tauri::command]
+pub fn readings_below_limit(
+    state: State<'_, AppState>,
+    column: String,
+    limit: f64,
+    mode: Option<String>
+) -> Result<String, String> {
+    let mut command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.readings_below_limit(&column, limit, mode.as_deref())
+}
+
+#[tauri::command]
+pub fn save_raw_vs_filled(
+    state: State<'_, AppState>,
+    output_path: String
+) -> Result<String, String> {
+    let command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.save_raw_vs_filled(&output_path)
+}
+
+#[tauri::command]
+pub fn flow_extremes(state: State<'_, AppState>) -> Result<String, String> {
+    let command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.flow_extremes()
+}
+
+#[tauri::command]
+pub fn pipe_size_sanity_check(
+    state: State<'_, AppState>,
+    pipe_shape: String,
+    pipe_size: String
+) -> Result<String, String> {
+    let command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.pipe_size_sanity_check(&pipe_shape, &pipe_size)
+}
+
+#[tauri::command]
+pub fn describe_active_calculator(state: State<'_, AppState>) -> Result<String, String> {
+    let command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.describe_active_calculator()
+}
+
+#[tauri::command]
+pub fn write_provenance(
+    state: State<'_, AppState>,
+    path: String,
+    include_hash: Option<bool>
+) -> Result<String, String> {
+    let command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.write_provenance(&path, include_hash.unwrap_or(true))
+}
+
+#[tauri::command]
+pub fn set_missing_velocity_mode(
+    state: State<'_, AppState>,
+    mode: String,
+    velocity: Option<f64>
+) -> Result<String, String> {
+    let mut command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.set_missing_velocity_mode(&mode, velocity)
+}
+
+#[tauri::command]
+pub fn set_fdv_comment(state: State<'_, AppState>, lines: Vec<String>) -> Result<String, String> {
+    let mut command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.set_fdv_comment(lines)
+}
+
+#[tauri::command]
+pub fn set_flow_output_style(
+    state: State<'_, AppState>,
+    output_style: String
+) -> Result<String, String> {
+    let mut command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.set_flow_output_style(&output_style)
+}
+
+#[tauri::command]
+pub fn set_flow_unit(state: State<'_, AppState>, flow_unit: String) -> Result<String, String> {
+    let mut command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.set_flow_unit(&flow_unit)
+}
+
+#[tauri::command]
+pub fn set_flow_no_data_value(
+    state: State<'_, AppState>,
+    value: Option<f64>
+) -> Result<String, String> {
+    let mut command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.set_flow_no_data_value(value)
+}
+
+#[tauri::command]
+pub fn set_tip_resolution(
+    state: State<'_, AppState>,
+    tip_resolution_mm: f64
+) -> Result<String, String> {
+    let mut command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.set_tip_resolution(tip_resolution_mm)
+}
+
+#[tauri::command]
+pub fn set_overwrite_policy(state: State<'_, AppState>, policy: String) -> Result<String, String> {
+    let mut command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.set_overwrite_policy(&policy)
+}
+
+#[tauri::command]
+pub fn set_write_checksums(state: State<'_, AppState>, enabled: bool) -> Result<String, String> {
+    let mut command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.set_write_checksums(enabled)
+}
+
+#[tauri::command]
+pub async fn set_monitor_type(
+    state: State<'_, AppState>,
+    monitor_type: String
+) -> Result<String, String> {
+    let mut command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+    command_handler.set_monitor_type(&monitor_type)
+}
+
 pub fn create_app_state() -> AppState {
     AppState {
         command_handler: Mutex::new(CommandHandler::new()),
@@ -91,17 +548,216 @@ pub fn create_fdv_flow(
     )
 }
 
+#[tauri::command]
+pub fn create_fdv_flow_constant_velocity(
+    state: State<'_, AppState>,
+    output_path: String,
+    depth_col: String,
+    velocity: f64,
+    pipe_shape: String,
+    pipe_size: String
+) -> Result<String, String> {
+    let mut command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+
+    command_handler.create_fdv_flow_constant_velocity(
+        &output_path,
+        &depth_col,
+        velocity,
+        &pipe_shape,
+        &pipe_size
+    )
+}
+
+#[tauri::command]
+pub fn create_fdv_flow_split(
+    state: State<'_, AppState>,
+    output_dir: String,
+    period: String,
+    period_days: Option<i64>,
+    depth_col: String,
+    velocity_col: Option<String>,
+    pipe_shape: String,
+    pipe_size: String
+) -> Result<String, String> {
+    let mut command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+
+    let period = ReportPeriod::from_str(&period, period_days).map_err(|e| e.to_string())?;
+    command_handler.create_fdv_flow_split(
+        &output_dir,
+        period,
+        &depth_col,
+        &velocity_col.as_deref(),
+        &pipe_shape,
+        &pipe_size
+    )
+}
+
 #[tauri::command]
 pub fn create_rainfall(
     state: State<'_, AppState>,
     output_path: String,
-    rainfall_col: String
+    rainfall_col: String,
+    input_mode: Option<String>,
+    tip_resolution: Option<f64>
 ) -> Result<String, String> {
     let mut command_handler = state.command_handler
         .lock()
         .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
 
-    command_handler.create_rainfall(&output_path, &rainfall_col)
+    command_handler.create_rainfall(
+        &output_path,
+        &rainfall_col,
+        &input_mode.as_deref(),
+        tip_resolution
+    )
+}
+
+#[tauri::command]
+pub fn suggest_pipe_size(
+    state: State<'_, AppState>,
+    depth_col: String,
+    pipe_size: Option<String>
+) -> Result<String, String> {
+    let command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+
+    command_handler.suggest_pipe_size(&depth_col, &pipe_size.as_deref())
+}
+
+#[tauri::command]
+pub fn validate_pipe_params(
+    state: State<'_, AppState>,
+    pipe_shape: String,
+    pipe_size: String
+) -> Result<String, String> {
+    let command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+
+    command_handler.validate_pipe_params(&pipe_shape, &pipe_size)
+}
+
+#[tauri::command]
+pub fn column_histogram(
+    state: State<'_, AppState>,
+    column: String,
+    bins: usize
+) -> Result<String, String> {
+    let command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+
+    command_handler.column_histogram(&column, bins)
+}
+
+#[tauri::command]
+pub async fn read_fdv_header(state: State<'_, AppState>, path: String) -> Result<String, String> {
+    let command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+
+    command_handler.read_fdv_header(&path)
+}
+
+#[tauri::command]
+pub async fn validate_fdv_file(state: State<'_, AppState>, path: String) -> Result<String, String> {
+    let command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+
+    command_handler.validate_fdv_file(&path)
+}
+
+#[tauri::command]
+pub fn depth_velocity_pairs(
+    state: State<'_, AppState>,
+    max_points: Option<usize>
+) -> Result<String, String> {
+    let command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+
+    command_handler.depth_velocity_pairs(max_points)
+}
+
+#[tauri::command]
+pub fn timeseries_preview(
+    state: State<'_, AppState>,
+    column: String,
+    max_points: Option<usize>
+) -> Result<String, String> {
+    let command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+
+    command_handler.timeseries_preview(&column, max_points)
+}
+
+#[tauri::command]
+pub fn detect_flatlines(
+    state: State<'_, AppState>,
+    column: String,
+    min_run: usize
+) -> Result<String, String> {
+    let command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+
+    command_handler.detect_flatlines(&column, min_run)
+}
+
+#[tauri::command]
+pub fn null_timestamps(
+    state: State<'_, AppState>,
+    column: String,
+    max: usize
+) -> Result<String, String> {
+    let command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+
+    command_handler.null_timestamps(&column, max)
+}
+
+#[tauri::command]
+pub fn daily_completeness(state: State<'_, AppState>) -> Result<String, String> {
+    let command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+
+    command_handler.daily_completeness()
+}
+
+#[tauri::command]
+pub fn find_duplicate_timestamps(state: State<'_, AppState>) -> Result<String, String> {
+    let command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+
+    command_handler.find_duplicate_timestamps()
+}
+
+#[tauri::command]
+pub fn detect_interval_changes(state: State<'_, AppState>) -> Result<String, String> {
+    let command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+
+    command_handler.detect_interval_changes()
+}
+
+#[tauri::command]
+pub fn data_quality_summary(state: State<'_, AppState>) -> Result<String, String> {
+    let command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+
+    command_handler.data_quality_summary()
 }
 
 #[tauri::command]
@@ -128,34 +784,184 @@ pub fn calculate_r3(
 pub async fn run_batch_process(
     state: State<'_, AppState>,
     file_infos: Vec<Value>,
-    output_dir: String
+    output_dir: String,
+    force: Option<bool>
 ) -> Result<String, String> {
     let command_handler = state.command_handler
         .lock()
         .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
     let output_path = Path::new(&output_dir);
 
-    match command_handler.run_batch_process(file_infos, output_path) {
+    match command_handler.run_batch_process(file_infos, output_path, force.unwrap_or(false)) {
         Ok(()) => Ok("Batch processing completed successfully".to_string()),
         Err(e) => Err(format!("Error during batch processing: {}", e)),
     }
 }
 
+#[tauri::command]
+pub async fn run_batch_from_directory(
+    state: State<'_, AppState>,
+    dir: String,
+    output_dir: String,
+    default_pipe_shape: Option<String>,
+    default_pipe_size: Option<String>,
+    recursive: bool,
+    force: Option<bool>
+) -> Result<String, String> {
+    let command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+
+    command_handler
+        .run_batch_from_directory(
+            Path::new(&dir),
+            Path::new(&output_dir),
+            &default_pipe_shape.as_deref(),
+            &default_pipe_size.as_deref(),
+            recursive,
+            force.unwrap_or(false)
+        )
+        .map_err(|e| format!("Error during batch processing: {}", e))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn interim_report_options(
+    period: Option<String>,
+    period_days: Option<i64>,
+    incomplete_days: Option<String>,
+    include_empty_periods: Option<bool>,
+    pipe_shape: Option<String>,
+    pipe_size: Option<String>,
+    manning_n: Option<f64>,
+    pipe_gradient: Option<f64>,
+    decimals: Option<u32>,
+    include_grand_total: Option<bool>,
+    daily_aggregations: Option<Vec<String>>
+) -> Result<InterimReportOptions, String> {
+    let mut options = InterimReportOptions::default();
+    if let Some(p) = period {
+        options.period = ReportPeriod::from_str(&p, period_days).map_err(|e| e.to_string())?;
+    }
+    if let Some(d) = incomplete_days {
+        options.daily_completeness = DailyCompleteness::from_str(&d).map_err(|e| e.to_string())?;
+    }
+    options.include_empty_periods = include_empty_periods.unwrap_or(false);
+    options.pipe_shape = pipe_shape.filter(|s| !s.is_empty());
+    options.pipe_size = pipe_size.filter(|s| !s.is_empty());
+    options.manning_n = manning_n;
+    options.pipe_gradient = pipe_gradient;
+    options.decimals = decimals;
+    options.include_grand_total = include_grand_total.unwrap_or(true);
+    if let Some(ops) = daily_aggregations.filter(|ops| !ops.is_empty()) {
+        options.daily_aggregations = Some(
+            ops
+                .iter()
+                .map(|op| AggOp::from_str(op))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?
+        );
+    }
+    Ok(options)
+}
+
 #[tauri::command]
 pub async fn generate_interim_reports(
     state: State<'_, AppState>,
-    output_path: String
+    output_path: String,
+    period: Option<String>,
+    period_days: Option<i64>,
+    incomplete_days: Option<String>,
+    include_empty_periods: Option<bool>,
+    pipe_shape: Option<String>,
+    pipe_size: Option<String>,
+    manning_n: Option<f64>,
+    pipe_gradient: Option<f64>,
+    decimals: Option<u32>,
+    include_grand_total: Option<bool>,
+    daily_aggregations: Option<Vec<String>>
 ) -> Result<String, String> {
     let command_handler = state.command_handler
         .lock()
         .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
 
-    match command_handler.save_interim_reports_to_excel(&output_path) {
-        Ok(()) => Ok(format!("Interim reports saved successfully to {}", output_path)),
+    let options = interim_report_options(
+        period,
+        period_days,
+        incomplete_days,
+        include_empty_periods,
+        pipe_shape,
+        pipe_size,
+        manning_n,
+        pipe_gradient,
+        decimals,
+        include_grand_total,
+        daily_aggregations
+    )?;
+    match command_handler.save_interim_reports_to_excel(&output_path, &options) {
+        Ok(sha256) => {
+            let result =
+                json!({
+                "success": true,
+                "message": format!("Interim reports saved successfully to {}", output_path),
+                "outputPath": output_path,
+                "sha256": sha256,
+            });
+            Ok(result.to_string())
+        }
         Err(e) => Err(format!("Error generating interim reports: {}", e)),
     }
 }
 
+#[tauri::command]
+pub async fn interim_reports_json(
+    state: State<'_, AppState>,
+    period: Option<String>,
+    period_days: Option<i64>,
+    incomplete_days: Option<String>,
+    include_empty_periods: Option<bool>,
+    pipe_shape: Option<String>,
+    pipe_size: Option<String>,
+    manning_n: Option<f64>,
+    pipe_gradient: Option<f64>,
+    decimals: Option<u32>,
+    include_grand_total: Option<bool>,
+    daily_aggregations: Option<Vec<String>>
+) -> Result<String, String> {
+    let command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+
+    let options = interim_report_options(
+        period,
+        period_days,
+        incomplete_days,
+        include_empty_periods,
+        pipe_shape,
+        pipe_size,
+        manning_n,
+        pipe_gradient,
+        decimals,
+        include_grand_total,
+        daily_aggregations
+    )?;
+    command_handler.interim_reports_json(&options)
+}
+
+#[tauri::command]
+pub async fn save_combined_report(
+    state: State<'_, AppState>,
+    file_paths: Vec<String>,
+    output_path: String
+) -> Result<String, String> {
+    let command_handler = state.command_handler
+        .lock()
+        .map_err(|_| "Failed to acquire lock on CommandHandler".to_string())?;
+
+    command_handler
+        .save_combined_report(&file_paths, &output_path)
+        .map_err(|e| format!("Error generating combined report: {}", e))
+}
+
 #[tauri::command]
 pub async fn generate_rainfall_totals(
     state: State<'_, AppState>,
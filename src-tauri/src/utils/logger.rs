@@ -2,6 +2,8 @@ use chrono::Local;
 use log::{Level, LevelFilter, Metadata, Record, SetLoggerError};
 use serde::Serialize;
 use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufWriter, Write};
 use std::sync::Mutex;
 use tauri::Emitter;
 
@@ -147,6 +149,29 @@ pub fn set_frontend_logging(enabled: bool) {
     }
 }
 
+fn write_logs(path: &str, logs: &[LogMessage]) -> std::io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    writeln!(writer, "FDV Converter v{}", env!("CARGO_PKG_VERSION"))?;
+    writeln!(
+        writer,
+        "Exported: {}",
+        Local::now().format("%Y-%m-%d %H:%M:%S")
+    )?;
+    writeln!(writer)?;
+    for log in logs {
+        writeln!(writer, "[{}] {} - {}", log.timestamp, log.level, log.message)?;
+    }
+    writer.flush()
+}
+
+#[tauri::command]
+pub fn export_logs(path: String) -> Result<String, String> {
+    let logs = get_recent_logs();
+    write_logs(&path, &logs).map_err(|e| format!("Failed to export logs: {}", e))?;
+    log::info!("Exported {} log entries to {}", logs.len(), path);
+    Ok(format!("Logs exported successfully to {}", path))
+}
+
 #[tauri::command]
 pub fn clear_logs() {
     if let Some(logger) = LOGGER.lock().unwrap().as_ref() {